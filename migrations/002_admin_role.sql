@@ -0,0 +1,3 @@
+-- Platform administrators, see auth::Role::Admin
+
+ALTER TABLE utilisateur ADD COLUMN IF NOT EXISTS administrateur BOOLEAN NOT NULL DEFAULT FALSE;
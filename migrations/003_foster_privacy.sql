@@ -0,0 +1,5 @@
+-- Activity tracking for the retention job and anonymized foster profiles
+
+ALTER TABLE utilisateur ADD COLUMN IF NOT EXISTS derniere_connexion TIMESTAMP NOT NULL DEFAULT now();
+
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS anonymise_le TIMESTAMP;
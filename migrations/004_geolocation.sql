@@ -0,0 +1,9 @@
+-- Coordinates used by the distance-based search on animals and shelters
+
+ALTER TABLE association ADD COLUMN IF NOT EXISTS latitude DOUBLE PRECISION;
+ALTER TABLE association ADD COLUMN IF NOT EXISTS longitude DOUBLE PRECISION;
+
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS latitude DOUBLE PRECISION;
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS longitude DOUBLE PRECISION;
+
+CREATE INDEX IF NOT EXISTS association_coordinates_idx ON association (latitude, longitude);
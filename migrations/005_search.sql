@@ -0,0 +1,45 @@
+-- Full-text search index over animals, shelters and tags
+-- French stemming with accent folding, so that "male" matches "Mâle"
+
+CREATE EXTENSION IF NOT EXISTS unaccent;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_ts_config WHERE cfgname = 'french_unaccent') THEN
+        CREATE TEXT SEARCH CONFIGURATION french_unaccent (COPY = french);
+        ALTER TEXT SEARCH CONFIGURATION french_unaccent
+            ALTER MAPPING FOR hword, hword_part, word WITH unaccent, french_stem;
+    END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS search_document (
+    type_entite TEXT NOT NULL,
+    entite_id INTEGER NOT NULL,
+    titre TEXT NOT NULL,
+    contenu TEXT NOT NULL,
+    document TSVECTOR NOT NULL,
+    PRIMARY KEY (type_entite, entite_id)
+);
+
+CREATE INDEX IF NOT EXISTS search_document_idx ON search_document USING GIN (document);
+
+INSERT INTO search_document (type_entite, entite_id, titre, contenu, document)
+SELECT 'animal', id, nom, concat_ws(' ', race, couleur, description),
+    setweight(to_tsvector('french_unaccent', nom), 'A')
+    || setweight(to_tsvector('french_unaccent', concat_ws(' ', race, couleur)), 'B')
+    || setweight(to_tsvector('french_unaccent', description), 'C')
+FROM animal
+ON CONFLICT (type_entite, entite_id) DO NOTHING;
+
+INSERT INTO search_document (type_entite, entite_id, titre, contenu, document)
+SELECT 'association', id, nom, coalesce(description, ''),
+    setweight(to_tsvector('french_unaccent', nom), 'A')
+    || setweight(to_tsvector('french_unaccent', coalesce(description, '')), 'C')
+FROM association
+ON CONFLICT (type_entite, entite_id) DO NOTHING;
+
+INSERT INTO search_document (type_entite, entite_id, titre, contenu, document)
+SELECT 'tag', id, nom, nom, setweight(to_tsvector('french_unaccent', nom), 'A')
+FROM tag
+ON CONFLICT (type_entite, entite_id) DO NOTHING;
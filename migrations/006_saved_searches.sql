@@ -0,0 +1,43 @@
+-- Saved searches for fosters and the alerts they produce
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'frequence_alerte') THEN
+        CREATE TYPE frequence_alerte AS ENUM ('Immédiate', 'Quotidienne', 'Hebdomadaire');
+    END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS recherche_sauvegardee (
+    id SERIAL PRIMARY KEY,
+    famille_id INTEGER NOT NULL REFERENCES famille (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    nom TEXT NOT NULL,
+    espece_id INTEGER REFERENCES espece (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    sexe sexe,
+    age_min INTEGER,
+    age_max INTEGER,
+    rayon_km INTEGER,
+    frequence frequence_alerte NOT NULL DEFAULT 'Immédiate',
+    derniere_notification TIMESTAMP,
+    cree_le TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS recherche_sauvegardee_famille_idx ON recherche_sauvegardee (famille_id);
+
+CREATE TABLE IF NOT EXISTS recherche_tag (
+    recherche_id INTEGER NOT NULL REFERENCES recherche_sauvegardee (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tag (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    PRIMARY KEY (recherche_id, tag_id)
+);
+
+CREATE TABLE IF NOT EXISTS alerte (
+    id SERIAL PRIMARY KEY,
+    recherche_id INTEGER NOT NULL REFERENCES recherche_sauvegardee (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    famille_id INTEGER NOT NULL REFERENCES famille (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    animal_id INTEGER NOT NULL REFERENCES animal (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    cree_le TIMESTAMP NOT NULL DEFAULT now(),
+    publiee_le TIMESTAMP,
+    lue BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS alerte_famille_idx ON alerte (famille_id, publiee_le);
@@ -0,0 +1,40 @@
+-- Structured foster attributes and tag constraints used by compatibility matching
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'logement') THEN
+        CREATE TYPE logement AS ENUM ('Appartement', 'Maison');
+    END IF;
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'niveau_experience') THEN
+        CREATE TYPE niveau_experience AS ENUM ('Débutant', 'Intermédiaire', 'Expérimenté');
+    END IF;
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'contrainte_tag') THEN
+        CREATE TYPE contrainte_tag AS ENUM (
+            'Besoin de jardin',
+            'Pas en appartement',
+            'Pas avec des chats',
+            'Pas avec des chiens',
+            'Pas avec des enfants',
+            'Expérience requise'
+        );
+    END IF;
+END
+$$;
+
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS logement logement;
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS jardin BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS possede_chats BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS possede_chiens BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS enfants BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS experience niveau_experience;
+
+-- Fosters who described a garden keep it
+UPDATE famille SET jardin = TRUE WHERE terrain IS NOT NULL AND trim(terrain) <> '';
+
+CREATE TABLE IF NOT EXISTS famille_espece (
+    famille_id INTEGER NOT NULL REFERENCES famille (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    espece_id INTEGER NOT NULL REFERENCES espece (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    PRIMARY KEY (famille_id, espece_id)
+);
+
+ALTER TABLE tag ADD COLUMN IF NOT EXISTS contrainte contrainte_tag;
@@ -0,0 +1,17 @@
+-- Intake dates and status history used by the shelter dashboard
+-- Animals created before this migration get the migration date as intake date
+
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS cree_le TIMESTAMP NOT NULL DEFAULT now();
+
+CREATE TABLE IF NOT EXISTS animal_statut_historique (
+    id SERIAL PRIMARY KEY,
+    animal_id INTEGER NOT NULL REFERENCES animal (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    statut statut NOT NULL,
+    change_le TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS animal_statut_historique_animal_idx ON animal_statut_historique (animal_id, statut);
+
+INSERT INTO animal_statut_historique (animal_id, statut, change_le)
+SELECT id, statut, cree_le FROM animal a
+WHERE NOT EXISTS (SELECT 1 FROM animal_statut_historique h WHERE h.animal_id = a.id);
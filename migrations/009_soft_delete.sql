@@ -0,0 +1,10 @@
+-- Archived animals, shelters and fosters are hidden by the repositories
+-- and hard-deleted by the purge job once the archive retention period is over
+
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+ALTER TABLE association ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS animal_deleted_at_idx ON animal (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS association_deleted_at_idx ON association (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS famille_deleted_at_idx ON famille (deleted_at) WHERE deleted_at IS NOT NULL;
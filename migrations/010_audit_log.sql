@@ -0,0 +1,41 @@
+-- Append-only trail of mutations and logins
+-- Actor and entity ids are not foreign keys so entries outlive purged rows
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'action_audit') THEN
+        CREATE TYPE action_audit AS ENUM ('Création', 'Modification', 'Suppression', 'Restauration', 'Purge', 'Connexion', 'Connexion échouée');
+    END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    cree_le TIMESTAMP NOT NULL DEFAULT now(),
+    acteur_id INTEGER,
+    role TEXT,
+    action action_audit NOT NULL,
+    type_entite TEXT NOT NULL,
+    entite_id INTEGER,
+    association_id INTEGER,
+    diff JSONB NOT NULL DEFAULT '{}'::jsonb,
+    ip TEXT,
+    user_agent TEXT,
+    methode TEXT,
+    chemin TEXT
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_entite_idx ON audit_log (type_entite, entite_id);
+CREATE INDEX IF NOT EXISTS audit_log_association_idx ON audit_log (association_id, id) WHERE association_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS audit_log_acteur_idx ON audit_log (acteur_id, id);
+
+CREATE OR REPLACE FUNCTION audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_log_append_only ON audit_log;
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
@@ -0,0 +1,7 @@
+-- Row versions backing the ETag / If-Match optimistic concurrency checks,
+-- bumped by the repositories on every update
+
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE association ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE famille ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE demande ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,67 @@
+-- Countries are stored as ISO 3166-1 alpha-2 codes and phone numbers in E.164 form
+-- Country names of the supported countries are mapped to their code, in French or English,
+-- ignoring case and accents. Other values are kept and listed in `pays_a_verifier` for manual review.
+
+CREATE TEMPORARY TABLE nom_pays (nom TEXT PRIMARY KEY, code CHAR(2) NOT NULL);
+INSERT INTO nom_pays (nom, code) VALUES
+    ('fr', 'FR'), ('france', 'FR'), ('republique francaise', 'FR'),
+    ('be', 'BE'), ('belgique', 'BE'), ('belgium', 'BE'),
+    ('ch', 'CH'), ('suisse', 'CH'), ('switzerland', 'CH'),
+    ('lu', 'LU'), ('luxembourg', 'LU'),
+    ('mc', 'MC'), ('monaco', 'MC'),
+    ('de', 'DE'), ('allemagne', 'DE'), ('germany', 'DE'),
+    ('at', 'AT'), ('autriche', 'AT'), ('austria', 'AT'),
+    ('es', 'ES'), ('espagne', 'ES'), ('spain', 'ES'),
+    ('it', 'IT'), ('italie', 'IT'), ('italy', 'IT'),
+    ('pt', 'PT'), ('portugal', 'PT'),
+    ('nl', 'NL'), ('pays-bas', 'NL'), ('pays bas', 'NL'), ('netherlands', 'NL'),
+    ('gb', 'GB'), ('uk', 'GB'), ('royaume-uni', 'GB'), ('royaume uni', 'GB'), ('united kingdom', 'GB');
+
+UPDATE association SET pays = nom_pays.code
+FROM nom_pays
+WHERE lower(unaccent(trim(association.pays))) = nom_pays.nom AND association.pays <> nom_pays.code;
+UPDATE famille SET pays = nom_pays.code
+FROM nom_pays
+WHERE lower(unaccent(trim(famille.pays))) = nom_pays.nom AND famille.pays <> nom_pays.code;
+
+DROP TABLE nom_pays;
+
+CREATE OR REPLACE VIEW pays_a_verifier AS
+SELECT 'association' AS source, id, pays FROM association WHERE pays !~ '^[A-Z]{2}$'
+UNION ALL
+SELECT 'famille' AS source, id, pays FROM famille WHERE pays <> '' AND pays !~ '^[A-Z]{2}$';
+
+UPDATE association
+SET telephone = '+33' || substr(regexp_replace(telephone, '[^0-9]', '', 'g'), 2)
+WHERE pays = 'FR' AND regexp_replace(telephone, '[^0-9]', '', 'g') ~ '^0[1-9][0-9]{8}$';
+UPDATE association
+SET telephone = '+' || regexp_replace(telephone, '[^0-9]', '', 'g')
+WHERE telephone ~ '^\+' AND telephone !~ '^\+[0-9]+$';
+
+UPDATE famille
+SET telephone = '+33' || substr(regexp_replace(telephone, '[^0-9]', '', 'g'), 2)
+WHERE pays = 'FR' AND regexp_replace(telephone, '[^0-9]', '', 'g') ~ '^0[1-9][0-9]{8}$';
+UPDATE famille
+SET telephone = '+' || regexp_replace(telephone, '[^0-9]', '', 'g')
+WHERE telephone ~ '^\+' AND telephone !~ '^\+[0-9]+$';
+
+-- Anonymized fosters keep an empty country. The constraints are only added once no address
+-- awaits review: fix the rows listed in `pays_a_verifier`, then run this migration again.
+DO $$
+DECLARE
+    remaining INTEGER;
+BEGIN
+    SELECT count(*) INTO remaining FROM pays_a_verifier;
+    IF remaining > 0 THEN
+        RAISE WARNING '% addresses have an unknown country, see the pays_a_verifier view', remaining;
+        RETURN;
+    END IF;
+
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'association_pays_iso') THEN
+        ALTER TABLE association ADD CONSTRAINT association_pays_iso CHECK (pays ~ '^[A-Z]{2}$');
+    END IF;
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'famille_pays_iso') THEN
+        ALTER TABLE famille ADD CONSTRAINT famille_pays_iso CHECK (pays = '' OR pays ~ '^[A-Z]{2}$');
+    END IF;
+END
+$$;
@@ -0,0 +1,17 @@
+-- Shelters publish animals once their SIRET was found in the company registry or approved by an admin
+-- SIRETs are stored without the spaces grouping their digits
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'statut_verification') THEN
+        CREATE TYPE statut_verification AS ENUM ('En attente', 'Vérifiée', 'Refusée');
+    END IF;
+END
+$$;
+
+ALTER TABLE association ADD COLUMN IF NOT EXISTS verification statut_verification NOT NULL DEFAULT 'En attente';
+ALTER TABLE association ADD COLUMN IF NOT EXISTS verifiee_le TIMESTAMP;
+
+UPDATE association SET siret = regexp_replace(siret, '\s', '', 'g') WHERE siret ~ '\s';
+
+CREATE INDEX IF NOT EXISTS association_verification_idx ON association (verification) WHERE verification <> 'Vérifiée';
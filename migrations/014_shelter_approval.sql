@@ -0,0 +1,31 @@
+-- New shelters wait for an admin to approve their registration, based on the documents they upload
+-- Shelters registered before this migration are approved, notifications are kept in an inbox per account
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'statut_inscription') THEN
+        CREATE TYPE statut_inscription AS ENUM ('En attente', 'Approuvée', 'Rejetée');
+    END IF;
+END
+$$;
+
+ALTER TABLE association ADD COLUMN IF NOT EXISTS inscription statut_inscription NOT NULL DEFAULT 'Approuvée';
+ALTER TABLE association ALTER COLUMN inscription SET DEFAULT 'En attente';
+ALTER TABLE association ADD COLUMN IF NOT EXISTS motif_rejet TEXT;
+ALTER TABLE association ADD COLUMN IF NOT EXISTS examinee_le TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS association_inscription_idx ON association (inscription) WHERE inscription = 'En attente';
+
+-- Supporting documents are stored outside of the publicly served images
+ALTER TABLE media ADD COLUMN IF NOT EXISTS justificatif BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS notification (
+    id SERIAL PRIMARY KEY,
+    utilisateur_id INTEGER NOT NULL REFERENCES utilisateur (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    titre TEXT NOT NULL,
+    message TEXT NOT NULL,
+    cree_le TIMESTAMP NOT NULL DEFAULT now(),
+    lue BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS notification_utilisateur_idx ON notification (utilisateur_id, cree_le);
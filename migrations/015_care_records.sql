@@ -0,0 +1,53 @@
+-- Care records of animals: health sheet, vaccinations, medications and vet visits
+-- Entries can be added by the shelter or by the foster hosting the animal, vet documents are stored as media
+
+CREATE TABLE IF NOT EXISTS fiche_soins (
+    animal_id INTEGER PRIMARY KEY REFERENCES animal (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    sterilise BOOLEAN NOT NULL DEFAULT FALSE,
+    numero_puce TEXT,
+    regime_alimentaire TEXT,
+    modifiee_le TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS vaccination (
+    id SERIAL PRIMARY KEY,
+    animal_id INTEGER NOT NULL REFERENCES animal (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    vaccin TEXT NOT NULL,
+    administre_le DATE NOT NULL,
+    rappel_le DATE,
+    veterinaire TEXT,
+    ajoute_par INTEGER REFERENCES utilisateur (id) ON UPDATE CASCADE ON DELETE SET NULL,
+    cree_le TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS vaccination_animal_idx ON vaccination (animal_id, administre_le);
+
+CREATE TABLE IF NOT EXISTS traitement (
+    id SERIAL PRIMARY KEY,
+    animal_id INTEGER NOT NULL REFERENCES animal (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    medicament TEXT NOT NULL,
+    posologie TEXT NOT NULL,
+    debut DATE NOT NULL,
+    fin DATE,
+    frequence_jours INTEGER NOT NULL DEFAULT 1 CHECK (frequence_jours > 0),
+    ajoute_par INTEGER REFERENCES utilisateur (id) ON UPDATE CASCADE ON DELETE SET NULL,
+    cree_le TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS traitement_animal_idx ON traitement (animal_id, debut);
+
+CREATE TABLE IF NOT EXISTS visite_veterinaire (
+    id SERIAL PRIMARY KEY,
+    animal_id INTEGER NOT NULL REFERENCES animal (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    prevue_le TIMESTAMP NOT NULL,
+    motif TEXT NOT NULL,
+    veterinaire TEXT,
+    compte_rendu TEXT,
+    ajoute_par INTEGER REFERENCES utilisateur (id) ON UPDATE CASCADE ON DELETE SET NULL,
+    cree_le TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS visite_veterinaire_animal_idx ON visite_veterinaire (animal_id, prevue_le);
+
+-- Visit documents are not attached to the animal so they stay out of its public pictures
+ALTER TABLE media ADD COLUMN IF NOT EXISTS visite_id INTEGER REFERENCES visite_veterinaire (id) ON UPDATE CASCADE ON DELETE CASCADE;
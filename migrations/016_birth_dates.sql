@@ -0,0 +1,30 @@
+-- Animals store a birth date instead of an age that goes stale, with how precisely the date is known
+-- Existing ages were entered when the animal was created, so the birth year is estimated from that date
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'precision_naissance') THEN
+        CREATE TYPE precision_naissance AS ENUM ('Exacte', 'Mois', 'Année');
+    END IF;
+END
+$$;
+
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS date_naissance DATE;
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS precision_naissance precision_naissance NOT NULL DEFAULT 'Exacte';
+
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM information_schema.columns WHERE table_name = 'animal' AND column_name = 'age') THEN
+        UPDATE animal
+        SET date_naissance = (cree_le - make_interval(years => age))::date,
+            precision_naissance = 'Année'
+        WHERE date_naissance IS NULL;
+
+        ALTER TABLE animal DROP COLUMN age;
+    END IF;
+END
+$$;
+
+ALTER TABLE animal ALTER COLUMN date_naissance SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS animal_date_naissance_idx ON animal (date_naissance);
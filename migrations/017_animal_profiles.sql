@@ -0,0 +1,40 @@
+-- Structured animal profiles: size, energy, what the animal gets along with, its needs and a longer story
+-- Unknown compatibilities stay NULL, weights are kept as a history of weigh-ins
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'taille') THEN
+        CREATE TYPE taille AS ENUM ('Petite', 'Moyenne', 'Grande', 'Très grande');
+    END IF;
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'niveau_energie') THEN
+        CREATE TYPE niveau_energie AS ENUM ('Calme', 'Modéré', 'Actif', 'Très actif');
+    END IF;
+END
+$$;
+
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS taille taille;
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS niveau_energie niveau_energie;
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS ok_enfants BOOLEAN;
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS ok_chiens BOOLEAN;
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS ok_chats BOOLEAN;
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS propre BOOLEAN;
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS besoins_speciaux BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS puce BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS histoire TEXT;
+
+CREATE TABLE IF NOT EXISTS pesee (
+    id SERIAL PRIMARY KEY,
+    animal_id INTEGER NOT NULL REFERENCES animal (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    poids_kg DOUBLE PRECISION NOT NULL CHECK (poids_kg > 0),
+    pesee_le DATE NOT NULL,
+    ajoute_par INTEGER REFERENCES utilisateur (id) ON UPDATE CASCADE ON DELETE SET NULL,
+    cree_le TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS pesee_animal_idx ON pesee (animal_id, pesee_le);
+
+-- Saved searches can require a size and compatibilities
+ALTER TABLE recherche_sauvegardee ADD COLUMN IF NOT EXISTS taille taille;
+ALTER TABLE recherche_sauvegardee ADD COLUMN IF NOT EXISTS ok_enfants BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE recherche_sauvegardee ADD COLUMN IF NOT EXISTS ok_chiens BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE recherche_sauvegardee ADD COLUMN IF NOT EXISTS ok_chats BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,21 @@
+-- Breeds are reference data linked to a species, with the other spellings they are known by
+-- Animals point to a breed or are mixed or of unknown breed, the race column keeps the breed name for display and search
+
+CREATE TABLE IF NOT EXISTS race (
+    id SERIAL PRIMARY KEY,
+    espece_id INTEGER NOT NULL REFERENCES espece (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    nom TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS race_espece_nom_idx ON race (espece_id, lower(nom));
+
+CREATE TABLE IF NOT EXISTS race_alias (
+    race_id INTEGER NOT NULL REFERENCES race (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    alias TEXT NOT NULL,
+    PRIMARY KEY (race_id, alias)
+);
+
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS race_id INTEGER REFERENCES race (id) ON UPDATE CASCADE ON DELETE SET NULL;
+ALTER TABLE animal ADD COLUMN IF NOT EXISTS croise BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS animal_race_idx ON animal (race_id);
@@ -0,0 +1,5 @@
+-- Lets the limiter sweep forget lockout entries that stopped failing
+
+ALTER TABLE login_lockout ADD COLUMN IF NOT EXISTS updated_at BIGINT NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS rate_limit_bucket_updated_at_idx ON rate_limit_bucket (updated_at);
@@ -0,0 +1,26 @@
+-- Redacts the personal data written to the audit log before entries were redacted on write
+-- The append-only trigger is lifted for the duration of the rewrite only
+
+ALTER TABLE audit_log DISABLE TRIGGER audit_log_append_only;
+
+UPDATE audit_log a
+SET diff = a.diff || redacted.fields
+FROM (
+    SELECT l.id, jsonb_object_agg(
+        f.key,
+        jsonb_build_object(
+            'avant', CASE WHEN f.value -> 'avant' = 'null'::jsonb THEN 'null'::jsonb ELSE '"***"'::jsonb END,
+            'apres', CASE WHEN f.value -> 'apres' = 'null'::jsonb THEN 'null'::jsonb ELSE '"***"'::jsonb END
+        )
+    ) AS fields
+    FROM audit_log l, jsonb_each(l.diff) f
+    WHERE (l.type_entite = 'famille' AND f.key IN ('prenom', 'nom', 'telephone', 'rue', 'commune', 'code_postal', 'latitude', 'longitude'))
+       OR (l.type_entite = 'association' AND f.key IN ('responsable', 'telephone'))
+       OR (l.type_entite = 'utilisateur' AND f.key = 'email')
+    GROUP BY l.id
+) redacted
+WHERE a.id = redacted.id;
+
+UPDATE audit_log SET diff = '{}'::jsonb WHERE action = 'Connexion échouée';
+
+ALTER TABLE audit_log ENABLE TRIGGER audit_log_append_only;
@@ -0,0 +1,3 @@
+-- Token generation of each account; bumping it invalidates the JWTs issued before
+
+ALTER TABLE utilisateur ADD COLUMN IF NOT EXISTS version_jeton INTEGER NOT NULL DEFAULT 0;
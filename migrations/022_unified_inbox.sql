@@ -0,0 +1,28 @@
+-- Admin notifications join the saved-search alerts in a single inbox per account
+-- Alerts now belong to an account, and only search alerts reference a search, a foster and an animal
+
+ALTER TABLE alerte ADD COLUMN IF NOT EXISTS utilisateur_id INTEGER REFERENCES utilisateur (id) ON UPDATE CASCADE ON DELETE CASCADE;
+ALTER TABLE alerte ADD COLUMN IF NOT EXISTS titre TEXT;
+ALTER TABLE alerte ADD COLUMN IF NOT EXISTS message TEXT;
+
+UPDATE alerte SET utilisateur_id = famille.utilisateur_id
+FROM famille
+WHERE famille.id = alerte.famille_id AND alerte.utilisateur_id IS NULL;
+
+ALTER TABLE alerte ALTER COLUMN utilisateur_id SET NOT NULL;
+ALTER TABLE alerte ALTER COLUMN recherche_id DROP NOT NULL;
+ALTER TABLE alerte ALTER COLUMN famille_id DROP NOT NULL;
+ALTER TABLE alerte ALTER COLUMN animal_id DROP NOT NULL;
+
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM information_schema.tables WHERE table_name = 'notification') THEN
+        INSERT INTO alerte (utilisateur_id, titre, message, cree_le, publiee_le, lue)
+        SELECT utilisateur_id, titre, message, cree_le, cree_le, lue FROM notification;
+
+        DROP TABLE notification;
+    END IF;
+END
+$$;
+
+CREATE INDEX IF NOT EXISTS alerte_utilisateur_idx ON alerte (utilisateur_id, publiee_le);
@@ -0,0 +1,6 @@
+-- The microchip flag of an animal is derived from the number recorded on its health sheet
+
+UPDATE animal SET puce = EXISTS (
+    SELECT 1 FROM fiche_soins
+    WHERE fiche_soins.animal_id = animal.id AND fiche_soins.numero_puce IS NOT NULL
+);
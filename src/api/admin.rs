@@ -0,0 +1,308 @@
+use actix_files::NamedFile;
+use actix_web::{HttpResponse, web};
+use chrono::Utc;
+use log::info;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{DbConn, IntoActiveModel};
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::api::espece::{create_breed, create_species, delete_breed, delete_species, update_breed, update_species};
+use crate::api::etag::update_error;
+use crate::api::tag::patch_tag;
+use crate::auth::CustomError;
+use crate::database::models::AlerteActiveModel;
+use crate::database::models::sea_orm_active_enums::{StatutInscription, StatutVerification};
+use crate::database::repositories::{AlerteRepository, AnimalRepository, AssociationRepository, AuditRepository, FamilleRepository, MediaRepository};
+use crate::dto::AuditQuery;
+use crate::registry::verified_at;
+use crate::validators::common_validators::process_json_validation;
+
+pub fn configure_protected(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/animaux/{id}/restaurer")
+            .post(restore_animal)
+        )
+        .service(web::resource("/associations/{id}/restaurer")
+            .post(restore_shelter)
+        )
+        .service(web::resource("/associations/verification")
+            .get(get_unverified_shelters)
+        )
+        .service(web::resource("/associations/{id}/verification")
+            .post(review_shelter)
+        )
+        .service(web::resource("/associations/inscriptions")
+            .get(get_pending_registrations)
+        )
+        .service(web::resource("/associations/{id}/inscription")
+            .post(review_registration)
+        )
+        .service(web::resource("/associations/{id}/justificatifs")
+            .get(get_shelter_documents)
+        )
+        .service(web::resource("/justificatifs/{id}")
+            .get(download_document)
+        )
+        .service(web::resource("/familles/{id}/restaurer")
+            .post(restore_foster)
+        )
+        .service(web::resource("/audit")
+            .get(get_audit_log)
+        )
+        .service(web::resource("/tags/{id}")
+            .patch(patch_tag)
+        )
+        .service(web::resource("/especes")
+            .post(create_species)
+        )
+        .service(web::resource("/especes/{id}")
+            .put(update_species)
+            .delete(delete_species)
+        )
+        .service(web::resource("/especes/{id}/races")
+            .post(create_breed)
+        )
+        .service(web::resource("/races/{id}")
+            .put(update_breed)
+            .delete(delete_breed)
+        );
+}
+
+pub async fn restore_animal(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
+    let repo = AnimalRepository::new(db.get_ref());
+
+    let animal = repo
+        .find_archived_by_id(animal_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    // An animal cannot come back while its shelter is archived.
+    AssociationRepository::new(db.get_ref())
+        .find_by_id(animal.association_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::BadClientData)?;
+
+    let restored_animal = repo
+        .restore(animal_id)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?
+        .ok_or(CustomError::NotFound)?;
+
+    info!("Animal with ID {} restored", animal_id);
+    Ok(HttpResponse::Ok().json(restored_animal))
+}
+
+pub async fn restore_shelter(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, CustomError> {
+    let shelter_id = path.into_inner();
+
+    let restored_shelter = AssociationRepository::new(db.get_ref())
+        .restore(shelter_id)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?
+        .ok_or(CustomError::NotFound)?;
+
+    info!("Shelter with ID {} restored", shelter_id);
+    Ok(HttpResponse::Ok().json(restored_shelter))
+}
+
+/// Shelters still pending or refused by the company registry, awaiting a manual review.
+pub async fn get_unverified_shelters(db: web::Data<DbConn>) -> Result<HttpResponse, CustomError> {
+    let shelters = AssociationRepository::new(db.get_ref())
+        .find_by_verification(vec![StatutVerification::EnAttente, StatutVerification::Refusée])
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok().json(shelters))
+}
+
+#[derive(Deserialize)]
+pub struct VerificationReview {
+    pub verifiee: bool,
+}
+
+pub async fn review_shelter(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    review: web::Json<VerificationReview>,
+) -> Result<HttpResponse, CustomError> {
+    let shelter_id = path.into_inner();
+    let repo = AssociationRepository::new(db.get_ref());
+
+    let shelter = repo
+        .find_model_by_id(shelter_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    let verification = if review.verifiee {
+        StatutVerification::Vérifiée
+    } else {
+        StatutVerification::Refusée
+    };
+
+    let mut shelter_active_model = shelter.into_active_model();
+    shelter_active_model.verifiee_le = Set(verified_at(&verification));
+    shelter_active_model.verification = Set(verification);
+
+    let reviewed_shelter = repo
+        .update_model(shelter_active_model)
+        .await
+        .map_err(update_error)?;
+
+    info!("Shelter with ID {} reviewed, verified: {}", shelter_id, review.verifiee);
+    Ok(HttpResponse::Ok().json(reviewed_shelter))
+}
+
+pub async fn get_pending_registrations(db: web::Data<DbConn>) -> Result<HttpResponse, CustomError> {
+    let shelters = AssociationRepository::new(db.get_ref())
+        .find_by_inscription(StatutInscription::EnAttente)
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok().json(shelters))
+}
+
+#[derive(Deserialize, Validate)]
+pub struct RegistrationReview {
+    pub approuvee: bool,
+    #[validate(length(
+        min = 3,
+        max = 500,
+        message = "The reason must be between 3 and 500 characters"
+    ))]
+    pub motif: Option<String>,
+}
+
+pub async fn review_registration(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    review: web::Json<RegistrationReview>,
+) -> Result<HttpResponse, CustomError> {
+    process_json_validation(&review)?;
+    let shelter_id = path.into_inner();
+    let review = review.into_inner();
+
+    if !review.approuvee && review.motif.is_none() {
+        return Err(CustomError::ValidationError {
+            error_messages: "motif: A reason is required to reject a registration".to_string(),
+        });
+    }
+
+    let repo = AssociationRepository::new(db.get_ref());
+    let shelter = repo
+        .find_model_by_id(shelter_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    let (inscription, motif_rejet, titre, message) = if review.approuvee {
+        (
+            StatutInscription::Approuvée,
+            None,
+            "Inscription validée".to_string(),
+            format!("L'inscription de {} a été validée, vous pouvez désormais gérer vos animaux et vos demandes.", shelter.nom),
+        )
+    } else {
+        let motif = review.motif.unwrap_or_default();
+        (
+            StatutInscription::Rejetée,
+            Some(motif.clone()),
+            "Inscription refusée".to_string(),
+            format!("L'inscription de {} a été refusée : {}", shelter.nom, motif),
+        )
+    };
+
+    let utilisateur_id = shelter.utilisateur_id;
+    let mut shelter_active_model = shelter.into_active_model();
+    shelter_active_model.inscription = Set(inscription);
+    shelter_active_model.motif_rejet = Set(motif_rejet);
+    shelter_active_model.examinee_le = Set(Some(Utc::now().naive_utc()));
+
+    let reviewed_shelter = repo
+        .update_model(shelter_active_model)
+        .await
+        .map_err(update_error)?;
+
+    let now = Utc::now().naive_utc();
+    let notice_model = AlerteActiveModel {
+        utilisateur_id: Set(utilisateur_id),
+        titre: Set(Some(titre)),
+        message: Set(Some(message)),
+        cree_le: Set(now),
+        publiee_le: Set(Some(now)),
+        lue: Set(false),
+        ..Default::default()
+    };
+    AlerteRepository::new(db.get_ref())
+        .create(notice_model)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+
+    info!("Registration of shelter with ID {} reviewed, approved: {}", shelter_id, review.approuvee);
+    Ok(HttpResponse::Ok().json(reviewed_shelter))
+}
+
+pub async fn get_shelter_documents(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, CustomError> {
+    let documents = MediaRepository::new(db.get_ref())
+        .find_documents_by_association(path.into_inner())
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok().json(documents))
+}
+
+pub async fn download_document(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+) -> Result<NamedFile, CustomError> {
+    let document = MediaRepository::new(db.get_ref())
+        .find_by_id(path.into_inner())
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .filter(|media| media.justificatif)
+        .ok_or(CustomError::NotFound)?;
+
+    NamedFile::open_async(format!("./static{}", document.url))
+        .await
+        .map_err(|_e| CustomError::NotFound)
+}
+
+pub async fn restore_foster(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, CustomError> {
+    let foster_id = path.into_inner();
+
+    let restored_foster = FamilleRepository::new(db.get_ref())
+        .restore(foster_id)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?
+        .ok_or(CustomError::NotFound)?;
+
+    info!("Foster with ID {} restored", foster_id);
+    Ok(HttpResponse::Ok().json(restored_foster))
+}
+
+pub async fn get_audit_log(
+    db: web::Data<DbConn>,
+    query: web::Query<AuditQuery>,
+) -> Result<HttpResponse, CustomError> {
+    let entries = AuditRepository::new(db.get_ref())
+        .find(query.into_inner().into_filter(None))
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok().json(entries))
+}
@@ -1,16 +1,22 @@
-use actix_web::{Error, HttpMessage as _, HttpRequest, HttpResponse, web};
-use chrono::{Duration, Local};
+use actix_web::{Error, HttpRequest, HttpResponse, web};
+use chrono::{Duration, Local, NaiveDate};
 use log::{info, warn};
-use sea_orm::{DbConn};
+use std::collections::HashMap;
+use sea_orm::{DbConn, TransactionTrait};
 use validator::Validate;
 
 use serde::{Deserialize, Serialize};
 
-use crate::auth::CustomError;
-use crate::database::models::{AnimalActiveModel, AnimalTagActiveModel, DemandeActiveModel};
-use crate::database::models::sea_orm_active_enums::{Sexe, Statut, StatutDemande};
-use crate::database::repositories::{AnimalRepository, AnimalTagRepository, DemandeRepository, FamilleRepository};
-use crate::validators::common_validators::{process_json_validation};
+use crate::api::etag::conditional_json;
+use crate::api::merge_patch::MergePatch;
+use crate::auth::{AuthenticatedUser, CustomError, Policy};
+use crate::database::models::{AnimalActiveModel, AnimalActiveModelEx, AnimalModelEx, AnimalTagActiveModel, DemandeActiveModel, PeseeActiveModel};
+use crate::database::models::sea_orm_active_enums::{NiveauEnergie, PrecisionNaissance, Sexe, Statut, StatutDemande, StatutInscription, StatutVerification, Taille};
+use crate::database::repositories::{AnimalRepository, AnimalTagRepository, AssociationRepository, DemandeRepository, FamilleRepository, RaceRepository};
+use crate::dto::{AgeQuery, AnimalResponse, Audience, DemandeResponse, FosterResponse, Nearby, ProfileQuery, RankQuery, Ranked};
+use crate::geo::{Coordinates, NearQuery};
+use crate::services::{AlertService, matching_service};
+use crate::validators::common_validators::{process_json_validation, validate_birth_date};
 
 use sea_orm::ActiveValue::Set;
 
@@ -35,6 +41,12 @@ pub fn configure_protected_req(cfg: &mut web::ServiceConfig) {
         );
 }
 
+pub fn configure_protected_matches(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("")
+            .get(get_matches)
+        );
+}
+
 pub fn configure_protected_foster(cfg: &mut web::ServiceConfig) {
     cfg.service(web::resource("")
             .post(request_animal)
@@ -49,24 +61,20 @@ pub struct AnimalCreate {
         message = "Name must be between 3 and 50 characters"
     ))]
     pub nom_animal: String,
-    #[validate(length(
-        min = 3,
-        max = 50,
-        message = "This must be between 3 and 50 characters"
-    ))]
-    pub race_animal: Option<String>,
+    /// Breed of the chosen species, unknown when missing.
+    pub race_id: Option<i32>,
+    #[serde(default)]
+    pub croise_animal: bool,
     #[validate(length(
         min = 3,
         max = 50,
         message = "Colour name must be between 3 and 50 characters"
     ))]
     pub couleur_animal: String,
-    #[validate(range(
-        min = 1,
-        max = 100,
-        message = "Age must be realistic"
-    ))]
-    pub age_animal: i32,
+    #[validate(custom(function = validate_birth_date))]
+    pub date_naissance_animal: NaiveDate,
+    /// Exact when missing.
+    pub precision_naissance_animal: Option<PrecisionNaissance>,
     pub sexe_animal : Sexe,
     #[validate(length(
         min = 3,
@@ -74,23 +82,170 @@ pub struct AnimalCreate {
         message = "Please describe this animal using between 3 and 50 characters"
     ))]
     pub description_animal: String,
+    pub taille_animal: Option<Taille>,
+    pub niveau_energie_animal: Option<NiveauEnergie>,
+    pub ok_enfants_animal: Option<bool>,
+    pub ok_chiens_animal: Option<bool>,
+    pub ok_chats_animal: Option<bool>,
+    pub propre_animal: Option<bool>,
+    #[serde(default)]
+    pub besoins_speciaux_animal: bool,
+    #[validate(length(
+        max = 10000,
+        message = "The story must be at most 10000 characters"
+    ))]
+    pub histoire_animal: Option<String>,
+    /// Recorded as the first weigh-in.
+    #[validate(range(
+        min = 0.01,
+        max = 150.0,
+        message = "Weight must be realistic"
+    ))]
+    pub poids_animal: Option<f64>,
     pub espece_animal: String,
     pub association_id: i32,
     pub tags: Vec<i32>
 }
 
-pub async fn get_animals(db: web::Data<DbConn>) -> Result<HttpResponse, Error> {
+#[derive(Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct AnimalPatch {
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "Name must be between 3 and 50 characters"
+    ))]
+    pub nom: String,
+    pub race_id: Option<i32>,
+    pub croise: bool,
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "Colour name must be between 3 and 50 characters"
+    ))]
+    pub couleur: String,
+    #[validate(custom(function = validate_birth_date))]
+    pub date_naissance: NaiveDate,
+    pub precision_naissance: PrecisionNaissance,
+    pub sexe: Sexe,
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "Please describe this animal using between 3 and 50 characters"
+    ))]
+    pub description: String,
+    pub taille: Option<Taille>,
+    pub niveau_energie: Option<NiveauEnergie>,
+    pub ok_enfants: Option<bool>,
+    pub ok_chiens: Option<bool>,
+    pub ok_chats: Option<bool>,
+    pub propre: Option<bool>,
+    pub besoins_speciaux: bool,
+    #[validate(length(
+        max = 10000,
+        message = "The story must be at most 10000 characters"
+    ))]
+    pub histoire: Option<String>,
+    pub espece_id: i32,
+}
+
+/// Status and foster are changed through the dedicated status endpoint.
+impl MergePatch for AnimalPatch {
+    type Model = AnimalModelEx;
+    type ActiveModel = AnimalActiveModelEx;
+
+    fn from_model(animal: &AnimalModelEx) -> Self {
+        Self {
+            nom: animal.nom.clone(),
+            race_id: animal.race_id,
+            croise: animal.croise,
+            couleur: animal.couleur.clone(),
+            date_naissance: animal.date_naissance,
+            precision_naissance: animal.precision_naissance.clone(),
+            sexe: animal.sexe.clone(),
+            description: animal.description.clone(),
+            taille: animal.taille.clone(),
+            niveau_energie: animal.niveau_energie.clone(),
+            ok_enfants: animal.ok_enfants,
+            ok_chiens: animal.ok_chiens,
+            ok_chats: animal.ok_chats,
+            propre: animal.propre,
+            besoins_speciaux: animal.besoins_speciaux,
+            histoire: animal.histoire.clone(),
+            espece_id: animal.espece_id,
+        }
+    }
+
+    fn apply(self, animal: &mut AnimalActiveModelEx) {
+        animal.nom.set_if_not_equals(self.nom);
+        animal.race_id.set_if_not_equals(self.race_id);
+        animal.croise.set_if_not_equals(self.croise);
+        animal.couleur.set_if_not_equals(self.couleur);
+        animal.date_naissance.set_if_not_equals(self.date_naissance);
+        animal.precision_naissance.set_if_not_equals(self.precision_naissance);
+        animal.sexe.set_if_not_equals(self.sexe);
+        animal.description.set_if_not_equals(self.description);
+        animal.taille.set_if_not_equals(self.taille);
+        animal.niveau_energie.set_if_not_equals(self.niveau_energie);
+        animal.ok_enfants.set_if_not_equals(self.ok_enfants);
+        animal.ok_chiens.set_if_not_equals(self.ok_chiens);
+        animal.ok_chats.set_if_not_equals(self.ok_chats);
+        animal.propre.set_if_not_equals(self.propre);
+        animal.besoins_speciaux.set_if_not_equals(self.besoins_speciaux);
+        animal.histoire.set_if_not_equals(self.histoire);
+        animal.espece_id.set_if_not_equals(self.espece_id);
+    }
+}
+
+pub async fn get_animals(
+    db: web::Data<DbConn>,
+    query: web::Query<NearQuery>,
+    age: web::Query<AgeQuery>,
+    profile: web::Query<ProfileQuery>,
+) -> Result<HttpResponse, Error> {
     let repo = AnimalRepository::new(db.get_ref());
+    let filter = age.condition()?.add(profile.condition());
+
+    let Some(area) = query.search_area()? else {
+        let animals = repo
+            .find_all(filter)
+            .await
+            .map_err(|_e| CustomError::NotFound)?;
+
+        return Ok(HttpResponse::Ok().json(AnimalResponse::from_list(animals, None)));
+    };
+
+    let shelters = AssociationRepository::new(db.get_ref())
+        .find_within(area.bounding_box())
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    let distances: HashMap<i32, f64> = shelters
+        .iter()
+        .filter_map(|shelter| {
+            let coordinates = Coordinates::from_columns(shelter.latitude, shelter.longitude);
+            area.distance_to(coordinates).map(|distance| (shelter.id, distance))
+        })
+        .collect();
 
     let animals = repo
-        .find_all()
+        .find_by_shelters(distances.keys().copied().collect(), filter)
         .await
         .map_err(|_e| CustomError::NotFound)?;
 
-    Ok(HttpResponse::Ok().json(animals))
+    let hits = animals
+        .into_iter()
+        .map(|animal| Nearby {
+            distance_km: distances[&animal.association_id],
+            item: AnimalResponse::new(animal, None),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(Nearby::sorted(hits)))
 }
 
 pub async fn get_animal(
+    req: HttpRequest,
     db: web::Data<DbConn>,
     path: web::Path<i32>
 ) -> Result<HttpResponse, CustomError> {
@@ -103,16 +258,20 @@ pub async fn get_animal(
         .map_err(|_e| CustomError::NotFound)?;
 
     match animal {
-        Some(animal) => Ok(HttpResponse::Ok().json(animal)),
-        None => Err(CustomError::NotFound),
+        Some(animal) if animal.refuge.as_ref().is_some_and(|shelter| shelter.is_published()) => {
+            conditional_json(&req, animal.version, &AnimalResponse::new(animal, None))
+        }
+        _ => Err(CustomError::NotFound),
     }
 }
 
 pub async fn get_requests(
     db: web::Data<DbConn>,
-    path: web::Path<i32>
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
 ) -> Result<HttpResponse, Error> {
     let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
     let repo = DemandeRepository::new(db.get_ref());
 
     let requests = repo
@@ -120,30 +279,57 @@ pub async fn get_requests(
         .await
         .map_err(|_e| CustomError::NotFound)?;
 
-    Ok(HttpResponse::Ok().json(requests))
+    Ok(HttpResponse::Ok().json(DemandeResponse::from_list(requests, Some(&current_user))))
 }
 
-pub async fn request_animal(
+pub async fn get_matches(
     db: web::Data<DbConn>,
     path: web::Path<i32>,
-    req: HttpRequest,
+    current_user: AuthenticatedUser,
+    query: web::Query<RankQuery>,
 ) -> Result<HttpResponse, CustomError> {
     let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+
+    let animal = AnimalRepository::new(db.get_ref())
+        .find_by_id(animal_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
 
-    let user_id = req.extensions_mut().get::<i32>().cloned().unwrap();
+    let fosters = FamilleRepository::new(db.get_ref())
+        .find_match_candidates(animal.espece_id, &matching_service::constraints(&animal))
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    let candidates = fosters
+        .into_iter()
+        .map(|foster| Ranked {
+            compatibilite: matching_service::score(&foster, &animal),
+            item: FosterResponse::new(foster.into(), Audience::Public),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(Ranked::best(candidates, query.limit())))
+}
+
+pub async fn request_animal(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
 
-    let repo = FamilleRepository::new(db.get_ref());
+    let foster_id = current_user.foster_id()?;
 
-    let foster = repo
-        .find_by_user_id(user_id)
+    let animal = AnimalRepository::new(db.get_ref())
+        .find_by_id(animal_id)
         .await
         .map_err(|_e| CustomError::InternalError)?;
-    if foster.is_none() {
+    if !animal.is_some_and(|animal| animal.refuge.as_ref().is_some_and(|shelter| shelter.is_published())) {
         return Err(CustomError::NotFound);
     }
 
-    let foster_id = foster.as_ref().unwrap().id;
-
     info!(
         "Attempting to create request for animal with ID: {}",
         animal_id
@@ -191,35 +377,89 @@ pub async fn request_animal(
     Ok(HttpResponse::Created().json(request_response))
 }
 
+/// Name of the breed, kept with the animal for display and search.
+/// The breed has to belong to the animal's species.
+pub async fn breed_name(db: &DbConn, espece_id: i32, race_id: Option<i32>) -> Result<Option<String>, CustomError> {
+    let Some(race_id) = race_id else {
+        return Ok(None);
+    };
+
+    let breed = RaceRepository::new(db)
+        .find_by_id(race_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .filter(|breed| breed.espece_id == espece_id)
+        .ok_or(CustomError::ValidationError {
+            error_messages: "race_id: This breed does not belong to the chosen species".to_string(),
+        })?;
+
+    Ok(Some(breed.nom))
+}
+
+/// Shelters publish animals once their SIRET is verified and their registration approved.
+pub async fn ensure_can_publish(db: &DbConn, shelter_id: i32) -> Result<(), CustomError> {
+    let shelter = AssociationRepository::new(db)
+        .find_model_by_id(shelter_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    if shelter.verification != StatutVerification::Vérifiée {
+        return Err(CustomError::UnverifiedShelter);
+    }
+    if shelter.inscription != StatutInscription::Approuvée {
+        return Err(CustomError::PendingApproval);
+    }
+
+    Ok(())
+}
+
 pub async fn create_animal(
     db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
     json_animal: web::Json<AnimalCreate>,
 ) -> Result<HttpResponse, CustomError> {
     process_json_validation(&json_animal)?;
+    current_user.authorize(db.get_ref(), Policy::OwnsShelter(json_animal.association_id)).await?;
+    ensure_can_publish(db.get_ref(), json_animal.association_id).await?;
 
     warn!(
         "Attempting to create animal with name: {}",
         json_animal.nom_animal
     );
 
-    let repo = AnimalRepository::new(db.get_ref());
-
     let animal = json_animal.into_inner();
+    let espece_id = animal.espece_animal.parse::<i32>().map_err(|_e| CustomError::BadClientData)?;
+    let race = breed_name(db.get_ref(), espece_id, animal.race_id).await?;
 
     let animal_model = AnimalActiveModel {
         nom: Set(animal.nom_animal),
-        race: Set(animal.race_animal),
+        race: Set(race),
+        race_id: Set(animal.race_id),
+        croise: Set(animal.croise_animal),
         couleur: Set(animal.couleur_animal),
-        age: Set(animal.age_animal),
+        date_naissance: Set(animal.date_naissance_animal),
+        precision_naissance: Set(animal.precision_naissance_animal.unwrap_or(PrecisionNaissance::Exacte)),
         sexe : Set(animal.sexe_animal),
         description: Set(animal.description_animal),
+        taille: Set(animal.taille_animal),
+        niveau_energie: Set(animal.niveau_energie_animal),
+        ok_enfants: Set(animal.ok_enfants_animal),
+        ok_chiens: Set(animal.ok_chiens_animal),
+        ok_chats: Set(animal.ok_chats_animal),
+        propre: Set(animal.propre_animal),
+        besoins_speciaux: Set(animal.besoins_speciaux_animal),
+        histoire: Set(animal.histoire_animal),
         statut: Set(Statut::EnRefuge),
         association_id: Set(animal.association_id),
-        espece_id: Set(animal.espece_animal.parse::<i32>().unwrap()),
+        espece_id: Set(espece_id),
         ..Default::default()
     };
 
-    let created_animal = repo
+    let txn = db.begin().await.map_err(|_e| CustomError::CreationError)?;
+    let txn_repo = AnimalRepository::new(&txn);
+
+    let created_animal = txn_repo
         .create(animal_model)
         .await
         .map_err(|_e| CustomError::CreationError)?;
@@ -233,12 +473,33 @@ pub async fn create_animal(
                 tag_id: Set(tag),
             };
 
-            AnimalTagRepository::new(db.get_ref())
+            AnimalTagRepository::new(&txn)
                 .create(animal_tag_model)
                 .await
                 .map_err(|_e| CustomError::CreationError)?;
         }
     }
 
+    if let Some(poids_kg) = animal.poids_animal {
+        let weight_model = PeseeActiveModel {
+            animal_id: Set(created_animal.id),
+            poids_kg: Set(poids_kg),
+            pesee_le: Set(created_animal.cree_le.date()),
+            ajoute_par: Set(Some(current_user.user_id)),
+            ..Default::default()
+        };
+
+        txn_repo
+            .add_weight(weight_model)
+            .await
+            .map_err(|_e| CustomError::CreationError)?;
+    }
+
+    txn.commit().await.map_err(|_e| CustomError::CreationError)?;
+
+    if let Err(e) = AlertService::new(db.get_ref()).animal_available(created_animal.id).await {
+        warn!("Could not create alerts for animal with ID {}: {}", created_animal.id, e);
+    }
+
     Ok(HttpResponse::Created().json(created_animal))
 }
\ No newline at end of file
@@ -1,15 +1,30 @@
-use actix_web::{Error, HttpMessage as _, HttpRequest, HttpResponse, web};
+use actix_web::http::header::ContentDisposition;
+use actix_web::{Error, HttpRequest, HttpResponse, web};
+use chrono::{Datelike, Local, Months, NaiveDate, NaiveTime};
 use log::{info, warn};
 use sea_orm::DbConn;
 use validator::Validate;
 
 use serde::{Deserialize, Serialize};
-
-use crate::auth::{CustomError, hash_password};
-use crate::database::models::{AssociationActiveModel, AssociationActiveModelEx, DemandeActiveModelEx, UtilisateurActiveModel};
-use crate::database::repositories::{AnimalRepository, AssociationRepository, DemandeRepository, UtilisateurRepository};
+use serde_json::Value;
+
+use crate::api::etag::{check_if_match, conditional_json, tagged_json, update_error};
+use crate::api::media::{get_documents, upload_document};
+use crate::api::merge_patch::{MergePatch, merge_patch};
+use crate::auth::{AuthenticatedUser, CustomError, Policy, hash_password};
+use crate::database::models::{AnimalActiveModelEx, AssociationActiveModel, AssociationActiveModelEx, AssociationModelEx, DemandeActiveModelEx, UtilisateurActiveModel};
+use crate::api::animal::{AnimalPatch, breed_name, ensure_can_publish};
+use crate::database::repositories::{AnimalRepository, AssociationRepository, AuditRepository, DemandeRepository, EspeceRepository, StatsRepository, UtilisateurRepository};
+use crate::dto::{AnimalResponse, AuditQuery, DemandeResponse, Nearby, ShelterStats};
+use crate::geo::{Coordinates, GEOCODER, Geocoder, NearQuery};
+use crate::registry::{CompanyRegistry, verification_status, verified_at};
+use crate::limiter::RateLimiter;
+use crate::middleware::RoleGuard;
+use crate::services::{AccountService, AlertService, AnimalCsvService};
+use crate::database::models::sea_orm_active_enums::Statut;
 use crate::database::models::sea_orm_active_enums::StatutDemande::*;
-use crate::validators::common_validators::{process_json_validation, validate_phone, validate_siret, validate_zipcode};
+use crate::validators::common_validators::{process_json_validation, validate_contact, validate_country, validate_siret};
+use crate::validators::siret::normalize_siret;
 
 use sea_orm::ActiveValue::Set;
 
@@ -42,7 +57,28 @@ pub fn configure_protected_requested(cfg: &mut web::ServiceConfig) {
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(web::resource("")
-            .post(update_shelter)
+            .post(patch_shelter)
+            .patch(patch_shelter)
+        )
+        .service(web::resource("/stats")
+            .get(get_stats)
+        )
+        .service(web::resource("/animaux/import")
+            .wrap(RoleGuard::shelter())
+            .post(import_animals)
+        )
+        .service(web::resource("/export/animaux")
+            .get(export_animals)
+        )
+        .service(web::resource("/export/demandes")
+            .get(export_requests)
+        )
+        .service(web::resource("/audit")
+            .get(get_audit_log)
+        )
+        .service(web::resource("/justificatifs")
+            .get(get_documents)
+            .post(upload_document)
         )
         .service(web::resource("/{id}")
             .get(get_shelter)
@@ -51,15 +87,24 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .delete(delete_shelter)
         )
         .service(web::resource("/animaux/{id}")
+            .wrap(RoleGuard::shelter())
             .get(get_resident_details)
+            .patch(patch_resident)
+            .delete(archive_resident)
+        )
+        .service(web::resource("/animaux/{id}/statut")
+            .wrap(RoleGuard::shelter())
+            .post(update_resident_status)
         )
         .service(web::resource("/demandes/{id}")
             .get(get_request_details)
         )
         .service(web::resource("/demandes/{id}/accept")
+            .wrap(RoleGuard::shelter())
             .post(accept_request)
         )
         .service(web::resource("/demandes/{id}/deny")
+            .wrap(RoleGuard::shelter())
             .post(deny_request)
         )
        ;
@@ -91,17 +136,11 @@ pub struct AssociationCreate {
         message = "Your city's name must be between 2 and 58 characters"
     ))]
     pub commune: String,
-    #[validate(custom(function = validate_zipcode))]
     pub code_postal: String,
-    #[validate(length(
-        min = 4,
-        max = 42,
-        message = "Country name must be between 4 and 42 characters"
-    ))]
+    #[validate(custom(function = validate_country))]
     pub pays: String,
     #[validate(custom(function = validate_siret))]
     pub siret: String,
-    #[validate(custom(function = validate_phone))]
     pub telephone: String,
     #[validate(url)]
     pub site: Option<String>,
@@ -128,65 +167,110 @@ pub struct AssociationCreate {
 }
 
 #[derive(Deserialize, Serialize, Validate)]
-pub struct AssociationUpdate {
+#[serde(deny_unknown_fields)]
+pub struct ShelterPatch {
     #[validate(length(
         min = 2,
         max = 50,
         message = "Your shelter's name must usually be between 2 and 50 characters"
     ))]
-    pub nom: Option<String>,
+    pub nom: String,
     #[validate(length(
         min = 2,
         max = 50,
         message = "Their full name must usually be between 2 and 50 characters"
     ))]
-    pub responsable: Option<String>,
+    pub responsable: String,
     #[validate(length(
         min = 2,
         max = 50,
         message = "Your address must usually be between 2 and 50 characters"
     ))]
-    pub rue: Option<String>,
+    pub rue: String,
     #[validate(length(
         min = 2,
         max = 58,
         message = "Your city's name must be between 2 and 58 characters"
     ))]
-    pub commune: Option<String>,
-    #[validate(custom(function = validate_zipcode))]
-    pub code_postal: Option<String>,
-    #[validate(length(
-        min = 4,
-        max = 42,
-        message = "Country name must be between 4 and 42 characters"
-    ))]
-    pub pays: Option<String>,
+    pub commune: String,
+    pub code_postal: String,
+    #[validate(custom(function = validate_country))]
+    pub pays: String,
     #[validate(custom(function = validate_siret))]
-    pub siret: Option<String>,
-    #[validate(custom(function = validate_phone))]
-    pub telephone: Option<String>,
+    pub siret: String,
+    pub telephone: String,
     #[validate(url)]
-    pub site: Option<Option<String>>,
+    pub site: Option<String>,
     #[validate(length(
         min = 3,
         max = 200,
         message = "Please describe your shelter using between 3 and 200 characters"
     ))]
-    pub description: Option<Option<String>>,
+    pub description: Option<String>,
 }
 
-pub async fn get_shelters(db: web::Data<DbConn>) -> Result<HttpResponse, Error> {
+impl MergePatch for ShelterPatch {
+    type Model = AssociationModelEx;
+    type ActiveModel = AssociationActiveModelEx;
+
+    fn from_model(shelter: &AssociationModelEx) -> Self {
+        Self {
+            nom: shelter.nom.clone(),
+            responsable: shelter.responsable.clone(),
+            rue: shelter.rue.clone(),
+            commune: shelter.commune.clone(),
+            code_postal: shelter.code_postal.clone(),
+            pays: shelter.pays.clone(),
+            siret: shelter.siret.clone(),
+            telephone: shelter.telephone.clone(),
+            site: shelter.site.clone(),
+            description: shelter.description.clone(),
+        }
+    }
+
+    fn apply(self, shelter: &mut AssociationActiveModelEx) {
+        shelter.nom.set_if_not_equals(self.nom);
+        shelter.responsable.set_if_not_equals(self.responsable);
+        shelter.rue.set_if_not_equals(self.rue);
+        shelter.commune.set_if_not_equals(self.commune);
+        shelter.code_postal.set_if_not_equals(self.code_postal);
+        shelter.pays.set_if_not_equals(self.pays);
+        shelter.siret.set_if_not_equals(self.siret);
+        shelter.telephone.set_if_not_equals(self.telephone);
+        shelter.site.set_if_not_equals(self.site);
+        shelter.description.set_if_not_equals(self.description);
+    }
+}
+
+pub async fn get_shelters(db: web::Data<DbConn>, query: web::Query<NearQuery>) -> Result<HttpResponse, Error> {
     let repo = AssociationRepository::new(db.get_ref());
 
+    let Some(area) = query.search_area()? else {
+        let shelters = repo
+            .find_all()
+            .await
+            .map_err(|_e| CustomError::NotFound)?;
+
+        return Ok(HttpResponse::Ok().json(shelters));
+    };
+
     let shelters = repo
-        .find_all()
+        .find_within(area.bounding_box())
         .await
         .map_err(|_e| CustomError::NotFound)?;
 
-    Ok(HttpResponse::Ok().json(shelters))
+    let hits = shelters
+        .into_iter()
+        .filter_map(|shelter| {
+            let coordinates = Coordinates::from_columns(shelter.latitude, shelter.longitude);
+            area.distance_to(coordinates).map(|distance_km| Nearby { item: shelter, distance_km })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(Nearby::sorted(hits)))
 }
 
-pub async fn get_shelter(db: web::Data<DbConn>, path: web::Path<i32>) -> Result<HttpResponse, CustomError> {
+pub async fn get_shelter(req: HttpRequest, db: web::Data<DbConn>, path: web::Path<i32>) -> Result<HttpResponse, CustomError> {
     let shelter_id = path.into_inner();
     let repo = AssociationRepository::new(db.get_ref());
 
@@ -196,16 +280,22 @@ pub async fn get_shelter(db: web::Data<DbConn>, path: web::Path<i32>) -> Result<
         .map_err(|_e| CustomError::NotFound)?;
     
     match shelter {
-        Some(shelter) => Ok(HttpResponse::Ok().json(shelter)),
-        None => Err(CustomError::NotFound)
+        Some(shelter) if shelter.is_published() => conditional_json(&req, shelter.version, &shelter),
+        _ => Err(CustomError::NotFound)
     }
 }
 
 pub async fn create_shelter(
     db: web::Data<DbConn>,
+    limiter: web::Data<RateLimiter>,
+    registry: web::Data<dyn CompanyRegistry>,
+    req: HttpRequest,
     json_shelter: web::Json<AssociationCreate>,
 ) -> Result<HttpResponse, CustomError> {
     process_json_validation(&json_shelter)?;
+    let contact = validate_contact(&json_shelter.pays, &json_shelter.code_postal, &json_shelter.telephone)?;
+
+    limiter.check_registration(&limiter.client_ip(&req)).await?;
 
     info!(
         "Attempting to create shelter with name: {}",
@@ -241,17 +331,26 @@ pub async fn create_shelter(
 
     let repo = AssociationRepository::new(db.get_ref());
 
+    let coordinates = GEOCODER.geocode(&contact.code_postal, &shelter.commune, &contact.pays);
+
+    let siret = normalize_siret(&shelter.siret);
+    let verification = verification_status(registry.get_ref(), &siret).await;
+
     let shelter_model = AssociationActiveModel {
         nom: Set(shelter.nom),
         responsable: Set(shelter.responsable),
         rue: Set(shelter.rue),
         commune: Set(shelter.commune),
-        code_postal: Set(shelter.code_postal),
-        pays: Set(shelter.pays),
-        siret: Set(shelter.siret),
-        telephone: Set(shelter.telephone),
+        code_postal: Set(contact.code_postal),
+        pays: Set(contact.pays),
+        siret: Set(siret),
+        telephone: Set(contact.telephone),
         site: Set(shelter.site),
         description: Set(shelter.description),
+        latitude: Set(coordinates.map(|c| c.latitude)),
+        longitude: Set(coordinates.map(|c| c.longitude)),
+        verifiee_le: Set(verified_at(&verification)),
+        verification: Set(verification),
         utilisateur_id: Set(created_user.id),
         ..Default::default()
     };
@@ -265,149 +364,388 @@ pub async fn create_shelter(
     Ok(HttpResponse::Created().json(created_shelter))
 }
 
-pub async fn update_shelter(
-    db: web::Data<DbConn>,
+pub async fn patch_shelter(
     req: HttpRequest,
-    json_shelter: web::Json<AssociationUpdate>,
+    db: web::Data<DbConn>,
+    registry: web::Data<dyn CompanyRegistry>,
+    current_user: AuthenticatedUser,
+    patch: web::Json<Value>,
 ) -> Result<HttpResponse, CustomError> {
-    process_json_validation(&json_shelter)?;
-
-    let user_id = req.extensions_mut().get::<i32>().cloned().unwrap();
-
     let repo = AssociationRepository::new(db.get_ref());
 
     let shelter_data = repo
-        .find_by_user_id(user_id)
+        .find_by_user_id(current_user.user_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+    check_if_match(&req, shelter_data.version)?;
+
+    let mut shelter: ShelterPatch = merge_patch(&shelter_data, patch.into_inner())?;
+    let contact = validate_contact(&shelter.pays, &shelter.code_postal, &shelter.telephone)?;
+    shelter.pays = contact.pays;
+    shelter.code_postal = contact.code_postal;
+    shelter.telephone = contact.telephone;
+    shelter.siret = normalize_siret(&shelter.siret);
+
+    // A new SIRET has to be verified again before the shelter publishes other animals.
+    let verification = if shelter.siret != shelter_data.siret {
+        Some(verification_status(registry.get_ref(), &shelter.siret).await)
+    } else {
+        None
+    };
+
+    let address_changed = shelter.code_postal != shelter_data.code_postal
+        || shelter.commune != shelter_data.commune
+        || shelter.pays != shelter_data.pays;
+    let coordinates = address_changed
+        .then(|| GEOCODER.geocode(&shelter.code_postal, &shelter.commune, &shelter.pays))
+        .flatten();
+
+    let mut shelter_active_model: AssociationActiveModelEx = shelter_data.into();
+    shelter.apply(&mut shelter_active_model);
+    if address_changed {
+        shelter_active_model.latitude = Set(coordinates.map(|c| c.latitude));
+        shelter_active_model.longitude = Set(coordinates.map(|c| c.longitude));
+    }
+    if let Some(verification) = verification {
+        shelter_active_model.verifiee_le = Set(verified_at(&verification));
+        shelter_active_model.verification = Set(verification);
+    }
+
+    let updated_shelter = repo
+        .update(shelter_active_model)
+        .await
+        .map_err(update_error)?;
+
+    info!("Shelter succesfully updated");
+    tagged_json(updated_shelter.version, &updated_shelter)
+}
+
+pub async fn delete_shelter(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    AccountService::new(db.get_ref())
+        .delete_account(current_user.user_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+const DEFAULT_STATS_MONTHS: u32 = 12;
+const MAX_STATS_MONTHS: u32 = 36;
+const DEFAULT_UNREQUESTED_DAYS: i32 = 30;
+
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    pub debut: Option<NaiveDate>,
+    pub fin: Option<NaiveDate>,
+    pub sans_demande_jours: Option<i32>,
+}
+
+pub async fn get_stats(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+    query: web::Query<StatsQuery>,
+) -> Result<HttpResponse, CustomError> {
+    let shelter_id = current_user.shelter_id()?;
+
+    let fin = query.fin.unwrap_or_else(|| Local::now().date_naive());
+    let debut = match query.debut {
+        Some(debut) => debut,
+        None => fin
+            .with_day(1)
+            .and_then(|first_day| first_day.checked_sub_months(Months::new(DEFAULT_STATS_MONTHS - 1)))
+            .ok_or(CustomError::BadClientData)?,
+    };
+    let max_debut = fin
+        .checked_sub_months(Months::new(MAX_STATS_MONTHS))
+        .ok_or(CustomError::BadClientData)?;
+    if debut > fin || debut < max_debut {
+        return Err(CustomError::BadClientData);
+    }
+
+    let sans_demande_jours = query.sans_demande_jours.unwrap_or(DEFAULT_UNREQUESTED_DAYS);
+    if !(1..=365).contains(&sans_demande_jours) {
+        return Err(CustomError::BadClientData);
+    }
+
+    let repo = StatsRepository::new(db.get_ref());
+    let stats = async {
+        Ok::<_, sea_orm::DbErr>(ShelterStats {
+            animaux_par_statut: repo.count_by_statut(shelter_id).await?,
+            animaux_par_espece: repo.count_by_espece(shelter_id).await?,
+            demandes: repo.count_requests(shelter_id).await?,
+            delais: repo.placement_delays(shelter_id).await?,
+            periode_debut: debut,
+            periode_fin: fin,
+            par_mois: repo
+                .monthly(shelter_id, debut.and_time(NaiveTime::MIN), fin.and_time(NaiveTime::MIN))
+                .await?,
+            sans_demande_jours,
+            sans_demande: repo.find_unrequested(shelter_id, sans_demande_jours).await?,
+        })
+    }
+    .await
+    .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+pub async fn get_audit_log(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+    query: web::Query<AuditQuery>,
+) -> Result<HttpResponse, CustomError> {
+    let shelter_id = current_user.shelter_id()?;
+
+    let entries = AuditRepository::new(db.get_ref())
+        .find(query.into_inner().into_filter(Some(shelter_id)))
         .await
         .map_err(|_e| CustomError::InternalError)?;
-    if shelter_data.is_none() {
-        return Err(CustomError::NotFound);
+
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+pub async fn import_animals(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse, CustomError> {
+    let shelter_id = current_user.shelter_id()?;
+    if body.is_empty() {
+        return Err(CustomError::BadClientData);
     }
+    ensure_can_publish(db.get_ref(), shelter_id).await?;
 
-    match shelter_data {
-        Some(shelter_data) => {
-            let mut shelter_active_model: AssociationActiveModelEx = shelter_data.into();
-
-            let shelter = json_shelter.into_inner();
-
-            if let Some(nom) = shelter.nom {
-                shelter_active_model.nom = Set(nom);
-            }
-            if let Some(responsable) = shelter.responsable {
-                shelter_active_model.responsable = Set(responsable);
-            }
-            if let Some(rue) = shelter.rue {
-                shelter_active_model.rue= Set(rue);
-            }
-            if let Some(commune) = shelter.commune {
-                shelter_active_model.commune = Set(commune);
-            }
-            if let Some(code_postal) = shelter.code_postal {
-                shelter_active_model.code_postal = Set(code_postal);
-            }
-            if let Some(pays) = shelter.pays {
-                shelter_active_model.pays = Set(pays);
-            }
-            if let Some(siret) = shelter.siret {
-                shelter_active_model.siret = Set(siret);
-            }
-            if let Some(telephone) = shelter.telephone {
-                shelter_active_model.telephone = Set(telephone);
-            }
-            if let Some(site) = shelter.site {
-                shelter_active_model.site = Set(site);
-            }
-            if let Some(description) = shelter.description {
-                shelter_active_model.description = Set(description);
-            }
-
-            let updated_shelter = repo
-                .update(shelter_active_model)
-                .await
-                .map_err(|_e| CustomError::UpdateError)?;
+    let report = AnimalCsvService::new(db.get_ref())
+        .import(shelter_id, &body, query.dry_run)
+        .await?;
 
-            info!("Shelter succesfully updated");
-            Ok(HttpResponse::Ok().json(updated_shelter))
+    if !report.erreurs.is_empty() {
+        return Ok(HttpResponse::UnprocessableEntity().json(report));
+    }
+    if report.animaux.is_empty() {
+        return Ok(HttpResponse::Ok().json(report));
+    }
+
+    info!("{} animals imported for shelter with ID {}", report.animaux.len(), shelter_id);
+
+    let alerts = AlertService::new(db.get_ref());
+    for animal in &report.animaux {
+        if let Err(e) = alerts.animal_available(animal.id).await {
+            warn!("Could not create alerts for animal with ID {}: {}", animal.id, e);
         }
-        None => Err(CustomError::NotFound),
     }
+
+    Ok(HttpResponse::Created().json(report))
 }
 
-pub async fn delete_shelter(
+fn csv_attachment(filename: &str, data: Vec<u8>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(ContentDisposition::attachment(filename))
+        .body(data)
+}
+
+pub async fn export_animals(
     db: web::Data<DbConn>,
-    req: HttpRequest,
+    current_user: AuthenticatedUser,
 ) -> Result<HttpResponse, CustomError> {
+    let shelter_id = current_user.shelter_id()?;
 
-    let user_id = req.extensions_mut().get::<i32>().cloned().unwrap();
+    let data = AnimalCsvService::new(db.get_ref())
+        .export_animals(shelter_id)
+        .await?;
 
-    let user_repo = UtilisateurRepository::new(db.get_ref());
+    Ok(csv_attachment("animaux.csv", data))
+}
+
+pub async fn export_requests(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let shelter_id = current_user.shelter_id()?;
+
+    let data = AnimalCsvService::new(db.get_ref())
+        .export_requests(shelter_id)
+        .await?;
+
+    Ok(csv_attachment("demandes.csv", data))
+}
+
+pub async fn get_resident_details(
+    req: HttpRequest,
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+    let repo = AnimalRepository::new(db.get_ref());
 
-    let user = user_repo
-        .find_by_id(user_id)
+    let animal = repo
+        .find_by_id(animal_id)
         .await
-        .map_err(|_e| CustomError::InternalError)?;
-    if user.is_none() {
-        return Err(CustomError::NotFound);
+        .map_err(|_e| CustomError::NotFound)?;
+
+    match animal {
+        Some(animal) => conditional_json(&req, animal.version, &AnimalResponse::new(animal, Some(&current_user))),
+        None => Err(CustomError::NotFound),
     }
+}
 
-    let repo = AssociationRepository::new(db.get_ref());
+pub async fn patch_resident(
+    req: HttpRequest,
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+    patch: web::Json<Value>,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+    let repo = AnimalRepository::new(db.get_ref());
 
-    let shelter = repo
-        .find_by_user_id(user_id)
+    let animal_data = repo
+        .find_by_id(animal_id)
         .await
-        .map_err(|_e| CustomError::InternalError)?;
-    if shelter.is_none() {
-        return Err(CustomError::NotFound);
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+    ensure_can_publish(db.get_ref(), animal_data.association_id).await?;
+    check_if_match(&req, animal_data.version)?;
+
+    let animal: AnimalPatch = merge_patch(&animal_data, patch.into_inner())?;
+
+    if animal.espece_id != animal_data.espece_id {
+        EspeceRepository::new(db.get_ref())
+            .find_by_id(animal.espece_id)
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .ok_or(CustomError::BadClientData)?;
     }
-    if !shelter.as_ref().unwrap().pensionnaires.is_empty() {
-        return Err(CustomError::ShelteredError);
+
+    // Animals entered before breeds existed keep their free-text breed until one is chosen.
+    let breed_changed = animal.race_id != animal_data.race_id
+        || (animal.race_id.is_some() && animal.espece_id != animal_data.espece_id);
+    let race = if breed_changed {
+        Some(breed_name(db.get_ref(), animal.espece_id, animal.race_id).await?)
+    } else {
+        None
+    };
+
+    let mut animal_active_model: AnimalActiveModelEx = animal_data.into();
+    animal.apply(&mut animal_active_model);
+    if let Some(race) = race {
+        animal_active_model.race.set_if_not_equals(race);
     }
 
-    let shelter_id = shelter.as_ref().unwrap().id;
-    info!("Attempting to delete shelter with ID: {}", shelter_id);
+    let updated_animal = repo
+        .update(animal_active_model)
+        .await
+        .map_err(update_error)?;
+
+    info!("Animal with ID {} updated", animal_id);
+    tagged_json(updated_animal.version, &AnimalResponse::new(updated_animal, Some(&current_user)))
+}
+
+pub async fn archive_resident(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+    let repo = AnimalRepository::new(db.get_ref());
 
-    let delete_result = repo
-        .delete(shelter_id)
+    let animal = repo
+        .find_model_by_id(animal_id)
         .await
-        .map_err(|_e| CustomError::DeletionError)?;
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+    if animal.statut == Statut::Accueilli {
+        return Err(CustomError::BadClientData);
+    }
 
-    let delete_user = user_repo
-        .delete(user_id)
+    let archive_result = repo
+        .delete(animal_id)
         .await
         .map_err(|_e| CustomError::DeletionError)?;
+    if archive_result.rows_affected == 0 {
+        return Err(CustomError::DeletionError);
+    }
 
+    info!("Animal with ID {} archived", animal_id);
+    Ok(HttpResponse::NoContent().finish())
+}
 
-    if delete_result.rows_affected > 0  && delete_user.rows_affected > 0 {
-        info!("Shelter with ID {} successfully deleted", shelter_id);
-        info!("User with ID {} also successfully deleted", user_id);
-        Ok(HttpResponse::NoContent().finish())
-    } else {
-        warn!("Shelter with ID {} was not deleted (0 rows affected)", shelter_id);
-        warn!("User with ID {} was not deleted (0 rows affected)", user_id);
-        Err(CustomError::InternalError)
-    }
+#[derive(Deserialize)]
+pub struct AnimalStatutUpdate {
+    pub statut: Statut,
 }
 
-pub async fn get_resident_details(
+pub async fn update_resident_status(
+    req: HttpRequest,
     db: web::Data<DbConn>,
-    path: web::Path<i32>
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+    json_statut: web::Json<AnimalStatutUpdate>,
 ) -> Result<HttpResponse, CustomError> {
     let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
     let repo = AnimalRepository::new(db.get_ref());
 
     let animal = repo
         .find_by_id(animal_id)
         .await
-        .map_err(|_e| CustomError::NotFound)?;
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+    ensure_can_publish(db.get_ref(), animal.association_id).await?;
+    check_if_match(&req, animal.version)?;
 
-    match animal {
-        Some(animal) => Ok(HttpResponse::Ok().json(animal)),
-        None => Err(CustomError::NotFound),
+    let statut = json_statut.into_inner().statut;
+    let back_in_shelter = statut == Statut::EnRefuge && animal.statut != Statut::EnRefuge;
+
+    let status_changed = statut != animal.statut;
+
+    let mut animal_active_model: AnimalActiveModelEx = animal.into();
+    if statut == Statut::EnRefuge {
+        animal_active_model.famille_id = Set(None);
+    }
+    if status_changed {
+        animal_active_model.statut = Set(statut);
+    }
+
+    let updated_animal = repo
+        .update(animal_active_model)
+        .await
+        .map_err(update_error)?;
+
+    info!("Status of animal with ID {} updated", animal_id);
+
+    if back_in_shelter
+        && let Err(e) = AlertService::new(db.get_ref()).animal_available(animal_id).await
+    {
+        warn!("Could not create alerts for animal with ID {}: {}", animal_id, e);
     }
+
+    tagged_json(updated_animal.version, &AnimalResponse::new(updated_animal, Some(&current_user)))
 }
 
-pub async fn get_request_details(db: web::Data<DbConn>, path: web::Path<i32>) -> Result<HttpResponse, CustomError> {
+pub async fn get_request_details(
+    req: HttpRequest,
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
     let request_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsRequestedAnimal(request_id)).await?;
     let repo = DemandeRepository::new(db.get_ref());
 
     let request = repo
@@ -416,17 +754,24 @@ pub async fn get_request_details(db: web::Data<DbConn>, path: web::Path<i32>) ->
         .map_err(|_e| CustomError::NotFound)?;
 
     match request {
-        Some(request) => Ok(HttpResponse::Ok().json(request)),
+        Some(request) => conditional_json(
+            &req,
+            request.version,
+            &DemandeResponse::new(request, Some(&current_user), current_user.association_id),
+        ),
         None => Err(CustomError::NotFound),
     }
 }
 
 pub async fn accept_request(
+    req: HttpRequest,
     db: web::Data<DbConn>,
     path: web::Path<i32>,
+    current_user: AuthenticatedUser,
 ) -> Result<HttpResponse, CustomError> {
 
     let request_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsRequestedAnimal(request_id)).await?;
 
     info!("Attempting to accept request with ID: {}", request_id);
 
@@ -439,6 +784,7 @@ pub async fn accept_request(
 
     match request_data {
         Some(request_data) => {
+            check_if_match(&req, request_data.version)?;
             let mut request_active_model: DemandeActiveModelEx = request_data.into();
 
             request_active_model.statut_demande = Set(Validée);
@@ -446,21 +792,27 @@ pub async fn accept_request(
             let updated_request = repo
                 .update(request_active_model)
                 .await
-                .map_err(|_e| CustomError::UpdateError)?;
+                .map_err(update_error)?;
 
             info!("Accepted request with ID {}", request_id);
-            Ok(HttpResponse::Ok().json(updated_request))
+            tagged_json(
+                updated_request.version,
+                &DemandeResponse::new(updated_request, Some(&current_user), current_user.association_id),
+            )
         }
         None => Err(CustomError::NotFound),
     }
 }
 
 pub async fn deny_request(
+    req: HttpRequest,
     db: web::Data<DbConn>,
     path: web::Path<i32>,
+    current_user: AuthenticatedUser,
 ) -> Result<HttpResponse, CustomError> {
 
     let request_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsRequestedAnimal(request_id)).await?;
 
     info!("Attempting to deny request with ID: {}", request_id);
 
@@ -473,6 +825,7 @@ pub async fn deny_request(
 
     match request_data {
         Some(request_data) => {
+            check_if_match(&req, request_data.version)?;
             let mut request_active_model: DemandeActiveModelEx = request_data.into();
 
             request_active_model.statut_demande = Set(Refusée);
@@ -480,10 +833,13 @@ pub async fn deny_request(
             let updated_request = repo
                 .update(request_active_model)
                 .await
-                .map_err(|_e| CustomError::UpdateError)?;
+                .map_err(update_error)?;
 
             info!("Denied request with ID {}", request_id);
-            Ok(HttpResponse::Ok().json(updated_request))
+            tagged_json(
+                updated_request.version,
+                &DemandeResponse::new(updated_request, Some(&current_user), current_user.association_id),
+            )
         }
         None => Err(CustomError::NotFound),
     }
@@ -492,8 +848,10 @@ pub async fn deny_request(
 pub async fn get_fostered(
     db: web::Data<DbConn>,
     path: web::Path<i32>,
+    current_user: AuthenticatedUser,
 ) -> Result<HttpResponse, Error> {
     let shelter_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsShelter(shelter_id)).await?;
 
     let repo = AnimalRepository::new(db.get_ref());
     info!("Attempting to find currently fostered animals");
@@ -503,14 +861,16 @@ pub async fn get_fostered(
         .await
         .map_err(|_e| CustomError::NotFound)?;
 
-    Ok(HttpResponse::Ok().json(fostered))
+    Ok(HttpResponse::Ok().json(AnimalResponse::from_list(fostered, Some(&current_user))))
 }
 
 pub async fn get_requested(
     db: web::Data<DbConn>,
     path: web::Path<i32>,
+    current_user: AuthenticatedUser,
 ) -> Result<HttpResponse, Error> {
     let shelter_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsShelter(shelter_id)).await?;
 
     let repo = AnimalRepository::new(db.get_ref());
     info!("Attempting to find currently requested animals");
@@ -520,5 +880,5 @@ pub async fn get_requested(
         .await
         .map_err(|_e| CustomError::NotFound)?;
 
-    Ok(HttpResponse::Ok().json(requested))
+    Ok(HttpResponse::Ok().json(AnimalResponse::from_list(requested, Some(&current_user))))
 }
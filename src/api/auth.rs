@@ -1,13 +1,18 @@
 use actix_web::{HttpResponse, web};
+use chrono::Utc;
 use sea_orm::DbConn;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::auth::CustomError;
+use crate::audit::{Actor, AuditEntry};
+use crate::auth::{CustomError, Role};
+use crate::auth::JWT_KEYS;
 use crate::auth::jwt::{generate_claims, generate_token_from_claims};
 use crate::auth::password::verify_password;
-use crate::database::models::UtilisateurModelEx;
-use crate::database::repositories::UtilisateurRepository;
+use crate::database::models::sea_orm_active_enums::ActionAudit;
+use crate::database::repositories::{AuditRepository, UtilisateurRepository};
+use crate::dto::UserResponse;
+use crate::limiter::RateLimiter;
 
 use crate::validators::common_validators::process_json_validation;
 
@@ -21,16 +26,31 @@ pub struct LoginRequest {
 #[derive(Serialize)]
 pub struct LoginResponse {
     pub access_token: String,
-    pub user: UtilisateurModelEx
+    pub user: UserResponse,
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.route("", web::post().to(login));
 }
 
-async fn login(db: web::Data<DbConn>, req: web::Json<LoginRequest>) -> Result<HttpResponse, CustomError> {
+pub fn configure_well_known(cfg: &mut web::ServiceConfig) {
+    cfg.route("/jwks.json", web::get().to(get_jwks));
+}
+
+async fn get_jwks() -> HttpResponse {
+    HttpResponse::Ok().json(&JWT_KEYS.jwks)
+}
+
+async fn login(
+    db: web::Data<DbConn>,
+    limiter: web::Data<RateLimiter>,
+    req: web::Json<LoginRequest>,
+) -> Result<HttpResponse, CustomError> {
     process_json_validation(&req)?;
 
+    limiter.check_account(&req.email).await?;
+    limiter.check_lockout(&req.email).await?;
+
     let user_repository = UtilisateurRepository::new(db.get_ref());
 
     let user = match user_repository
@@ -39,19 +59,52 @@ async fn login(db: web::Data<DbConn>, req: web::Json<LoginRequest>) -> Result<Ht
         .map_err(|_e| CustomError::InternalError)?
     {
         Some(user) => user,
-        None => return Err(CustomError::WrongLogin),
+        None => {
+            limiter.record_login_failure(&req.email).await;
+            record_login_failure(&db, None).await?;
+            return Err(CustomError::WrongLogin);
+        }
     };
 
     let is_valid = verify_password(&req.mot_de_passe, &user.mot_de_passe)?;
-    if !is_valid {
+    if !is_valid || user.is_archived() {
+        limiter.record_login_failure(&req.email).await;
+        record_login_failure(&db, Some(user.id)).await?;
         return Err(CustomError::WrongLogin);
     }
 
+    limiter.record_login_success(&req.email).await;
+
+    AuditRepository::new(db.get_ref())
+        .record(
+            AuditEntry::new(ActionAudit::Connexion, "utilisateur", Some(user.id))
+                .for_shelter(user.refuge.as_ref().map(|shelter| shelter.id))
+                .by(Actor {
+                    user_id: user.id,
+                    role: Role::from_user(&user),
+                }),
+        )
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    user_repository
+        .touch_last_login(user.id, Utc::now().naive_utc())
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
     let claims = generate_claims(&user);
     let access_token = generate_token_from_claims(&claims)?;
 
     Ok(HttpResponse::Ok().json(LoginResponse {
         access_token,
-        user
+        user: user.into(),
     }))
 }
+
+/// The submitted email is not kept: it may belong to someone else or be a mistyped password.
+async fn record_login_failure(db: &DbConn, user_id: Option<i32>) -> Result<(), CustomError> {
+    AuditRepository::new(db)
+        .record(AuditEntry::new(ActionAudit::ConnexionÉchouée, "utilisateur", user_id))
+        .await
+        .map_err(|_e| CustomError::InternalError)
+}
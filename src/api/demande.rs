@@ -1,14 +1,16 @@
-use actix_web::{Error, HttpMessage as _, HttpRequest, HttpResponse, web};
+use actix_web::{Error, HttpRequest, HttpResponse, web};
 use log::info;
 use sea_orm::DbConn;
 
 use sea_orm::prelude::Date;
 use serde::{Deserialize, Serialize};
 
-use crate::auth::CustomError;
+use crate::api::etag::conditional_json;
+use crate::auth::{AuthenticatedUser, CustomError, Policy};
 use crate::database::models::DemandeActiveModel;
 use crate::database::models::sea_orm_active_enums::StatutDemande;
-use crate::database::repositories::{DemandeRepository, FamilleRepository};
+use crate::database::repositories::DemandeRepository;
+use crate::dto::DemandeResponse;
 
 use sea_orm::ActiveValue::Set;
 
@@ -31,21 +33,9 @@ pub struct DemandeCreate {
     pub date_fin: Date,
 }
 
-pub async fn get_current_requests(db: web::Data<DbConn>, req: HttpRequest) -> Result<HttpResponse, CustomError> {
+pub async fn get_current_requests(db: web::Data<DbConn>, current_user: AuthenticatedUser) -> Result<HttpResponse, CustomError> {
 
-    let user_id = req.extensions_mut().get::<i32>().cloned().unwrap();
-
-    let repo = FamilleRepository::new(db.get_ref());
-
-    let foster = repo
-        .find_by_user_id(user_id)
-        .await
-        .map_err(|_e| CustomError::InternalError)?;
-    if foster.is_none() {
-        return Err(CustomError::NotFound);
-    }
-
-    let foster_id = foster.as_ref().unwrap().id;
+    let foster_id = current_user.foster_id()?;
 
     let repo = DemandeRepository::new(db.get_ref());
 
@@ -54,11 +44,20 @@ pub async fn get_current_requests(db: web::Data<DbConn>, req: HttpRequest) -> Re
         .await
         .map_err(|_e| CustomError::NotFound)?;
 
-    Ok(HttpResponse::Ok().json(requests))
+    Ok(HttpResponse::Ok().json(DemandeResponse::from_list(requests, Some(&current_user))))
 }
 
-pub async fn get_request(db: web::Data<DbConn>, path: web::Path<i32>) -> Result<HttpResponse, CustomError> {
+pub async fn get_request(
+    req: HttpRequest,
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
     let request_id = path.into_inner();
+    current_user.authorize(
+        db.get_ref(),
+        Policy::AnyOf(vec![Policy::MadeRequest(request_id), Policy::OwnsRequestedAnimal(request_id)]),
+    ).await?;
     let repo = DemandeRepository::new(db.get_ref());
 
     let request = repo
@@ -67,15 +66,17 @@ pub async fn get_request(db: web::Data<DbConn>, path: web::Path<i32>) -> Result<
         .map_err(|_e| CustomError::NotFound)?;
 
     match request {
-        Some(request) => Ok(HttpResponse::Ok().json(request)),
+        Some(request) => conditional_json(&req, request.version, &DemandeResponse::new(request, Some(&current_user), None)),
         None => Err(CustomError::NotFound),
     }
 }
 
 pub async fn create_request(
     db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
     json_request: web::Json<DemandeCreate>,
 ) -> Result<HttpResponse, Error> {
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(json_request.animal_id)).await?;
 
     info!(
         "Attempting to create request : {}",
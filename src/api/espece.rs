@@ -1,15 +1,117 @@
 use actix_web::{Error, HttpResponse, web};
-use sea_orm::DbConn;
-use crate::auth::CustomError;
+use log::info;
+use sea_orm::ActiveValue::Set;
+use sea_orm::entity::prelude::HasMany;
+use sea_orm::{DbConn, IntoActiveModel, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use validator::Validate;
 
-use crate::database::repositories::EspeceRepository;
+use crate::auth::CustomError;
+use crate::database::models::{EspeceActiveModel, RaceActiveModel, RaceModelEx};
+use crate::database::repositories::{EspeceRepository, RaceRepository};
+use crate::validators::common_validators::process_json_validation;
 
 pub fn configure_public(cfg: &mut web::ServiceConfig) {
     cfg.service(web::resource("")
             .get(get_all_species)
+        )
+        .service(web::resource("/{id}/races")
+            .get(get_breeds)
         );
 }
 
+#[derive(Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct EspeceInput {
+    #[validate(length(
+        min = 2,
+        max = 50,
+        message = "Name must be between 2 and 50 characters"
+    ))]
+    pub nom: String,
+}
+
+#[derive(Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct RaceInput {
+    #[validate(length(
+        min = 2,
+        max = 80,
+        message = "Name must be between 2 and 80 characters"
+    ))]
+    pub nom: String,
+    /// Other spellings, matched without case when importing animals.
+    #[serde(default)]
+    #[validate(length(max = 20, message = "A breed can have at most 20 aliases"))]
+    pub alias: Vec<String>,
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+impl RaceInput {
+    /// Trims the aliases and drops duplicates and the ones equal to the name.
+    fn aliases(&self) -> Result<Vec<String>, CustomError> {
+        let mut seen = HashSet::from([normalize(&self.nom)]);
+        let mut aliases = vec![];
+
+        for alias in self.alias.iter().map(|alias| alias.trim()) {
+            if !(2..=80).contains(&alias.chars().count()) {
+                return Err(CustomError::ValidationError {
+                    error_messages: "alias: Aliases must be between 2 and 80 characters".to_string(),
+                });
+            }
+            if seen.insert(normalize(alias)) {
+                aliases.push(alias.to_string());
+            }
+        }
+
+        Ok(aliases)
+    }
+}
+
+/// A breed name or alias must point to a single breed of the species.
+fn check_unique_breed(breeds: &[RaceModelEx], race_id: Option<i32>, nom: &str, aliases: &[String]) -> Result<(), CustomError> {
+    let taken: HashSet<String> = breeds
+        .iter()
+        .filter(|breed| Some(breed.id) != race_id)
+        .flat_map(|breed| {
+            let aliases = match &breed.alias {
+                HasMany::Loaded(aliases) => aliases.iter().map(|alias| normalize(&alias.alias)).collect(),
+                HasMany::Unloaded => vec![],
+            };
+            aliases.into_iter().chain([normalize(&breed.nom)])
+        })
+        .collect();
+
+    match std::iter::once(nom).chain(aliases.iter().map(String::as_str)).find(|name| taken.contains(&normalize(name))) {
+        Some(name) => Err(CustomError::ValidationError {
+            error_messages: format!("nom: '{}' is already used by another breed of this species", name.trim()),
+        }),
+        None => Ok(()),
+    }
+}
+
+async fn check_unique_species(db: &DbConn, espece_id: Option<i32>, nom: &str) -> Result<(), CustomError> {
+    let species = EspeceRepository::new(db)
+        .find_all()
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    if species
+        .iter()
+        .any(|espece| Some(espece.id) != espece_id && normalize(&espece.nom) == normalize(nom))
+    {
+        return Err(CustomError::ValidationError {
+            error_messages: "nom: This species already exists".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
 pub async fn get_all_species(db: web::Data<DbConn>) -> Result<HttpResponse, Error> {
     let repo = EspeceRepository::new(db.get_ref());
 
@@ -19,4 +121,216 @@ pub async fn get_all_species(db: web::Data<DbConn>) -> Result<HttpResponse, Erro
         .map_err(|_e| CustomError::NotFound)?;
 
     Ok(HttpResponse::Ok().json(species))
-}
\ No newline at end of file
+}
+
+pub async fn get_breeds(db: web::Data<DbConn>, path: web::Path<i32>) -> Result<HttpResponse, CustomError> {
+    let espece_id = path.into_inner();
+
+    EspeceRepository::new(db.get_ref())
+        .find_by_id(espece_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    let mut breeds = RaceRepository::new(db.get_ref())
+        .find_by_species(espece_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+    breeds.sort_by_key(|breed| breed.nom.to_lowercase());
+
+    Ok(HttpResponse::Ok().json(breeds))
+}
+
+pub async fn create_species(
+    db: web::Data<DbConn>,
+    json_species: web::Json<EspeceInput>,
+) -> Result<HttpResponse, CustomError> {
+    process_json_validation(&json_species)?;
+    let nom = json_species.into_inner().nom.trim().to_string();
+    check_unique_species(db.get_ref(), None, &nom).await?;
+
+    let species_model = EspeceActiveModel {
+        nom: Set(nom),
+        ..Default::default()
+    };
+
+    let created_species = EspeceRepository::new(db.get_ref())
+        .create(species_model)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+
+    info!("Species created with ID: {}", created_species.id);
+    Ok(HttpResponse::Created().json(created_species))
+}
+
+pub async fn update_species(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    json_species: web::Json<EspeceInput>,
+) -> Result<HttpResponse, CustomError> {
+    let espece_id = path.into_inner();
+    process_json_validation(&json_species)?;
+    let nom = json_species.into_inner().nom.trim().to_string();
+    check_unique_species(db.get_ref(), Some(espece_id), &nom).await?;
+
+    let repo = EspeceRepository::new(db.get_ref());
+    let mut species_model = repo
+        .find_by_id(espece_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?
+        .into_active_model();
+    species_model.nom = Set(nom);
+
+    let updated_species = repo
+        .update(species_model)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?;
+
+    Ok(HttpResponse::Ok().json(updated_species))
+}
+
+/// Species still used by animals cannot be removed.
+pub async fn delete_species(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, CustomError> {
+    let espece_id = path.into_inner();
+    let repo = EspeceRepository::new(db.get_ref());
+
+    if repo
+        .has_animals(espece_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+    {
+        return Err(CustomError::BadClientData);
+    }
+
+    let result = repo
+        .delete(espece_id)
+        .await
+        .map_err(|_e| CustomError::DeletionError)?;
+    if result.rows_affected == 0 {
+        return Err(CustomError::NotFound);
+    }
+
+    info!("Species with ID {} deleted", espece_id);
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn create_breed(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    json_breed: web::Json<RaceInput>,
+) -> Result<HttpResponse, CustomError> {
+    let espece_id = path.into_inner();
+    process_json_validation(&json_breed)?;
+    let aliases = json_breed.aliases()?;
+    let nom = json_breed.into_inner().nom.trim().to_string();
+
+    EspeceRepository::new(db.get_ref())
+        .find_by_id(espece_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    let repo = RaceRepository::new(db.get_ref());
+    let breeds = repo
+        .find_by_species(espece_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+    check_unique_breed(&breeds, None, &nom, &aliases)?;
+
+    let breed_model = RaceActiveModel {
+        espece_id: Set(espece_id),
+        nom: Set(nom),
+        ..Default::default()
+    };
+
+    let txn = db.begin().await.map_err(|_e| CustomError::CreationError)?;
+    let txn_repo = RaceRepository::new(&txn);
+    let created_breed = txn_repo
+        .create(breed_model)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+    txn_repo
+        .set_aliases(created_breed.id, aliases)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+    txn.commit().await.map_err(|_e| CustomError::CreationError)?;
+
+    let created_breed = repo
+        .find_with_aliases(created_breed.id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    info!("Breed created with ID {} for species with ID {}", created_breed.id, espece_id);
+    Ok(HttpResponse::Created().json(created_breed))
+}
+
+/// Replaces the name and aliases of the breed, animals keep pointing to it.
+pub async fn update_breed(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    json_breed: web::Json<RaceInput>,
+) -> Result<HttpResponse, CustomError> {
+    let race_id = path.into_inner();
+    process_json_validation(&json_breed)?;
+    let aliases = json_breed.aliases()?;
+    let nom = json_breed.into_inner().nom.trim().to_string();
+
+    let repo = RaceRepository::new(db.get_ref());
+    let breed = repo
+        .find_by_id(race_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    let breeds = repo
+        .find_by_species(breed.espece_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+    check_unique_breed(&breeds, Some(race_id), &nom, &aliases)?;
+
+    let mut breed_model = breed.into_active_model();
+    breed_model.nom.set_if_not_equals(nom);
+
+    let txn = db.begin().await.map_err(|_e| CustomError::UpdateError)?;
+    let txn_repo = RaceRepository::new(&txn);
+    txn_repo
+        .update(breed_model)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?;
+    txn_repo
+        .set_aliases(race_id, aliases)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?;
+    txn.commit().await.map_err(|_e| CustomError::UpdateError)?;
+
+    let updated_breed = repo
+        .find_with_aliases(race_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    Ok(HttpResponse::Ok().json(updated_breed))
+}
+
+pub async fn delete_breed(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, CustomError> {
+    let race_id = path.into_inner();
+
+    let result = RaceRepository::new(db.get_ref())
+        .delete(race_id)
+        .await
+        .map_err(|_e| CustomError::DeletionError)?;
+    if result.rows_affected == 0 {
+        return Err(CustomError::NotFound);
+    }
+
+    info!("Breed with ID {} deleted", race_id);
+    Ok(HttpResponse::NoContent().finish())
+}
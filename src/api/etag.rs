@@ -0,0 +1,72 @@
+use actix_web::http::header::{ETag, EntityTag, IfMatch, IfNoneMatch};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse};
+use sea_orm::DbErr;
+use serde::Serialize;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::auth::CustomError;
+
+/// Tags look like `"<version>-<digest>"`: `If-Match` only compares the row version,
+/// while `If-None-Match` also catches changes to nested relations such as pictures or residents.
+fn entity_tag(version: i32, body: &[u8]) -> EntityTag {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+
+    EntityTag::new_strong(format!("{}-{:x}", version, hasher.finish()))
+}
+
+fn version_of(tag: &EntityTag) -> Option<i32> {
+    tag.tag().split('-').next()?.parse().ok()
+}
+
+/// JSON response carrying an `ETag`.
+pub fn tagged_json<T: Serialize>(version: i32, body: &T) -> Result<HttpResponse, CustomError> {
+    let body = serde_json::to_vec(body).map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .insert_header(ETag(entity_tag(version, &body)))
+        .body(body))
+}
+
+/// Same as `tagged_json`, answering 304 when the client copy is still fresh.
+pub fn conditional_json<T: Serialize>(req: &HttpRequest, version: i32, body: &T) -> Result<HttpResponse, CustomError> {
+    let body = serde_json::to_vec(body).map_err(|_e| CustomError::InternalError)?;
+    let tag = entity_tag(version, &body);
+
+    let fresh = match req.get_header::<IfNoneMatch>() {
+        Some(IfNoneMatch::Any) => true,
+        Some(IfNoneMatch::Items(tags)) => tags.iter().any(|client_tag| client_tag.weak_eq(&tag)),
+        None => false,
+    };
+    if fresh {
+        return Ok(HttpResponse::NotModified().insert_header(ETag(tag)).finish());
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .insert_header(ETag(tag))
+        .body(body))
+}
+
+/// Rejects the update when `If-Match` names another version, and requires the header so that
+/// no client overwrites a change it has not seen.
+pub fn check_if_match(req: &HttpRequest, version: i32) -> Result<(), CustomError> {
+    match req.get_header::<IfMatch>() {
+        None => Err(CustomError::PreconditionRequired),
+        Some(IfMatch::Items(tags))
+            if !tags.iter().any(|tag| !tag.weak && version_of(tag) == Some(version)) =>
+        {
+            Err(CustomError::PreconditionFailed)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The repositories fail with `RecordNotUpdated` when the row was saved by someone else in the meantime.
+pub fn update_error(e: DbErr) -> CustomError {
+    match e {
+        DbErr::RecordNotUpdated => CustomError::PreconditionFailed,
+        _ => CustomError::UpdateError,
+    }
+}
@@ -1,16 +1,28 @@
-use actix_web::{HttpMessage, HttpRequest, HttpResponse, web};
-use log::{info, warn};
-use sea_orm::DbConn;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, web};
+use chrono::Utc;
+use log::info;
+use sea_orm::{DbConn, TransactionTrait};
 use validator::Validate;
 
 use serde::{Deserialize, Serialize};
-
-use crate::auth::{CustomError, hash_password};
-use crate::database::models::{FamilleActiveModel, FamilleActiveModelEx, UtilisateurActiveModel};
-use crate::database::repositories::{FamilleRepository, UtilisateurRepository};
-use crate::validators::common_validators::{process_json_validation, validate_phone, validate_zipcode};
+use serde_json::Value;
+
+use crate::api::etag::{check_if_match, conditional_json, tagged_json, update_error};
+use crate::api::merge_patch::{MergePatch, merge_patch};
+use crate::auth::{AuthenticatedUser, CustomError, hash_password};
+use crate::database::models::{AlerteModelEx, DemandeModelEx, EspeceModel, FamilleActiveModel, FamilleModel, FamilleModelEx, MediaModel, RechercheModelEx, UtilisateurActiveModel};
+use crate::database::models::sea_orm_active_enums::{Logement, NiveauExperience};
+use crate::database::repositories::{AlerteRepository, AnimalRepository, DemandeRepository, FamilleRepository, MediaRepository, RechercheRepository, UtilisateurRepository};
+use crate::dto::{AnimalResponse, Audience, FosterResponse, RankQuery, Ranked};
+use crate::geo::{GEOCODER, Geocoder};
+use crate::limiter::RateLimiter;
+use crate::services::{AccountService, matching_service};
+use crate::validators::common_validators::{process_json_validation, validate_contact, validate_country};
 
 use sea_orm::ActiveValue::Set;
+use sea_orm::entity::prelude::HasMany;
+use sea_orm::prelude::DateTime;
 
 pub fn configure_register(cfg: &mut web::ServiceConfig) {
     cfg.service(web::resource("")
@@ -20,11 +32,21 @@ pub fn configure_register(cfg: &mut web::ServiceConfig) {
 
 pub fn configure_protected(cfg: &mut web::ServiceConfig) {
     cfg.service(web::resource("")
-            .post(update_foster)
+            .post(patch_foster)
+            .patch(patch_foster)
         )
         .service(web::resource("/delete")
             .post(delete_foster)
         )
+        .service(web::resource("/export")
+            .get(export_foster)
+        )
+        .service(web::resource("/anonymisation")
+            .post(anonymize_foster)
+        )
+        .service(web::resource("/suggestions")
+            .get(get_suggestions)
+        )
         .service(web::resource("/{id}")
             .get(get_foster)
         );
@@ -44,7 +66,6 @@ pub struct FosterCreate {
         message = "Your full last name should be between 2 and 50 characters"
     ))]
     pub nom: String,
-    #[validate(custom(function = validate_phone))]
     pub telephone: String,
     #[validate(length(
         min = 2,
@@ -58,13 +79,8 @@ pub struct FosterCreate {
         message = "Your city's name must be between 2 and 58 characters"
     ))]
     pub commune: String,
-    #[validate(custom(function = validate_zipcode))]
     pub code_postal: String,
-    #[validate(length(
-        min = 4,
-        max = 42,
-        message = "Country name must be between 4 and 42 characters"
-    ))]
+    #[validate(custom(function = validate_country))]
     pub pays: String,
     #[validate(length(
         min = 3,
@@ -78,6 +94,18 @@ pub struct FosterCreate {
         message = "Please describe your garden/yard using between 3 and 50 characters"
     ))]
     pub terrain: Option<String>,
+    pub logement: Option<Logement>,
+    #[serde(default)]
+    pub jardin: bool,
+    #[serde(default)]
+    pub possede_chats: bool,
+    #[serde(default)]
+    pub possede_chiens: bool,
+    #[serde(default)]
+    pub enfants: bool,
+    pub experience: Option<NiveauExperience>,
+    #[serde(default)]
+    pub especes_acceptees: Vec<i32>,
     pub utilisateur_id: Option<i32>,
     #[validate(length(
         min = 8,
@@ -95,51 +123,134 @@ pub struct FosterCreate {
 }
 
 #[derive(Deserialize, Serialize, Validate)]
-pub struct FosterUpdate {
+#[serde(deny_unknown_fields)]
+pub struct FosterPatch {
     #[validate(length(
         min = 2,
         max = 50,
         message = "Your first name must usually be between 2 and 50 characters"
     ))]
-    pub prenom: Option<Option<String>>,
+    pub prenom: Option<String>,
     #[validate(length(
         min = 2,
         max = 50,
         message = "Your full last name should be between 2 and 50 characters"
     ))]
-    pub nom: Option<String>,
-    #[validate(custom(function = validate_phone))]
-    pub telephone: Option<String>,
+    pub nom: String,
+    pub telephone: String,
     #[validate(length(
         min = 2,
         max = 50,
         message = "Your address must usually be between 2 and 50 characters"
     ))]
-    pub rue: Option<String>,
-     #[validate(length(
+    pub rue: String,
+    #[validate(length(
         min = 2,
         max = 58,
         message = "Your city's name must be between 2 and 58 characters"
     ))]
-    pub commune: Option<String>,
-    #[validate(custom(function = validate_zipcode))]
-    pub code_postal: Option<String>,
+    pub commune: String,
+    pub code_postal: String,
+    #[validate(custom(function = validate_country))]
+    pub pays: String,
     #[validate(length(
         min = 3,
         max = 50,
         message = "Please describe your home using between 3 and 50 characters"
     ))]
-    pub hebergement: Option<String>,
+    pub hebergement: String,
     #[validate(length(
         min = 3,
         max = 50,
         message = "Please describe your garden/yard using between 3 and 50 characters"
     ))]
-    pub terrain: Option<Option<String>>,
-    pub utilisateur_id: Option<i32>,
+    pub terrain: Option<String>,
+    pub logement: Option<Logement>,
+    pub jardin: bool,
+    pub possede_chats: bool,
+    pub possede_chiens: bool,
+    pub enfants: bool,
+    pub experience: Option<NiveauExperience>,
+    pub especes_acceptees: Vec<i32>,
+}
+
+impl MergePatch for FosterPatch {
+    type Model = FamilleModelEx;
+    type ActiveModel = FamilleActiveModel;
+
+    fn from_model(foster: &FamilleModelEx) -> Self {
+        let especes_acceptees = match &foster.especes_acceptees {
+            HasMany::Loaded(especes) => especes.iter().map(|espece| espece.id).collect(),
+            HasMany::Unloaded => vec![],
+        };
+
+        Self {
+            prenom: foster.prenom.clone(),
+            nom: foster.nom.clone(),
+            telephone: foster.telephone.clone(),
+            rue: foster.rue.clone(),
+            commune: foster.commune.clone(),
+            code_postal: foster.code_postal.clone(),
+            pays: foster.pays.clone(),
+            hebergement: foster.hebergement.clone(),
+            terrain: foster.terrain.clone(),
+            logement: foster.logement.clone(),
+            jardin: foster.jardin,
+            possede_chats: foster.possede_chats,
+            possede_chiens: foster.possede_chiens,
+            enfants: foster.enfants,
+            experience: foster.experience.clone(),
+            especes_acceptees,
+        }
+    }
+
+    /// Accepted species live in their own table and are saved separately.
+    fn apply(self, foster: &mut FamilleActiveModel) {
+        foster.prenom.set_if_not_equals(self.prenom);
+        foster.nom.set_if_not_equals(self.nom);
+        foster.telephone.set_if_not_equals(self.telephone);
+        foster.rue.set_if_not_equals(self.rue);
+        foster.commune.set_if_not_equals(self.commune);
+        foster.code_postal.set_if_not_equals(self.code_postal);
+        foster.pays.set_if_not_equals(self.pays);
+        foster.hebergement.set_if_not_equals(self.hebergement);
+        foster.terrain.set_if_not_equals(self.terrain);
+        foster.logement.set_if_not_equals(self.logement);
+        foster.jardin.set_if_not_equals(self.jardin);
+        foster.possede_chats.set_if_not_equals(self.possede_chats);
+        foster.possede_chiens.set_if_not_equals(self.possede_chiens);
+        foster.enfants.set_if_not_equals(self.enfants);
+        foster.experience.set_if_not_equals(self.experience);
+    }
+}
+
+#[derive(Serialize)]
+pub struct FosterAccountExport {
+    pub id: i32,
+    pub email: String,
+    pub derniere_connexion: DateTime,
 }
 
-pub async fn get_foster(db: web::Data<DbConn>, path: web::Path<i32>) -> Result<HttpResponse, CustomError> {
+#[derive(Serialize)]
+pub struct FosterExport {
+    pub exporte_le: DateTime,
+    pub compte: FosterAccountExport,
+    pub profil: FamilleModel,
+    pub especes_acceptees: Vec<EspeceModel>,
+    pub demandes: Vec<DemandeModelEx>,
+    pub recherches: Vec<RechercheModelEx>,
+    /// Inbox of the account, alerts and notices from the admins.
+    pub messages: Vec<AlerteModelEx>,
+    /// Photos of the animals the foster asked to host.
+    pub medias: Vec<MediaModel>,
+}
+
+pub async fn get_foster(
+    req: HttpRequest,
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
 
     let foster_id = path.into_inner();
     let repo = FamilleRepository::new(db.get_ref());
@@ -150,16 +261,24 @@ pub async fn get_foster(db: web::Data<DbConn>, path: web::Path<i32>) -> Result<H
         .map_err(|_e| CustomError::NotFound)?;
 
     match foster {
-        Some(foster) => Ok(HttpResponse::Ok().json(foster)),
+        Some(foster) if current_user.is_admin() || current_user.famille_id == Some(foster.id) => {
+            conditional_json(&req, foster.version, &foster)
+        }
+        Some(foster) => conditional_json(&req, foster.version, &FosterResponse::new(foster.into(), Audience::Public)),
         None => Err(CustomError::NotFound),
     }
 }
 
 pub async fn create_foster(
     db: web::Data<DbConn>,
+    limiter: web::Data<RateLimiter>,
+    req: HttpRequest,
     json_foster: web::Json<FosterCreate>,
 ) -> Result<HttpResponse, CustomError> {
     process_json_validation(&json_foster)?;
+    let contact = validate_contact(&json_foster.pays, &json_foster.code_postal, &json_foster.telephone)?;
+
+    limiter.check_registration(&limiter.client_ip(&req)).await?;
 
     info!(
         "Attempting to create Foster with name: {}",
@@ -186,25 +305,37 @@ pub async fn create_foster(
         ..Default::default()
     };
 
-    let created_user = user_repo
+    let txn = db.begin().await.map_err(|_e| CustomError::CreationError)?;
+
+    let created_user = UtilisateurRepository::new(&txn)
         .create(user_model)
         .await
         .map_err(|_e| CustomError::CreationError)?;
 
     info!("User created with ID: {}", created_user.id);
 
-    let repo = FamilleRepository::new(db.get_ref());
+    let repo = FamilleRepository::new(&txn);
+
+    let coordinates = GEOCODER.geocode(&contact.code_postal, &foster.commune, &contact.pays);
 
     let foster_model = FamilleActiveModel {
         prenom: Set(foster.prenom),
         nom: Set(foster.nom),
-        telephone: Set(foster.telephone),
+        telephone: Set(contact.telephone),
         rue: Set(foster.rue),
         commune: Set(foster.commune),
-        code_postal: Set(foster.code_postal),
-        pays: Set(foster.pays),
+        code_postal: Set(contact.code_postal),
+        pays: Set(contact.pays),
         hebergement: Set(foster.hebergement),
         terrain: Set(foster.terrain),
+        logement: Set(foster.logement),
+        jardin: Set(foster.jardin),
+        possede_chats: Set(foster.possede_chats),
+        possede_chiens: Set(foster.possede_chiens),
+        enfants: Set(foster.enfants),
+        experience: Set(foster.experience),
+        latitude: Set(coordinates.map(|c| c.latitude)),
+        longitude: Set(coordinates.map(|c| c.longitude)),
         utilisateur_id: Set(created_user.id),
         ..Default::default()
     };
@@ -214,122 +345,202 @@ pub async fn create_foster(
         .await
         .map_err(|_e| CustomError::CreationError)?;
 
+    repo.set_accepted_species(created_foster.id, foster.especes_acceptees)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+
+    txn.commit().await.map_err(|_e| CustomError::CreationError)?;
+
     info!("Foster created with ID: {}", created_foster.id);
     Ok(HttpResponse::Created().json(created_foster))
 }
 
-pub async fn update_foster(
-    db: web::Data<DbConn>,
+pub async fn patch_foster(
     req: HttpRequest,
-    json_foster: web::Json<FosterUpdate>,
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+    patch: web::Json<Value>,
 ) -> Result<HttpResponse, CustomError> {
-    process_json_validation(&json_foster)?;
-
-    let user_id = req.extensions_mut().get::<i32>().cloned().unwrap();
-
+    let foster_id = current_user.foster_id()?;
     let repo = FamilleRepository::new(db.get_ref());
 
     let foster_data = repo
-        .find_by_user_id(user_id)
+        .find_with_preferences(foster_id)
         .await
-        .map_err(|_e| CustomError::InternalError)?;
-    if foster_data.is_none() {
-        return Err(CustomError::NotFound);
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+    check_if_match(&req, foster_data.version)?;
+
+    let mut foster: FosterPatch = merge_patch(&foster_data, patch.into_inner())?;
+    let contact = validate_contact(&foster.pays, &foster.code_postal, &foster.telephone)?;
+    foster.pays = contact.pays;
+    foster.code_postal = contact.code_postal;
+    foster.telephone = contact.telephone;
+
+    let species_changed = foster.especes_acceptees != FosterPatch::from_model(&foster_data).especes_acceptees;
+    let especes_acceptees = foster.especes_acceptees.clone();
+
+    let address_changed = foster.code_postal != foster_data.code_postal
+        || foster.commune != foster_data.commune
+        || foster.pays != foster_data.pays;
+    let coordinates = address_changed
+        .then(|| GEOCODER.geocode(&foster.code_postal, &foster.commune, &foster.pays))
+        .flatten();
+
+    let foster_model: FamilleModel = foster_data.into();
+    let mut foster_active_model: FamilleActiveModel = foster_model.into();
+    foster.apply(&mut foster_active_model);
+    if address_changed {
+        foster_active_model.latitude = Set(coordinates.map(|c| c.latitude));
+        foster_active_model.longitude = Set(coordinates.map(|c| c.longitude));
     }
 
-    match foster_data {
-        Some(foster_data) => {
-            let mut foster_active_model: FamilleActiveModelEx = foster_data.into();
-
-            let foster = json_foster.into_inner();
-
-            if let Some(prenom) = foster.prenom {    
-                foster_active_model.prenom = Set(prenom);
-            }
-            if let Some(nom) = foster.nom {
-                foster_active_model.nom = Set(nom);
-            }
-            if let Some(telephone) = foster.telephone {
-                foster_active_model.telephone = Set(telephone);
-            }
-            if let Some(rue) = foster.rue {
-                foster_active_model.rue = Set(rue);
-            }
-            if let Some(commune) = foster.commune {
-                foster_active_model.commune = Set(commune);
-            }
-            if let Some(code_postal) = foster.code_postal {
-                foster_active_model.code_postal = Set(code_postal);
-            }
-            if let Some(hebergement) = foster.hebergement {
-                foster_active_model.hebergement = Set(hebergement);
-            }
-            if let Some(terrain) = foster.terrain {
-                foster_active_model.terrain = Set(terrain);
-            }
-
-            let updated_foster = repo
-                .update(foster_active_model)
-                .await
-                .map_err(|_e| CustomError::UpdateError)?;
-
-            info!("Foster succesfully updated");
-            Ok(HttpResponse::Ok().json(updated_foster))
-        }
-        None => Err(CustomError::NotFound),
+    let txn = db.begin().await.map_err(|_e| CustomError::UpdateError)?;
+    let txn_repo = FamilleRepository::new(&txn);
+
+    txn_repo
+        .update_model(foster_active_model)
+        .await
+        .map_err(update_error)?;
+
+    if species_changed {
+        txn_repo
+            .set_accepted_species(foster_id, especes_acceptees)
+            .await
+            .map_err(|_e| CustomError::UpdateError)?;
     }
+
+    txn.commit().await.map_err(|_e| CustomError::UpdateError)?;
+
+    let updated_foster = repo
+        .find_with_preferences(foster_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    info!("Foster succesfully updated");
+    tagged_json(updated_foster.version, &updated_foster)
 }
 
-pub async fn delete_foster(
+pub async fn get_suggestions(
     db: web::Data<DbConn>,
-    req: HttpRequest,
+    current_user: AuthenticatedUser,
+    query: web::Query<RankQuery>,
 ) -> Result<HttpResponse, CustomError> {
+    let foster_id = current_user.foster_id()?;
 
-    let user_id = req.extensions_mut().get::<i32>().cloned().unwrap();
-    
-    let user_repo = UtilisateurRepository::new(db.get_ref());
+    let foster = FamilleRepository::new(db.get_ref())
+        .find_with_preferences(foster_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
 
-    let user = user_repo
-        .find_by_id(user_id)
+    let animals = AnimalRepository::new(db.get_ref())
+        .find_suggestions(
+            matching_service::accepted_species(&foster),
+            matching_service::unmet_constraints(&foster),
+        )
         .await
         .map_err(|_e| CustomError::InternalError)?;
-    if user.is_none() {
-        return Err(CustomError::NotFound);
-    }
 
-    let repo = FamilleRepository::new(db.get_ref());
+    let candidates = animals
+        .into_iter()
+        .map(|animal| Ranked {
+            compatibilite: matching_service::score(&foster, &animal),
+            item: AnimalResponse::new(animal, Some(&current_user)),
+        })
+        .collect();
 
-    let foster = repo
-        .find_by_user_id(user_id)
+    Ok(HttpResponse::Ok().json(Ranked::best(candidates, query.limit())))
+}
+
+pub async fn delete_foster(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    AccountService::new(db.get_ref())
+        .delete_account(current_user.user_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn export_foster(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let foster_id = current_user.foster_id()?;
+
+    info!("Exporting personal data of foster with ID: {}", foster_id);
+
+    let user = UtilisateurRepository::new(db.get_ref())
+        .find_by_id(current_user.user_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    let foster = FamilleRepository::new(db.get_ref())
+        .find_with_preferences(foster_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    let requests = DemandeRepository::new(db.get_ref())
+        .find_all_by_foster(foster_id)
         .await
         .map_err(|_e| CustomError::InternalError)?;
-    if foster.is_none() {
-        return Err(CustomError::NotFound);
-    }
-    if !foster.as_ref().unwrap().animals.is_empty() {
-        return Err(CustomError::FosteredError);
-    }
 
-    let foster_id = foster.as_ref().unwrap().id;
-    info!("Attempting to delete foster with ID: {}", foster_id);
+    let searches = RechercheRepository::new(db.get_ref())
+        .find_by_foster(foster_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
 
-    let delete_result = repo
-        .delete(foster_id)
+    let messages = AlerteRepository::new(db.get_ref())
+        .find_published_by_user(current_user.user_id)
         .await
-        .map_err(|_e| CustomError::DeletionError)?;
+        .map_err(|_e| CustomError::InternalError)?;
 
-    let delete_user = user_repo
-        .delete(user_id)
+    let animal_ids = requests.iter().map(|request| request.animal_id).collect();
+    let media = MediaRepository::new(db.get_ref())
+        .find_public_by_animals(animal_ids)
         .await
-        .map_err(|_e| CustomError::DeletionError)?;
-
-    if delete_result.rows_affected > 0 && delete_user.rows_affected > 0 {
-        info!("Foster with ID {} successfully deleted", foster_id);
-        info!("User with ID {} also successfully deleted", user_id);
-        Ok(HttpResponse::NoContent().finish())
-    } else {
-        warn!("Foster with ID {} was not deleted (0 rows affected)", foster_id);
-        warn!("User with ID {} was not deleted (0 rows affected)", user_id);
-        Err(CustomError::DeletionError)
-    }
-}
\ No newline at end of file
+        .map_err(|_e| CustomError::InternalError)?;
+
+    let especes_acceptees = match &foster.especes_acceptees {
+        HasMany::Loaded(especes) => especes.iter().cloned().map(Into::into).collect(),
+        HasMany::Unloaded => vec![],
+    };
+
+    let export = FosterExport {
+        exporte_le: Utc::now().naive_utc(),
+        compte: FosterAccountExport {
+            id: user.id,
+            email: user.email,
+            derniere_connexion: user.derniere_connexion,
+        },
+        profil: foster.into(),
+        especes_acceptees,
+        demandes: requests,
+        recherches: searches,
+        messages,
+        medias: media,
+    };
+
+    Ok(HttpResponse::Ok()
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"pfc-export-{}.json\"", foster_id),
+        ))
+        .json(export))
+}
+
+pub async fn anonymize_foster(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    AccountService::new(db.get_ref())
+        .anonymize_foster(current_user.user_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -8,11 +8,16 @@ use actix_multipart::{
     },
 };
 use log::{info, warn};
-use sea_orm::DbConn;
+use sea_orm::{DbConn, IntoActiveModel};
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
 
-use crate::auth::CustomError;
+use crate::auth::{AuthenticatedUser, CustomError, Policy};
 use crate::database::models::MediaActiveModel;
-use crate::database::repositories::{MediaRepository};
+use crate::database::models::sea_orm_active_enums::StatutInscription;
+use crate::database::repositories::{AssociationRepository, MediaRepository};
+use crate::middleware::RoleGuard;
 
 use sea_orm::ActiveValue::Set;
 
@@ -27,6 +32,7 @@ pub fn configure_protected(cfg: &mut web::ServiceConfig) {
             .post(upload_logo)
         )
         .service(web::resource("/photo")
+            .wrap(RoleGuard::shelter_or_admin())
             .post(upload_photo)
         );
 }
@@ -51,8 +57,11 @@ pub struct LogoUploadForm {
 
 pub async fn upload_logo(
     db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
     MultipartForm(form): MultipartForm<LogoUploadForm>,
 ) -> Result<HttpResponse, CustomError> {
+    let shelter_id = form.asso_id.as_ref().ok_or(CustomError::BadClientData)?.0;
+    current_user.authorize(db.get_ref(), Policy::OwnsShelter(shelter_id)).await?;
 
     let file_path = format!("/images/animaux/{}", form.file.file_name.unwrap());
     warn!("Saving picture to ./static{}", file_path);
@@ -61,13 +70,10 @@ pub async fn upload_logo(
     
     let repo = MediaRepository::new(db.get_ref());
 
-    let shelter_id  = form.asso_id.unwrap().to_string();
-    let id = shelter_id.parse::<i32>().unwrap();
-
     let media_model = MediaActiveModel {
         url: Set(file_path),
         ordre: Set(1),
-        association_id: Set(Some(id)),
+        association_id: Set(Some(shelter_id)),
         ..Default::default()
     };
 
@@ -89,8 +95,11 @@ pub struct PhotoUploadForm {
 
 async fn upload_photo(
     db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
     MultipartForm(form): MultipartForm<PhotoUploadForm>,
 ) -> Result<impl Responder, Error> {
+    let animal_id = form.animal_id.as_ref().ok_or(CustomError::BadClientData)?.0;
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
 
     let file_path = format!("/images/animaux/{}", form.file.file_name.unwrap());
     warn!("Saving picture to ./static{}", file_path);
@@ -99,13 +108,10 @@ async fn upload_photo(
 
     let repo = MediaRepository::new(db.get_ref());
 
-    let animal_id  = form.animal_id.unwrap().to_string();
-    let id = animal_id.parse::<i32>().unwrap();
-
     let media_model = MediaActiveModel {
         url: Set(file_path),
         ordre: Set(1),
-        animal_id: Set(Some(id)),
+        animal_id: Set(Some(animal_id)),
         ..Default::default()
     };
 
@@ -116,4 +122,89 @@ async fn upload_photo(
 
     info!("Logo uploaded with ID: {}", created_media.id);
     Ok(HttpResponse::Created().json(created_media))
-}
\ No newline at end of file
+}
+
+const DOCUMENT_EXTENSIONS: [&str; 4] = ["pdf", "jpg", "jpeg", "png"];
+
+#[derive(Debug, MultipartForm)]
+pub struct DocumentUploadForm {
+    #[multipart(limit = "10MB")]
+    pub file: TempFile,
+}
+
+/// Saves a PDF or picture under `./static/documents`, which is not served publicly, and returns its url.
+pub fn store_document(file: TempFile, directory: &str) -> Result<String, CustomError> {
+    let extension = file
+        .file_name
+        .as_deref()
+        .and_then(|name| Path::new(name).extension())
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase())
+        .filter(|extension| DOCUMENT_EXTENSIONS.contains(&extension.as_str()))
+        .ok_or(CustomError::BadClientData)?;
+
+    fs::create_dir_all(format!("./static{}", directory)).map_err(|_e| CustomError::InternalError)?;
+
+    let file_path = format!("{}/{}.{}", directory, Uuid::new_v4(), extension);
+    file.file
+        .persist(format!("./static{}", file_path))
+        .map_err(|_e| CustomError::CreationError)?;
+
+    Ok(file_path)
+}
+
+/// Registration documents, reviewed by the admins before approving the shelter.
+pub async fn upload_document(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+    MultipartForm(form): MultipartForm<DocumentUploadForm>,
+) -> Result<HttpResponse, CustomError> {
+    let shelter_id = current_user.shelter_id()?;
+    let file_path = store_document(form.file, &format!("/documents/associations/{}", shelter_id))?;
+
+    let media_model = MediaActiveModel {
+        url: Set(file_path),
+        ordre: Set(1),
+        association_id: Set(Some(shelter_id)),
+        justificatif: Set(true),
+        ..Default::default()
+    };
+
+    let created_media = MediaRepository::new(db.get_ref())
+        .create(media_model)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+
+    // New documents from a rejected shelter put its registration back in the review queue.
+    let shelter_repo = AssociationRepository::new(db.get_ref());
+    let shelter = shelter_repo
+        .find_model_by_id(shelter_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+    if shelter.inscription == StatutInscription::Rejetée {
+        let mut shelter_active_model = shelter.into_active_model();
+        shelter_active_model.inscription = Set(StatutInscription::EnAttente);
+        shelter_repo
+            .update_model(shelter_active_model)
+            .await
+            .map_err(|_e| CustomError::UpdateError)?;
+    }
+
+    info!("Document uploaded with ID {} for shelter with ID {}", created_media.id, shelter_id);
+    Ok(HttpResponse::Created().json(created_media))
+}
+
+pub async fn get_documents(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let shelter_id = current_user.shelter_id()?;
+
+    let documents = MediaRepository::new(db.get_ref())
+        .find_documents_by_association(shelter_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok().json(documents))
+}
@@ -0,0 +1,70 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use validator::Validate;
+
+use crate::auth::CustomError;
+use crate::validators::common_validators::format_validation_errors;
+
+/// Editable fields of a resource, updated with RFC 7396 merge-patch documents.
+/// Required columns are plain fields and nullable columns `Option`s, so `null` is only accepted where the column allows it.
+pub trait MergePatch: Serialize + DeserializeOwned + Validate {
+    type Model;
+    type ActiveModel;
+
+    fn from_model(model: &Self::Model) -> Self;
+
+    /// Sets the fields that changed, the others stay `Unchanged`.
+    fn apply(self, model: &mut Self::ActiveModel);
+}
+
+/// Applies `patch` to the editable fields of `model` and validates the fields it sets,
+/// so that a value saved before a rule was tightened does not block unrelated edits.
+/// Rules spanning several fields are always checked.
+pub fn merge_patch<P: MergePatch>(model: &P::Model, patch: Value) -> Result<P, CustomError> {
+    let Some(fields) = patch.as_object().map(|patch| patch.keys().cloned().collect::<Vec<String>>()) else {
+        return Err(CustomError::BadClientData);
+    };
+
+    let mut document = serde_json::to_value(P::from_model(model)).map_err(|_e| CustomError::InternalError)?;
+    merge(&mut document, patch);
+
+    let patched: P = serde_json::from_value(document).map_err(|e| CustomError::ValidationError {
+        error_messages: e.to_string(),
+    })?;
+    if let Err(mut validation_errors) = patched.validate() {
+        validation_errors
+            .0
+            .retain(|field, _| field == "__all__" || fields.iter().any(|patched_field| patched_field == field));
+        if !validation_errors.is_empty() {
+            return Err(CustomError::ValidationError {
+                error_messages: format_validation_errors(validation_errors),
+            });
+        }
+    }
+
+    Ok(patched)
+}
+
+/// MergePatch algorithm from RFC 7396, section 2.
+fn merge(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        return;
+    };
+
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(&key);
+        } else {
+            merge(target.entry(key).or_insert(Value::Null), value);
+        }
+    }
+}
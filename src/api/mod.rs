@@ -2,15 +2,24 @@ use actix_web::web::ServiceConfig;
 use actix_web::{HttpResponse, web};
 use sea_orm::DbConn;
 
-use crate::middleware::{AuthMiddleware, RoleGuard};
+use crate::auth::Role;
+use crate::limiter::RateLimiter;
+use crate::middleware::{AuthMiddleware, RateLimit, RoleGuard};
 
 pub mod auth;
-mod animal;
+mod admin;
+pub mod animal;
 mod association;
 mod demande;
 mod espece;
+mod etag;
 mod famille;
+mod merge_patch;
 mod media;
+mod recherche;
+mod search;
+mod soin;
+mod stats;
 mod tag;
 mod utilisateur;
 
@@ -21,102 +30,147 @@ async fn hello() -> HttpResponse {
     }))
 }
 
-pub fn configure_routes(cfg: &mut ServiceConfig, db: DbConn) {
+pub fn configure_routes(cfg: &mut ServiceConfig, db: DbConn, limiter: RateLimiter) {
     let db_data = web::Data::new(db.clone());
 
     cfg.app_data(db_data.clone())
+        .app_data(web::Data::new(limiter.clone()))
         .route("/", web::get().to(hello))
+        .service(
+            web::scope("/admin")
+            .wrap(RoleGuard::admin())
+            .wrap(AuthMiddleware::new(db.clone()))
+            .configure(admin::configure_protected)
+        )
+        .service(
+            web::scope("/.well-known")
+            .configure(auth::configure_well_known)
+        )
         .service(
             web::scope("/connexion")
-            .configure(|c| auth::configure(c))
+            .wrap(RateLimit::login(limiter.clone()))
+            .configure(auth::configure)
         )
         .service(
             web::scope("/animaux/nouveau-profil")
             .wrap(RoleGuard::shelter())
             .wrap(AuthMiddleware::new(db.clone()))
-            .configure(|c| animal::configure_protected_creation(c))
+            .configure(animal::configure_protected_creation)
         )
         .service(
             web::scope("/animaux")
-            .configure(|c| animal::configure_public(c))
+            .configure(animal::configure_public)
             .service(
             web::scope("/{id}/requests")
                 .wrap(RoleGuard::shelter())
                 .wrap(AuthMiddleware::new(db.clone()))
-                .configure(|c| animal::configure_protected_req(c))
+                .configure(animal::configure_protected_req)
+            )
+            .service(
+            web::scope("/{id}/matches")
+                .wrap(RoleGuard::shelter())
+                .wrap(AuthMiddleware::new(db.clone()))
+                .configure(animal::configure_protected_matches)
             )
             .service(
             web::scope("/{id}/faire-une-demande")
                 .wrap(RoleGuard::foster())
                 .wrap(AuthMiddleware::new(db.clone()))
-                .configure(|c| animal::configure_protected_foster(c))
+                .configure(animal::configure_protected_foster)
+            )
+            .service(
+            web::scope("/{id}/soins")
+                    .wrap(RoleGuard::any_of(&[Role::Shelter, Role::Foster]))
+                .wrap(AuthMiddleware::new(db.clone()))
+                .configure(soin::configure_protected)
             )
         )
         .service(
             web::scope("/associations/inscription")
-            .configure(|c| association::configure_register(c))
+            .wrap(RateLimit::register(limiter.clone()))
+            .configure(association::configure_register)
         )
         .service(
             web::scope("/associations/profil")
-            .wrap(RoleGuard::shelter())
+            .wrap(RoleGuard::shelter().allow_pending())
             .wrap(AuthMiddleware::new(db.clone()))
-            .configure(|c| association::configure(c))
+            .configure(association::configure)
         )
         .service(
             web::scope("/associations")
-            .configure(|c| association::configure_public(c))
+            .configure(association::configure_public)
             .service(
                 web::scope("/{id}/fostered")
                 .wrap(RoleGuard::shelter())
                 .wrap(AuthMiddleware::new(db.clone()))
-                .configure(|c| association::configure_protected_fostered(c))
+                .configure(association::configure_protected_fostered)
             )
             .service(
                 web::scope("/{id}/requested")
                 .wrap(RoleGuard::shelter())
                 .wrap(AuthMiddleware::new(db.clone()))
-                .configure(|c| association::configure_protected_requested(c))
+                .configure(association::configure_protected_requested)
             )
         )
         .service(
             web::scope("/demandes")
-            .wrap(RoleGuard::shelter())
+            .wrap(RoleGuard::any_of(&[Role::Shelter, Role::Foster]))
             .wrap(AuthMiddleware::new(db.clone()))
-            .configure(|c| demande::configure_protected(c))
+            .configure(demande::configure_protected)
         )
         .service(
             web::scope("/especes")
-            .configure(|c| espece::configure_public(c))
+            .configure(espece::configure_public)
         )
         .service(
             web::scope("/famille/inscription")
-            .configure(|c| famille::configure_register(c))
+            .wrap(RateLimit::register(limiter.clone()))
+            .configure(famille::configure_register)
         )       
         .service(
             web::scope("/famille/profil")
             .wrap(RoleGuard::foster())
             .wrap(AuthMiddleware::new(db.clone()))
-            .configure(|c| famille::configure_protected(c))
+            .configure(recherche::configure_protected)
+            .configure(famille::configure_protected)
         )
         .service(
             web::scope("/media")
-            .configure(|c| media::configure_public(c))
+            .configure(media::configure_public)
+        )
+        .service(
+            web::scope("/search")
+            .configure(search::configure_public)
+        )
+        .service(
+            web::scope("/soins")
+            .wrap(RoleGuard::any_of(&[Role::Shelter, Role::Foster]))
+            .wrap(AuthMiddleware::new(db.clone()))
+            .configure(soin::configure_reminders)
+        )
+        .service(
+            web::scope("/stats")
+            .configure(stats::configure_public)
         )
         .service(
             web::scope("/upload")
-            .configure(|c| media::configure_protected(c))
+            .wrap(RoleGuard::shelter_or_admin().allow_pending())
+            .wrap(AuthMiddleware::new(db.clone()))
+            .configure(media::configure_protected)
         )
         .service(web::scope("/tags/create")
-        .wrap(RoleGuard::shelter())
+            .wrap(RoleGuard::shelter_or_admin())
             .wrap(AuthMiddleware::new(db.clone()))
-            .configure(|c| tag::configure_protected(c))
+            .configure(tag::configure_protected)
         )
         .service(
             web::scope("/tags")
-            .configure(|c| tag::configure_public(c))
+            .configure(tag::configure_public)
         )
         .service(
             web::scope("/users")
-            .configure(|c| utilisateur::configure_protected(c))
+            .wrap(RoleGuard::authenticated().allow_pending())
+            .wrap(AuthMiddleware::new(db.clone()))
+            .configure(utilisateur::configure_protected)
         );
 }
\ No newline at end of file
@@ -0,0 +1,175 @@
+use actix_web::{HttpResponse, web};
+use chrono::Utc;
+use log::info;
+use sea_orm::{DbConn, TransactionTrait};
+use validator::Validate;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::utilisateur::{get_alerts, mark_alert_read};
+use crate::auth::{AuthenticatedUser, CustomError};
+use crate::database::models::sea_orm_active_enums::{FrequenceAlerte, Sexe, Taille};
+use crate::database::models::{RechercheActiveModel, RechercheTagActiveModel};
+use crate::database::repositories::RechercheRepository;
+use crate::validators::common_validators::process_json_validation;
+
+use sea_orm::ActiveValue::Set;
+
+pub fn configure_protected(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/recherches")
+            .get(get_saved_searches)
+            .post(create_saved_search)
+        )
+        .service(web::resource("/recherches/{id}")
+            .delete(delete_saved_search)
+        )
+        .service(web::resource("/alertes")
+            .get(get_alerts)
+        )
+        .service(web::resource("/alertes/{id}/lue")
+            .post(mark_alert_read)
+        );
+}
+
+#[derive(Deserialize, Serialize, Validate)]
+pub struct RechercheCreate {
+    #[validate(length(
+        min = 2,
+        max = 50,
+        message = "Your search's name must be between 2 and 50 characters"
+    ))]
+    pub nom: String,
+    pub espece_id: Option<i32>,
+    pub sexe: Option<Sexe>,
+    #[validate(range(
+        min = 0,
+        max = 100,
+        message = "Age must be realistic"
+    ))]
+    pub age_min: Option<i32>,
+    #[validate(range(
+        min = 0,
+        max = 100,
+        message = "Age must be realistic"
+    ))]
+    pub age_max: Option<i32>,
+    /// Locations are department centroids, see `geo::distance::MIN_RADIUS_KM`.
+    #[validate(range(
+        min = 50,
+        max = 500,
+        message = "Distance must be between 50 and 500 km"
+    ))]
+    pub rayon_km: Option<i32>,
+    pub taille: Option<Taille>,
+    #[serde(default)]
+    pub ok_enfants: bool,
+    #[serde(default)]
+    pub ok_chiens: bool,
+    #[serde(default)]
+    pub ok_chats: bool,
+    #[serde(default)]
+    pub tags: Vec<i32>,
+    pub frequence: FrequenceAlerte,
+}
+
+pub async fn get_saved_searches(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let foster_id = current_user.foster_id()?;
+
+    let searches = RechercheRepository::new(db.get_ref())
+        .find_by_foster(foster_id)
+        .await
+        .map_err(|_e| CustomError::NotFound)?;
+
+    Ok(HttpResponse::Ok().json(searches))
+}
+
+pub async fn create_saved_search(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+    json_search: web::Json<RechercheCreate>,
+) -> Result<HttpResponse, CustomError> {
+    process_json_validation(&json_search)?;
+
+    let foster_id = current_user.foster_id()?;
+    let search = json_search.into_inner();
+
+    if let (Some(age_min), Some(age_max)) = (search.age_min, search.age_max)
+        && age_min > age_max
+    {
+        return Err(CustomError::ValidationError {
+            error_messages: "age_max: Maximum age must be greater than minimum age".to_string(),
+        });
+    }
+
+    let now = Utc::now().naive_utc();
+
+    let search_model = RechercheActiveModel {
+        famille_id: Set(foster_id),
+        nom: Set(search.nom),
+        espece_id: Set(search.espece_id),
+        sexe: Set(search.sexe),
+        age_min: Set(search.age_min),
+        age_max: Set(search.age_max),
+        rayon_km: Set(search.rayon_km),
+        taille: Set(search.taille),
+        ok_enfants: Set(search.ok_enfants),
+        ok_chiens: Set(search.ok_chiens),
+        ok_chats: Set(search.ok_chats),
+        frequence: Set(search.frequence),
+        derniere_notification: Set(Some(now)),
+        cree_le: Set(now),
+        ..Default::default()
+    };
+
+    let txn = db.begin().await.map_err(|_e| CustomError::CreationError)?;
+    let repo = RechercheRepository::new(&txn);
+
+    let created_search = repo
+        .create(search_model)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+
+    for tag in search.tags {
+        let search_tag_model = RechercheTagActiveModel {
+            recherche_id: Set(created_search.id),
+            tag_id: Set(tag),
+        };
+
+        repo.add_tag(search_tag_model)
+            .await
+            .map_err(|_e| CustomError::CreationError)?;
+    }
+
+    txn.commit().await.map_err(|_e| CustomError::CreationError)?;
+
+    info!("Saved search created with ID: {}", created_search.id);
+    Ok(HttpResponse::Created().json(created_search))
+}
+
+pub async fn delete_saved_search(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let search_id = path.into_inner();
+    let foster_id = current_user.foster_id()?;
+    let repo = RechercheRepository::new(db.get_ref());
+
+    let search = repo
+        .find_by_id(search_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+    if search.famille_id != foster_id {
+        return Err(CustomError::Forbidden);
+    }
+
+    repo.delete(search_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
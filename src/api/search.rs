@@ -0,0 +1,46 @@
+use actix_web::{HttpResponse, web};
+use sea_orm::DbConn;
+use serde::Deserialize;
+
+use crate::auth::CustomError;
+use crate::database::repositories::{SearchKind, SearchRepository};
+use crate::dto::AgeQuery;
+
+const DEFAULT_LIMIT: u64 = 20;
+const MAX_LIMIT: u64 = 100;
+const MAX_QUERY_LENGTH: usize = 200;
+
+pub fn configure_public(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("")
+            .get(search)
+        );
+}
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    #[serde(rename = "type")]
+    pub kind: Option<SearchKind>,
+    pub limit: Option<u64>,
+}
+
+pub async fn search(
+    db: web::Data<DbConn>,
+    query: web::Query<SearchQuery>,
+    age: web::Query<AgeQuery>,
+) -> Result<HttpResponse, CustomError> {
+    let text = query.q.trim();
+    if text.is_empty() || text.chars().count() > MAX_QUERY_LENGTH {
+        return Err(CustomError::BadClientData);
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let birth_dates = age.birth_dates()?;
+
+    let hits = SearchRepository::new(db.get_ref())
+        .search(text, query.kind, birth_dates, limit)
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok().json(hits))
+}
@@ -0,0 +1,606 @@
+use actix_files::NamedFile;
+use actix_multipart::form::MultipartForm;
+use actix_web::{HttpResponse, web};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use log::info;
+use sea_orm::ActiveValue::Set;
+use sea_orm::entity::prelude::HasMany;
+use sea_orm::{Condition, DbConn, IntoActiveModel};
+use serde::Deserialize;
+use std::collections::HashMap;
+use validator::Validate;
+
+use crate::api::media::{DocumentUploadForm, store_document};
+use crate::auth::{AuthenticatedUser, CustomError, Policy, Role};
+use crate::database::models::{
+    FicheSoinsActiveModel, MediaActiveModel, MediaModel, PeseeActiveModel, TraitementActiveModel, VaccinationActiveModel,
+    VisiteVeterinaireActiveModel, VisiteVeterinaireModel,
+};
+use crate::database::repositories::{AnimalRepository, MediaRepository, SoinRepository};
+use crate::dto::RappelQuery;
+use crate::services::SoinService;
+use crate::services::account_service::remove_static_file;
+use crate::validators::common_validators::{process_json_validation, validate_microchip};
+
+const DEFAULT_REMINDER_DAYS: u32 = 30;
+
+pub fn configure_protected(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("")
+            .get(get_care_record)
+        )
+        .service(web::resource("/fiche")
+            .put(save_health_sheet)
+        )
+        .service(web::resource("/vaccinations")
+            .post(create_vaccination)
+        )
+        .service(web::resource("/vaccinations/{soin_id}")
+            .put(update_vaccination)
+            .delete(delete_vaccination)
+        )
+        .service(web::resource("/traitements")
+            .post(create_treatment)
+        )
+        .service(web::resource("/traitements/{soin_id}")
+            .put(update_treatment)
+            .delete(delete_treatment)
+        )
+        .service(web::resource("/visites")
+            .post(create_visit)
+        )
+        .service(web::resource("/visites/{soin_id}")
+            .put(update_visit)
+            .delete(delete_visit)
+        )
+        .service(web::resource("/visites/{soin_id}/documents")
+            .post(upload_visit_document)
+        )
+        .service(web::resource("/pesees")
+            .post(add_weight)
+        )
+        .service(web::resource("/documents/{media_id}")
+            .get(get_visit_document)
+            .delete(delete_visit_document)
+        );
+}
+
+pub fn configure_reminders(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/rappels")
+            .get(get_reminders)
+        );
+}
+
+#[derive(Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct FicheSoinsInput {
+    pub sterilise: bool,
+    #[validate(custom(function = validate_microchip))]
+    pub numero_puce: Option<String>,
+    #[validate(length(max = 500, message = "The diet must be at most 500 characters"))]
+    pub regime_alimentaire: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct VaccinationInput {
+    #[validate(length(min = 2, max = 100, message = "Vaccine must be between 2 and 100 characters"))]
+    pub vaccin: String,
+    pub administre_le: NaiveDate,
+    pub rappel_le: Option<NaiveDate>,
+    #[validate(length(max = 100, message = "Vet name must be at most 100 characters"))]
+    pub veterinaire: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct TraitementInput {
+    #[validate(length(min = 2, max = 100, message = "Medication must be between 2 and 100 characters"))]
+    pub medicament: String,
+    #[validate(length(min = 1, max = 200, message = "Dosage must be between 1 and 200 characters"))]
+    pub posologie: String,
+    pub debut: NaiveDate,
+    pub fin: Option<NaiveDate>,
+    #[validate(range(min = 1, max = 365, message = "Frequency must be between 1 and 365 days"))]
+    pub frequence_jours: Option<i32>,
+}
+
+#[derive(Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct VisiteInput {
+    pub prevue_le: NaiveDateTime,
+    #[validate(length(min = 3, max = 200, message = "Reason must be between 3 and 200 characters"))]
+    pub motif: String,
+    #[validate(length(max = 100, message = "Vet name must be at most 100 characters"))]
+    pub veterinaire: Option<String>,
+    #[validate(length(max = 5000, message = "Report must be at most 5000 characters"))]
+    pub compte_rendu: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct PeseeInput {
+    #[validate(range(min = 0.01, max = 150.0, message = "Weight must be realistic"))]
+    pub poids_kg: f64,
+    /// Today when missing.
+    pub pesee_le: Option<NaiveDate>,
+}
+
+impl VaccinationInput {
+    fn check_dates(&self) -> Result<(), CustomError> {
+        match self.rappel_le {
+            Some(rappel_le) if rappel_le <= self.administre_le => Err(CustomError::ValidationError {
+                error_messages: "rappel_le: The booster must be due after the vaccination".to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl TraitementInput {
+    fn check_dates(&self) -> Result<(), CustomError> {
+        match self.fin {
+            Some(fin) if fin < self.debut => Err(CustomError::ValidationError {
+                error_messages: "fin: The treatment cannot end before it starts".to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Shelters edit the record of their animals, the foster hosting an animal can read it and add entries.
+fn shelter_or_host(animal_id: i32) -> Policy {
+    Policy::AnyOf(vec![Policy::OwnsAnimal(animal_id), Policy::HostsAnimal(animal_id)])
+}
+
+async fn find_visit_of(db: &DbConn, animal_id: i32, visit_id: i32) -> Result<VisiteVeterinaireModel, CustomError> {
+    SoinRepository::new(db)
+        .find_visit(visit_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .filter(|visit| visit.animal_id == animal_id)
+        .ok_or(CustomError::NotFound)
+}
+
+async fn find_document_of(db: &DbConn, animal_id: i32, media_id: i32) -> Result<MediaModel, CustomError> {
+    let document = MediaRepository::new(db)
+        .find_by_id(media_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+    let visit_id = document.visite_id.ok_or(CustomError::NotFound)?;
+    find_visit_of(db, animal_id, visit_id).await?;
+
+    Ok(document)
+}
+
+pub async fn get_care_record(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), shelter_or_host(animal_id)).await?;
+
+    let record = SoinService::new(db.get_ref())
+        .dossier(animal_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok().json(record))
+}
+
+pub async fn save_health_sheet(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+    json_sheet: web::Json<FicheSoinsInput>,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+    process_json_validation(&json_sheet)?;
+    let sheet = json_sheet.into_inner();
+
+    let sheet_model = FicheSoinsActiveModel {
+        animal_id: Set(animal_id),
+        sterilise: Set(sheet.sterilise),
+        numero_puce: Set(sheet.numero_puce),
+        regime_alimentaire: Set(sheet.regime_alimentaire),
+        modifiee_le: Set(Utc::now().naive_utc()),
+    };
+
+    let saved_sheet = SoinRepository::new(db.get_ref())
+        .save_fiche(sheet_model)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?;
+
+    Ok(HttpResponse::Ok().json(saved_sheet))
+}
+
+pub async fn create_vaccination(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+    json_vaccination: web::Json<VaccinationInput>,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), shelter_or_host(animal_id)).await?;
+    process_json_validation(&json_vaccination)?;
+    json_vaccination.check_dates()?;
+    let vaccination = json_vaccination.into_inner();
+
+    let vaccination_model = VaccinationActiveModel {
+        animal_id: Set(animal_id),
+        vaccin: Set(vaccination.vaccin),
+        administre_le: Set(vaccination.administre_le),
+        rappel_le: Set(vaccination.rappel_le),
+        veterinaire: Set(vaccination.veterinaire),
+        ajoute_par: Set(Some(current_user.user_id)),
+        ..Default::default()
+    };
+
+    let created_vaccination = SoinRepository::new(db.get_ref())
+        .create_vaccination(vaccination_model)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+
+    info!("Vaccination {} recorded for animal with ID {}", created_vaccination.id, animal_id);
+    Ok(HttpResponse::Created().json(created_vaccination))
+}
+
+pub async fn update_vaccination(
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i32)>,
+    current_user: AuthenticatedUser,
+    json_vaccination: web::Json<VaccinationInput>,
+) -> Result<HttpResponse, CustomError> {
+    let (animal_id, vaccination_id) = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+    process_json_validation(&json_vaccination)?;
+    json_vaccination.check_dates()?;
+    let vaccination = json_vaccination.into_inner();
+
+    let repo = SoinRepository::new(db.get_ref());
+    let mut vaccination_model = repo
+        .find_vaccination(vaccination_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .filter(|existing| existing.animal_id == animal_id)
+        .ok_or(CustomError::NotFound)?
+        .into_active_model();
+
+    vaccination_model.vaccin = Set(vaccination.vaccin);
+    vaccination_model.administre_le = Set(vaccination.administre_le);
+    vaccination_model.rappel_le = Set(vaccination.rappel_le);
+    vaccination_model.veterinaire = Set(vaccination.veterinaire);
+
+    let updated_vaccination = repo
+        .update_vaccination(vaccination_model)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?;
+
+    Ok(HttpResponse::Ok().json(updated_vaccination))
+}
+
+pub async fn delete_vaccination(
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i32)>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let (animal_id, vaccination_id) = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+
+    let repo = SoinRepository::new(db.get_ref());
+    repo.find_vaccination(vaccination_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .filter(|existing| existing.animal_id == animal_id)
+        .ok_or(CustomError::NotFound)?;
+
+    repo.delete_vaccination(vaccination_id)
+        .await
+        .map_err(|_e| CustomError::DeletionError)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn create_treatment(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+    json_treatment: web::Json<TraitementInput>,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), shelter_or_host(animal_id)).await?;
+    process_json_validation(&json_treatment)?;
+    json_treatment.check_dates()?;
+    let treatment = json_treatment.into_inner();
+
+    let treatment_model = TraitementActiveModel {
+        animal_id: Set(animal_id),
+        medicament: Set(treatment.medicament),
+        posologie: Set(treatment.posologie),
+        debut: Set(treatment.debut),
+        fin: Set(treatment.fin),
+        frequence_jours: Set(treatment.frequence_jours.unwrap_or(1)),
+        ajoute_par: Set(Some(current_user.user_id)),
+        ..Default::default()
+    };
+
+    let created_treatment = SoinRepository::new(db.get_ref())
+        .create_treatment(treatment_model)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+
+    info!("Treatment {} recorded for animal with ID {}", created_treatment.id, animal_id);
+    Ok(HttpResponse::Created().json(created_treatment))
+}
+
+pub async fn update_treatment(
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i32)>,
+    current_user: AuthenticatedUser,
+    json_treatment: web::Json<TraitementInput>,
+) -> Result<HttpResponse, CustomError> {
+    let (animal_id, treatment_id) = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+    process_json_validation(&json_treatment)?;
+    json_treatment.check_dates()?;
+    let treatment = json_treatment.into_inner();
+
+    let repo = SoinRepository::new(db.get_ref());
+    let mut treatment_model = repo
+        .find_treatment(treatment_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .filter(|existing| existing.animal_id == animal_id)
+        .ok_or(CustomError::NotFound)?
+        .into_active_model();
+
+    treatment_model.medicament = Set(treatment.medicament);
+    treatment_model.posologie = Set(treatment.posologie);
+    treatment_model.debut = Set(treatment.debut);
+    treatment_model.fin = Set(treatment.fin);
+    treatment_model.frequence_jours = Set(treatment.frequence_jours.unwrap_or(1));
+
+    let updated_treatment = repo
+        .update_treatment(treatment_model)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?;
+
+    Ok(HttpResponse::Ok().json(updated_treatment))
+}
+
+pub async fn delete_treatment(
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i32)>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let (animal_id, treatment_id) = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+
+    let repo = SoinRepository::new(db.get_ref());
+    repo.find_treatment(treatment_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .filter(|existing| existing.animal_id == animal_id)
+        .ok_or(CustomError::NotFound)?;
+
+    repo.delete_treatment(treatment_id)
+        .await
+        .map_err(|_e| CustomError::DeletionError)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn create_visit(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+    json_visit: web::Json<VisiteInput>,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), shelter_or_host(animal_id)).await?;
+    process_json_validation(&json_visit)?;
+    let visit = json_visit.into_inner();
+
+    let visit_model = VisiteVeterinaireActiveModel {
+        animal_id: Set(animal_id),
+        prevue_le: Set(visit.prevue_le),
+        motif: Set(visit.motif),
+        veterinaire: Set(visit.veterinaire),
+        compte_rendu: Set(visit.compte_rendu),
+        ajoute_par: Set(Some(current_user.user_id)),
+        ..Default::default()
+    };
+
+    let created_visit = SoinRepository::new(db.get_ref())
+        .create_visit(visit_model)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+
+    info!("Vet visit {} recorded for animal with ID {}", created_visit.id, animal_id);
+    Ok(HttpResponse::Created().json(created_visit))
+}
+
+pub async fn update_visit(
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i32)>,
+    current_user: AuthenticatedUser,
+    json_visit: web::Json<VisiteInput>,
+) -> Result<HttpResponse, CustomError> {
+    let (animal_id, visit_id) = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+    process_json_validation(&json_visit)?;
+    let visit = json_visit.into_inner();
+
+    let mut visit_model = find_visit_of(db.get_ref(), animal_id, visit_id).await?.into_active_model();
+    visit_model.prevue_le = Set(visit.prevue_le);
+    visit_model.motif = Set(visit.motif);
+    visit_model.veterinaire = Set(visit.veterinaire);
+    visit_model.compte_rendu = Set(visit.compte_rendu);
+
+    let updated_visit = SoinRepository::new(db.get_ref())
+        .update_visit(visit_model)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?;
+
+    Ok(HttpResponse::Ok().json(updated_visit))
+}
+
+pub async fn delete_visit(
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i32)>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let (animal_id, visit_id) = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+    find_visit_of(db.get_ref(), animal_id, visit_id).await?;
+
+    let repo = SoinRepository::new(db.get_ref());
+    let visits = repo
+        .find_visits(vec![animal_id])
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+    for visit in visits.into_iter().filter(|visit| visit.id == visit_id) {
+        if let HasMany::Loaded(documents) = visit.documents {
+            for document in documents {
+                remove_static_file(&document.url);
+            }
+        }
+    }
+
+    repo.delete_visit(visit_id)
+        .await
+        .map_err(|_e| CustomError::DeletionError)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Vet documents are kept with the visit, apart from the public pictures of the animal.
+pub async fn upload_visit_document(
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i32)>,
+    current_user: AuthenticatedUser,
+    MultipartForm(form): MultipartForm<DocumentUploadForm>,
+) -> Result<HttpResponse, CustomError> {
+    let (animal_id, visit_id) = path.into_inner();
+    current_user.authorize(db.get_ref(), shelter_or_host(animal_id)).await?;
+    find_visit_of(db.get_ref(), animal_id, visit_id).await?;
+
+    let file_path = store_document(form.file, &format!("/documents/animaux/{}", animal_id))?;
+
+    let media_model = MediaActiveModel {
+        url: Set(file_path),
+        ordre: Set(1),
+        visite_id: Set(Some(visit_id)),
+        ..Default::default()
+    };
+
+    let created_media = MediaRepository::new(db.get_ref())
+        .create(media_model)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+
+    info!("Document uploaded with ID {} for vet visit with ID {}", created_media.id, visit_id);
+    Ok(HttpResponse::Created().json(created_media))
+}
+
+pub async fn get_visit_document(
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i32)>,
+    current_user: AuthenticatedUser,
+) -> Result<NamedFile, CustomError> {
+    let (animal_id, media_id) = path.into_inner();
+    current_user.authorize(db.get_ref(), shelter_or_host(animal_id)).await?;
+    let document = find_document_of(db.get_ref(), animal_id, media_id).await?;
+
+    NamedFile::open_async(format!("./static{}", document.url))
+        .await
+        .map_err(|_e| CustomError::NotFound)
+}
+
+pub async fn delete_visit_document(
+    db: web::Data<DbConn>,
+    path: web::Path<(i32, i32)>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let (animal_id, media_id) = path.into_inner();
+    current_user.authorize(db.get_ref(), Policy::OwnsAnimal(animal_id)).await?;
+    let document = find_document_of(db.get_ref(), animal_id, media_id).await?;
+
+    MediaRepository::new(db.get_ref())
+        .delete(media_id)
+        .await
+        .map_err(|_e| CustomError::DeletionError)?;
+    remove_static_file(&document.url);
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn add_weight(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+    json_weight: web::Json<PeseeInput>,
+) -> Result<HttpResponse, CustomError> {
+    let animal_id = path.into_inner();
+    current_user.authorize(db.get_ref(), shelter_or_host(animal_id)).await?;
+    process_json_validation(&json_weight)?;
+    let weight = json_weight.into_inner();
+
+    let today = Utc::now().date_naive();
+    let pesee_le = weight.pesee_le.unwrap_or(today);
+    if pesee_le > today {
+        return Err(CustomError::ValidationError {
+            error_messages: "pesee_le: The weigh-in cannot be in the future".to_string(),
+        });
+    }
+
+    let weight_model = PeseeActiveModel {
+        animal_id: Set(animal_id),
+        poids_kg: Set(weight.poids_kg),
+        pesee_le: Set(pesee_le),
+        ajoute_par: Set(Some(current_user.user_id)),
+        ..Default::default()
+    };
+
+    let created_weight = AnimalRepository::new(db.get_ref())
+        .add_weight(weight_model)
+        .await
+        .map_err(|_e| CustomError::CreationError)?;
+
+    Ok(HttpResponse::Created().json(created_weight))
+}
+
+/// Care due in the coming days for the shelter's animals or the animals hosted by the foster.
+pub async fn get_reminders(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+    query: web::Query<RappelQuery>,
+) -> Result<HttpResponse, CustomError> {
+    let animal_repo = AnimalRepository::new(db.get_ref());
+
+    let animals: HashMap<i32, String> = match current_user.role {
+        Role::Shelter => animal_repo
+            .find_by_shelters(vec![current_user.shelter_id()?], Condition::all())
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .into_iter()
+            .map(|animal| (animal.id, animal.nom))
+            .collect(),
+        _ => animal_repo
+            .find_hosted_by(current_user.foster_id()?)
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .into_iter()
+            .map(|animal| (animal.id, animal.nom))
+            .collect(),
+    };
+
+    let reminders = SoinService::new(db.get_ref())
+        .reminders(animals, query.jours.unwrap_or(DEFAULT_REMINDER_DAYS).min(365))
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok().json(reminders))
+}
@@ -0,0 +1,29 @@
+use actix_web::http::header::{CacheControl, CacheDirective};
+use actix_web::{HttpResponse, web};
+use sea_orm::DbConn;
+
+use crate::auth::CustomError;
+use crate::services::PublicStatsCache;
+
+pub fn configure_public(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("")
+            .get(get_public_stats)
+        );
+}
+
+pub async fn get_public_stats(
+    db: web::Data<DbConn>,
+    cache: web::Data<PublicStatsCache>,
+) -> Result<HttpResponse, CustomError> {
+    let stats = cache
+        .get_or_refresh(db.get_ref())
+        .await
+        .map_err(|_e| CustomError::InternalError)?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(CacheControl(vec![
+            CacheDirective::Public,
+            CacheDirective::MaxAge(cache.ttl().as_secs() as u32),
+        ]))
+        .json(stats.as_ref()))
+}
@@ -4,9 +4,12 @@ use sea_orm::DbConn;
 use validator::Validate;
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
+use crate::api::merge_patch::{MergePatch, merge_patch};
 use crate::auth::CustomError;
-use crate::database::models::TagActiveModel;
+use crate::database::models::{TagActiveModel, TagModel};
+use crate::database::models::sea_orm_active_enums::ContrainteTag;
 use crate::database::repositories::TagRepository;
 use crate::validators::common_validators::{process_json_validation};
 
@@ -41,6 +44,44 @@ pub struct TagCreate {
         message = "Please describe this tag using between 3 and 50 characters"
     ))]
     pub description: String,
+    pub contrainte: Option<ContrainteTag>,
+}
+
+#[derive(Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct TagPatch {
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "Name must be between 3 and 50 characters"
+    ))]
+    pub nom: String,
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "Please describe this tag using between 3 and 50 characters"
+    ))]
+    pub description: String,
+    pub contrainte: Option<ContrainteTag>,
+}
+
+impl MergePatch for TagPatch {
+    type Model = TagModel;
+    type ActiveModel = TagActiveModel;
+
+    fn from_model(tag: &TagModel) -> Self {
+        Self {
+            nom: tag.nom.clone(),
+            description: tag.description.clone(),
+            contrainte: tag.contrainte.clone(),
+        }
+    }
+
+    fn apply(self, tag: &mut TagActiveModel) {
+        tag.nom.set_if_not_equals(self.nom);
+        tag.description.set_if_not_equals(self.description);
+        tag.contrainte.set_if_not_equals(self.contrainte);
+    }
 }
 
 pub async fn get_tags(db: web::Data<DbConn>) -> Result<HttpResponse, CustomError> {
@@ -87,6 +128,7 @@ pub async fn create_tag(
     let tag_model = TagActiveModel {
         nom: Set(tag.nom),
         description: Set(tag.description),
+        contrainte: Set(tag.contrainte),
         ..Default::default()
     };
 
@@ -97,4 +139,32 @@ pub async fn create_tag(
 
     info!("Tag created with ID: {}", created_tag.id);
     Ok(HttpResponse::Created().json(created_tag))
-}
\ No newline at end of file
+}
+
+pub async fn patch_tag(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    patch: web::Json<Value>,
+) -> Result<HttpResponse, CustomError> {
+    let tag_id = path.into_inner();
+    let repo = TagRepository::new(db.get_ref());
+
+    let tag_data = repo
+        .find_by_id(tag_id)
+        .await
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+
+    let tag: TagPatch = merge_patch(&tag_data, patch.into_inner())?;
+
+    let mut tag_active_model: TagActiveModel = tag_data.into();
+    tag.apply(&mut tag_active_model);
+
+    let updated_tag = repo
+        .update(tag_active_model)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?;
+
+    info!("Tag with ID {} updated", tag_id);
+    Ok(HttpResponse::Ok().json(updated_tag))
+}
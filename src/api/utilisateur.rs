@@ -1,13 +1,22 @@
 use actix_web::{HttpResponse, web};
-use log::{info, warn};
+use log::info;
 use sea_orm::DbConn;
 
-use crate::auth::CustomError;
-use crate::database::repositories::UtilisateurRepository;
+use crate::auth::{AuthenticatedUser, CustomError, Policy};
+use crate::database::models::AlerteActiveModel;
+use crate::database::repositories::AlerteRepository;
+use crate::services::AccountService;
 
+use sea_orm::ActiveValue::Set;
 
 pub fn configure_protected(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("/{id}")
+    cfg.service(web::resource("/notifications")
+            .get(get_alerts)
+        )
+        .service(web::resource("/notifications/{id}/lue")
+            .post(mark_alert_read)
+        )
+        .service(web::resource("/{id}")
             .delete(delete_user)
         );
 }
@@ -15,30 +24,57 @@ pub fn configure_protected(cfg: &mut web::ServiceConfig) {
 pub async fn delete_user(
     db: web::Data<DbConn>,
     path: web::Path<i32>,
+    current_user: AuthenticatedUser,
 ) -> Result<HttpResponse, CustomError> {
     let user_id = path.into_inner();
-    let repo = UtilisateurRepository::new(db.get_ref());
+    current_user.authorize(db.get_ref(), Policy::IsAccount(user_id)).await?;
 
     info!("Attempting to delete user with ID: {}", user_id);
 
-    let user = repo
-        .find_by_id(user_id)
+    AccountService::new(db.get_ref())
+        .delete_account(user_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Inbox of the account: saved-search alerts for fosters and notices from the admins.
+pub async fn get_alerts(
+    db: web::Data<DbConn>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let alerts = AlerteRepository::new(db.get_ref())
+        .find_published_by_user(current_user.user_id)
         .await
         .map_err(|_e| CustomError::InternalError)?;
-    if user.is_none() {
-        return Err(CustomError::NotFound);
-    }
 
-    let delete_result = repo
-        .delete(user_id)
+    Ok(HttpResponse::Ok().json(alerts))
+}
+
+pub async fn mark_alert_read(
+    db: web::Data<DbConn>,
+    path: web::Path<i32>,
+    current_user: AuthenticatedUser,
+) -> Result<HttpResponse, CustomError> {
+    let alert_id = path.into_inner();
+    let repo = AlerteRepository::new(db.get_ref());
+
+    let alert = repo
+        .find_by_id(alert_id)
         .await
-        .map_err(|_e| CustomError::DeletionError)?;
-
-    if delete_result.rows_affected > 0 {
-        info!("User with ID {} successfully deleted", user_id);
-        Ok(HttpResponse::NoContent().finish())
-    } else {
-        warn!("User with ID {} was not deleted (0 rows affected)", user_id);
-        Err(CustomError::DeletionError)
+        .map_err(|_e| CustomError::InternalError)?
+        .ok_or(CustomError::NotFound)?;
+    if alert.utilisateur_id != current_user.user_id {
+        return Err(CustomError::Forbidden);
     }
-}
\ No newline at end of file
+
+    let mut alert_active_model: AlerteActiveModel = alert.into();
+    alert_active_model.lue = Set(true);
+
+    let updated_alert = repo
+        .update(alert_active_model)
+        .await
+        .map_err(|_e| CustomError::UpdateError)?;
+
+    Ok(HttpResponse::Ok().json(updated_alert))
+}
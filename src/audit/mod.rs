@@ -0,0 +1,169 @@
+use sea_orm::prelude::Json;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::cell::Cell;
+use std::future::Future;
+
+use crate::auth::Role;
+use crate::database::models::sea_orm_active_enums::ActionAudit;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Actor {
+    pub user_id: i32,
+    pub role: Role,
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestMetadata {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub methode: String,
+    pub chemin: String,
+}
+
+/// State of the request being served, read by the repositories when they write an audit entry.
+pub struct AuditContext {
+    pub metadata: RequestMetadata,
+    actor: Cell<Option<Actor>>,
+}
+
+tokio::task_local! {
+    static AUDIT_CONTEXT: AuditContext;
+}
+
+/// Runs `future` with `metadata` available to every audit entry it writes.
+pub async fn scope<F: Future>(metadata: RequestMetadata, future: F) -> F::Output {
+    let context = AuditContext {
+        metadata,
+        actor: Cell::new(None),
+    };
+
+    AUDIT_CONTEXT.scope(context, future).await
+}
+
+/// Called once the caller is authenticated. No-op outside of a request.
+pub fn set_actor(actor: Actor) {
+    let _ = AUDIT_CONTEXT.try_with(|context| context.actor.set(Some(actor)));
+}
+
+/// Actor and metadata of the current request, both absent for background jobs.
+pub fn current() -> (Option<Actor>, Option<RequestMetadata>) {
+    AUDIT_CONTEXT
+        .try_with(|context| (context.actor.get(), Some(context.metadata.clone())))
+        .unwrap_or((None, None))
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub action: ActionAudit,
+    pub type_entite: &'static str,
+    pub entite_id: Option<i32>,
+    pub association_id: Option<i32>,
+    pub diff: Json,
+    pub acteur: Option<Actor>,
+}
+
+impl AuditEntry {
+    pub fn new(action: ActionAudit, type_entite: &'static str, entite_id: Option<i32>) -> Self {
+        Self {
+            action,
+            type_entite,
+            entite_id,
+            association_id: None,
+            diff: json!({}),
+            acteur: None,
+        }
+    }
+
+    pub fn created<T: Serialize>(type_entite: &'static str, entite_id: i32, after: &T) -> Self {
+        Self::new(ActionAudit::Création, type_entite, Some(entite_id)).with_diff(diff(type_entite, None, Some(after)))
+    }
+
+    pub fn updated<T: Serialize>(type_entite: &'static str, entite_id: i32, before: Option<&T>, after: &T) -> Self {
+        Self::new(ActionAudit::Modification, type_entite, Some(entite_id))
+            .with_diff(diff(type_entite, before, Some(after)))
+    }
+
+    pub fn deleted<T: Serialize>(type_entite: &'static str, entite_id: i32, before: Option<&T>) -> Self {
+        Self::new(ActionAudit::Suppression, type_entite, Some(entite_id)).with_diff(diff(type_entite, before, None))
+    }
+
+    pub fn with_action(mut self, action: ActionAudit) -> Self {
+        self.action = action;
+        self
+    }
+
+    pub fn with_diff(mut self, diff: Json) -> Self {
+        self.diff = diff;
+        self
+    }
+
+    /// Makes the entry visible to the given shelter.
+    pub fn for_shelter(mut self, association_id: Option<i32>) -> Self {
+        self.association_id = association_id;
+        self
+    }
+
+    /// Overrides the actor of the request, e.g. on login where the caller is not authenticated yet.
+    pub fn by(mut self, actor: Actor) -> Self {
+        self.acteur = Some(actor);
+        self
+    }
+}
+
+/// Value stored in place of personal data.
+pub const REDACTED: &str = "***";
+
+/// Columns holding personal data, per entity type. The log is append-only and would keep them
+/// past anonymization and purges, so only the fact that they changed is recorded.
+fn personal_fields(type_entite: &str) -> &'static [&'static str] {
+    match type_entite {
+        "famille" => &["prenom", "nom", "telephone", "rue", "commune", "code_postal", "latitude", "longitude"],
+        "association" => &["responsable", "telephone"],
+        "utilisateur" => &["email"],
+        _ => &[],
+    }
+}
+
+fn redact(value: &Value) -> Value {
+    match value {
+        Value::Null => Value::Null,
+        _ => Value::String(REDACTED.to_string()),
+    }
+}
+
+/// Changed scalar fields as `{"champ": {"avant": .., "apres": ..}}`, personal data being redacted.
+/// Loaded relations are left out, as are fields skipped by the serializer such as password hashes.
+pub fn diff<T: Serialize>(type_entite: &str, before: Option<&T>, after: Option<&T>) -> Json {
+    let personal = personal_fields(type_entite);
+    let before = fields(before);
+    let after = fields(after);
+
+    let mut changes = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        let old = before.get(key).unwrap_or(&Value::Null);
+        let new = after.get(key).unwrap_or(&Value::Null);
+        if old == new || changes.contains_key(key) {
+            continue;
+        }
+
+        let change = if personal.contains(&key.as_str()) {
+            json!({ "avant": redact(old), "apres": redact(new) })
+        } else {
+            json!({ "avant": old, "apres": new })
+        };
+        changes.insert(key.clone(), change);
+    }
+
+    Value::Object(changes)
+}
+
+fn fields<T: Serialize>(model: Option<&T>) -> Map<String, Value> {
+    match model.map(serde_json::to_value) {
+        Some(Ok(Value::Object(fields))) => fields
+            .into_iter()
+            .filter(|(_, value)| !value.is_array() && !value.is_object())
+            .collect(),
+        _ => Map::new(),
+    }
+}
@@ -0,0 +1,46 @@
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use futures::future::{Ready, ready};
+
+use crate::auth::CustomError;
+use crate::auth::role::Role;
+
+/// Identity resolved once by `AuthMiddleware` and handed to handlers as an extractor.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub user_id: i32,
+    pub email: String,
+    pub role: Role,
+    pub association_id: Option<i32>,
+    /// Whether an admin approved the shelter's registration.
+    pub association_approuvee: bool,
+    pub famille_id: Option<i32>,
+}
+
+impl AuthenticatedUser {
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+
+    pub fn shelter_id(&self) -> Result<i32, CustomError> {
+        self.association_id.ok_or(CustomError::NotFound)
+    }
+
+    pub fn foster_id(&self) -> Result<i32, CustomError> {
+        self.famille_id.ok_or(CustomError::NotFound)
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = CustomError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<AuthenticatedUser>()
+                .cloned()
+                .ok_or(CustomError::Unauthorized),
+        )
+    }
+}
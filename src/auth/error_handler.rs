@@ -1,4 +1,4 @@
-use actix_web::{error::ResponseError, http::StatusCode, HttpResponse};
+use actix_web::{error::ResponseError, http::{StatusCode, header}, HttpResponse};
 use derive_more::{Display, Error};
 use serde::Serialize;
 
@@ -33,6 +33,20 @@ pub enum CustomError {
     NotFound,
     #[display("Les informations saisies n'ont pas l'air correctes. Merci de réessayer.")]
     WrongLogin,
+    #[display("Vous devez être connecté pour accéder à cette ressource.")]
+    Unauthorized,
+    #[display("Vous n'avez pas les droits nécessaires pour effectuer cette action.")]
+    Forbidden,
+    #[display("Ces informations ont été modifiées entre-temps. Merci de les recharger avant de réessayer.")]
+    PreconditionFailed,
+    #[display("Merci de renvoyer la version que vous modifiez dans l'en-tête If-Match.")]
+    PreconditionRequired,
+    #[display("Votre refuge doit être vérifié avant de pouvoir publier des animaux.")]
+    UnverifiedShelter,
+    #[display("Votre inscription doit être validée par un administrateur avant de pouvoir effectuer cette action.")]
+    PendingApproval,
+    #[display("Trop de tentatives. Merci de réessayer dans {} secondes.", retry_after)]
+    TooManyRequests { retry_after: u64 },
 }
 
 impl CustomError {
@@ -49,6 +63,13 @@ impl CustomError {
             CustomError::ShelteredError => "Still sheltering".to_string(),
             CustomError::NotFound => "Not Found".to_string(),
             CustomError::WrongLogin => "Invalid Credentials".to_string(),
+            CustomError::Unauthorized => "Unauthorized".to_string(),
+            CustomError::Forbidden => "Forbidden".to_string(),
+            CustomError::PreconditionFailed => "Precondition Failed".to_string(),
+            CustomError::PreconditionRequired => "Precondition Required".to_string(),
+            CustomError::UnverifiedShelter => "Unverified Shelter".to_string(),
+            CustomError::PendingApproval => "Pending Approval".to_string(),
+            CustomError::TooManyRequests { .. } => "Too Many Requests".to_string(),
         }
     }
 }
@@ -60,7 +81,11 @@ impl ResponseError for CustomError {
             message: self.to_string(),
             error: self.name(),
         };
-        HttpResponse::build(self.status_code()).json(error_response)
+        let mut response = HttpResponse::build(self.status_code());
+        if let CustomError::TooManyRequests { retry_after } = self {
+            response.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+        }
+        response.json(error_response)
     }
     fn status_code(&self) -> StatusCode {
         match *self {
@@ -75,6 +100,13 @@ impl ResponseError for CustomError {
             CustomError::ShelteredError => StatusCode::BAD_REQUEST,
             CustomError::NotFound => StatusCode::NOT_FOUND,
             CustomError::WrongLogin => StatusCode::UNAUTHORIZED,
+            CustomError::Unauthorized => StatusCode::UNAUTHORIZED,
+            CustomError::Forbidden => StatusCode::FORBIDDEN,
+            CustomError::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+            CustomError::PreconditionRequired => StatusCode::PRECONDITION_REQUIRED,
+            CustomError::UnverifiedShelter => StatusCode::FORBIDDEN,
+            CustomError::PendingApproval => StatusCode::FORBIDDEN,
+            CustomError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
         }
     }
 }
\ No newline at end of file
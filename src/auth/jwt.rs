@@ -1,10 +1,11 @@
 use chrono::{Duration, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, TokenData, Validation, decode, encode};
-use once_cell::sync::Lazy;
+use jsonwebtoken::{Header, TokenData, Validation, decode, decode_header, encode};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::auth::CustomError;
+use crate::auth::keys::JWT_KEYS;
+use crate::auth::role::Role;
 use crate::database::models::UtilisateurModelEx;
 
 #[derive(Deserialize, Serialize)]
@@ -20,13 +21,11 @@ pub struct Claims {
     pub jti: String,
     pub user_id: i32,
     pub email: String,
-    pub role: String,
+    pub role: Role,
+    #[serde(default)]
+    pub version_jeton: i32,
 }
 
-pub static JWT_SECRET: Lazy<String> = Lazy::new(|| {
-    std::env::var("JWT_SECRET").unwrap_or_else(|_| "default_jwt_secret_for_development_only".into())
-});
-
 pub fn generate_uuid() -> String {
     Uuid::new_v4().to_string()
 }
@@ -40,13 +39,7 @@ pub fn generate_claims(user: &UtilisateurModelEx) -> Claims {
     let iat = Utc::now().timestamp() as usize;
     let jti = generate_uuid();
 
-    let mut role: String = format!(""); 
-    if !user.refuge.is_none() {
-        role = format!("SHELTER");
-    };
-    if !user.accueillant.is_none() {
-        role = format!("FOSTER");
-    };
+    let role = Role::from_user(user);
 
     Claims {
         sub: user.id.to_string(),
@@ -55,44 +48,42 @@ pub fn generate_claims(user: &UtilisateurModelEx) -> Claims {
         jti,
         user_id: user.id,
         email: user.email.clone(),
-        role: role,
+        role,
+        version_jeton: user.version_jeton,
     }
 }
 
 pub fn generate_token_from_claims(claims: &Claims) -> Result<String, CustomError> {
+    let mut header = Header::new(JWT_KEYS.algorithm);
+    header.kid = JWT_KEYS.kid.clone();
+
     encode(
-        &Header::default(),
+        &header,
         claims,
-        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+        &JWT_KEYS.encoding_key,
     )
     .map_err(|_e| {log::error!("Error generating token: {}", _e); CustomError::InternalError})
 }
 
 pub fn decode_jwt(token: &str) -> Result<TokenData<Claims>, CustomError> {
+  let header = decode_header(token).map_err(|_e| {
+        log::error!("JWT header error: {}", _e);
+        CustomError::BadClientData
+    })?;
+
+  let decoding_key = JWT_KEYS.decoding_key(header.kid.as_deref()).ok_or_else(|| {
+        log::error!("No JWT verification key for kid {:?}", header.kid);
+        CustomError::BadClientData
+    })?;
+
   let token_data = decode::<Claims>(
     token,
-    &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
-    &Validation::default(),
+    decoding_key,
+    &Validation::new(JWT_KEYS.algorithm),
   ).map_err(|_e| {
         log::error!("JWT validation error: {}", _e);
         CustomError::BadClientData
     })?;
 
   Ok(token_data)
-}
-
-pub fn extract_user_id_from_token(token: &str) -> Option<i32> {
-    let validation = Validation::default();
-
-    match decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
-        &validation,
-    ) {
-        Ok(token_data) => Some(token_data.claims.user_id),
-        Err(e) => {
-            log::error!("Failed to decode token: {:?}", e);
-            None
-        }
-    }
 }
\ No newline at end of file
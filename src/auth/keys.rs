@@ -0,0 +1,210 @@
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm, OctetKeyPairParameters,
+    OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use once_cell::sync::Lazy;
+use rsa::RsaPublicKey;
+use rsa::pkcs1::DecodeRsaPublicKey;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::traits::PublicKeyParts;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+use crate::config::ConfigError;
+
+pub const DEFAULT_JWT_SECRET: &str = "default_jwt_secret_for_development_only";
+
+const ED25519_SPKI_LENGTH: usize = 44;
+
+pub static JWT_KEYS: Lazy<JwtKeys> = Lazy::new(|| {
+    JwtKeys::from_env().unwrap_or_else(|e| {
+        log::error!("Invalid JWT configuration: {}", e);
+        std::process::exit(1)
+    })
+});
+
+pub struct JwtKeys {
+    pub algorithm: Algorithm,
+    pub kid: Option<String>,
+    pub encoding_key: EncodingKey,
+    pub decoding_keys: HashMap<String, DecodingKey>,
+    pub jwks: JwkSet,
+}
+
+pub fn is_dev_mode() -> bool {
+    matches!(
+        env::var("APP_ENV").unwrap_or_default().to_lowercase().as_str(),
+        "dev" | "development"
+    )
+}
+
+fn read_key_file(path: &str) -> Result<Vec<u8>, ConfigError> {
+    fs::read(path).map_err(|e| ConfigError::new(format!("Could not read JWT key file {}: {}", path, e)))
+}
+
+/// Parses `JWT_PUBLIC_KEYS`, a comma-separated list of `kid=path/to/public.pem`.
+fn public_key_paths() -> Result<Vec<(String, String)>, ConfigError> {
+    env::var("JWT_PUBLIC_KEYS")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (kid, path) = entry
+                .split_once('=')
+                .ok_or_else(|| ConfigError::new("JWT_PUBLIC_KEYS entries must be formatted as kid=path"))?;
+            Ok((kid.trim().to_string(), path.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Accepts both SPKI (`BEGIN PUBLIC KEY`) and PKCS#1 (`BEGIN RSA PUBLIC KEY`) encodings.
+fn rsa_jwk(kid: &str, pem: &[u8]) -> Result<Jwk, ConfigError> {
+    let invalid = || ConfigError::new(format!("RSA public key {} is not a valid PEM public key", kid));
+    let pem = std::str::from_utf8(pem).map_err(|_e| invalid())?;
+    let public_key = RsaPublicKey::from_public_key_pem(pem)
+        .or_else(|_e| RsaPublicKey::from_pkcs1_pem(pem))
+        .map_err(|_e| invalid())?;
+
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(KeyAlgorithm::RS256),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        },
+        algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+            key_type: RSAKeyType::RSA,
+            n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+            e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+        }),
+    })
+}
+
+fn ed25519_jwk(kid: &str, pem: &[u8]) -> Result<Jwk, ConfigError> {
+    let invalid = || ConfigError::new(format!("Ed25519 public key {} is not a valid SubjectPublicKeyInfo", kid));
+    let pem = std::str::from_utf8(pem).map_err(|_e| invalid())?;
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let der = STANDARD.decode(body).map_err(|_e| invalid())?;
+    if der.len() != ED25519_SPKI_LENGTH {
+        return Err(invalid());
+    }
+
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(KeyAlgorithm::EdDSA),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        },
+        algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: URL_SAFE_NO_PAD.encode(&der[ED25519_SPKI_LENGTH - 32..]),
+        }),
+    })
+}
+
+impl JwtKeys {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let algorithm = match env::var("JWT_ALGORITHM")
+            .unwrap_or_else(|_| "HS256".to_string())
+            .to_uppercase()
+            .as_str()
+        {
+            "HS256" => Algorithm::HS256,
+            "RS256" => Algorithm::RS256,
+            "EDDSA" => Algorithm::EdDSA,
+            _ => return Err(ConfigError::new("JWT_ALGORITHM must be one of HS256, RS256 or EdDSA")),
+        };
+
+        match algorithm {
+            Algorithm::HS256 => Self::from_secret(),
+            _ => Self::from_pem_files(algorithm),
+        }
+    }
+
+    fn from_secret() -> Result<Self, ConfigError> {
+        let secret = match env::var("JWT_SECRET") {
+            Ok(secret) if !secret.is_empty() && secret != DEFAULT_JWT_SECRET => secret,
+            _ if is_dev_mode() => {
+                log::warn!("JWT_SECRET is not set, using the development secret");
+                DEFAULT_JWT_SECRET.to_string()
+            }
+            _ => return Err(ConfigError::new("JWT_SECRET must be set in the .env file outside of development mode")),
+        };
+
+        let mut decoding_keys = HashMap::new();
+        decoding_keys.insert(String::new(), DecodingKey::from_secret(secret.as_bytes()));
+
+        Ok(JwtKeys {
+            algorithm: Algorithm::HS256,
+            kid: None,
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_keys,
+            jwks: JwkSet { keys: vec![] },
+        })
+    }
+
+    fn from_pem_files(algorithm: Algorithm) -> Result<Self, ConfigError> {
+        let private_key_path = env::var("JWT_PRIVATE_KEY_PATH")
+            .map_err(|_e| ConfigError::new("JWT_PRIVATE_KEY_PATH must be set in the .env file"))?;
+        let kid = env::var("JWT_KEY_ID").map_err(|_e| ConfigError::new("JWT_KEY_ID must be set in the .env file"))?;
+
+        let private_key = read_key_file(&private_key_path)?;
+        let encoding_key = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(&private_key),
+            _ => EncodingKey::from_ed_pem(&private_key),
+        }
+        .map_err(|_e| ConfigError::new("Invalid JWT private key"))?;
+
+        let mut decoding_keys = HashMap::new();
+        let mut jwks = JwkSet { keys: vec![] };
+
+        for (key_id, path) in public_key_paths()? {
+            let pem = read_key_file(&path)?;
+            let (decoding_key, jwk) = match algorithm {
+                Algorithm::RS256 => (DecodingKey::from_rsa_pem(&pem), rsa_jwk(&key_id, &pem)?),
+                _ => (DecodingKey::from_ed_pem(&pem), ed25519_jwk(&key_id, &pem)?),
+            };
+
+            let decoding_key =
+                decoding_key.map_err(|_e| ConfigError::new(format!("Invalid JWT public key {}", key_id)))?;
+            decoding_keys.insert(key_id, decoding_key);
+            jwks.keys.push(jwk);
+        }
+
+        if !decoding_keys.contains_key(&kid) {
+            return Err(ConfigError::new(format!(
+                "JWT_PUBLIC_KEYS must contain the public key of the active JWT_KEY_ID ({})",
+                kid
+            )));
+        }
+
+        log::info!("Loaded {} JWT verification key(s), signing with '{}'", decoding_keys.len(), kid);
+
+        Ok(JwtKeys {
+            algorithm,
+            kid: Some(kid),
+            encoding_key,
+            decoding_keys,
+            jwks,
+        })
+    }
+
+    /// Picks the verification key matching the token's `kid`.
+    /// Tokens signed with the shared secret carry no `kid`.
+    pub fn decoding_key(&self, kid: Option<&str>) -> Option<&DecodingKey> {
+        match (&self.kid, kid) {
+            (None, _) => self.decoding_keys.get(""),
+            (Some(_), Some(kid)) => self.decoding_keys.get(kid),
+            (Some(_), None) => None,
+        }
+    }
+}
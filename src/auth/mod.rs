@@ -1,7 +1,15 @@
+pub mod authenticated_user;
 pub mod jwt;
+pub mod keys;
 pub mod password;
+pub mod policy;
+pub mod role;
 pub mod error_handler;
 
-pub use jwt::{Claims, User, generate_claims, generate_token_from_claims, decode_jwt, extract_user_id_from_token};
+pub use authenticated_user::AuthenticatedUser;
+pub use jwt::{Claims, User, generate_claims, generate_token_from_claims, decode_jwt};
+pub use keys::{JWT_KEYS, JwtKeys, is_dev_mode};
+pub use policy::Policy;
+pub use role::Role;
 pub use password::{hash_password, verify_password};
 pub use error_handler::CustomError;
\ No newline at end of file
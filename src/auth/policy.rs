@@ -0,0 +1,99 @@
+use futures::future::LocalBoxFuture;
+use sea_orm::DbConn;
+
+use crate::auth::{AuthenticatedUser, CustomError, Role};
+use crate::database::models::sea_orm_active_enums::Statut;
+use crate::database::repositories::{AnimalRepository, DemandeRepository};
+
+/// Resource-level rules checked by handlers once the route's `RoleGuard` has passed.
+/// Admins satisfy every policy.
+#[derive(Debug, Clone)]
+pub enum Policy {
+    /// The user has one of the given roles.
+    HasRole(Vec<Role>),
+    /// The account with this id is the user's own.
+    IsAccount(i32),
+    /// The shelter profile with this id belongs to the user.
+    OwnsShelter(i32),
+    /// The animal with this id is sheltered by the user's association.
+    OwnsAnimal(i32),
+    /// The animal with this id is currently hosted by the user's foster profile.
+    HostsAnimal(i32),
+    /// The request with this id was made by the user's foster profile.
+    MadeRequest(i32),
+    /// The request with this id targets an animal sheltered by the user's association.
+    OwnsRequestedAnimal(i32),
+    /// At least one of the inner policies is satisfied.
+    AnyOf(Vec<Policy>),
+}
+
+impl Policy {
+    pub async fn authorize(&self, db: &DbConn, user: &AuthenticatedUser) -> Result<(), CustomError> {
+        if user.is_admin() || self.is_satisfied(db, user).await? {
+            Ok(())
+        } else {
+            log::warn!("User {} denied by policy {:?}", user.user_id, self);
+            Err(CustomError::Forbidden)
+        }
+    }
+
+    fn is_satisfied<'a>(&'a self, db: &'a DbConn, user: &'a AuthenticatedUser) -> LocalBoxFuture<'a, Result<bool, CustomError>> {
+        Box::pin(async move {
+            match self {
+                Policy::HasRole(roles) => Ok(roles.contains(&user.role)),
+                Policy::IsAccount(user_id) => Ok(user.user_id == *user_id),
+                Policy::OwnsShelter(shelter_id) => Ok(user.association_id == Some(*shelter_id)),
+                Policy::OwnsAnimal(animal_id) => {
+                    let animal = AnimalRepository::new(db)
+                        .find_model_by_id(*animal_id)
+                        .await
+                        .map_err(|_e| CustomError::InternalError)?
+                        .ok_or(CustomError::NotFound)?;
+
+                    Ok(user.association_id == Some(animal.association_id))
+                }
+                Policy::HostsAnimal(animal_id) => {
+                    let animal = AnimalRepository::new(db)
+                        .find_model_by_id(*animal_id)
+                        .await
+                        .map_err(|_e| CustomError::InternalError)?
+                        .ok_or(CustomError::NotFound)?;
+
+                    Ok(animal.statut == Statut::Accueilli && user.famille_id.is_some() && animal.famille_id == user.famille_id)
+                }
+                Policy::MadeRequest(request_id) => {
+                    let request = DemandeRepository::new(db)
+                        .find_model_by_id(*request_id)
+                        .await
+                        .map_err(|_e| CustomError::InternalError)?
+                        .ok_or(CustomError::NotFound)?;
+
+                    Ok(user.famille_id == Some(request.famille_id))
+                }
+                Policy::OwnsRequestedAnimal(request_id) => {
+                    let request = DemandeRepository::new(db)
+                        .find_model_by_id(*request_id)
+                        .await
+                        .map_err(|_e| CustomError::InternalError)?
+                        .ok_or(CustomError::NotFound)?;
+
+                    Policy::OwnsAnimal(request.animal_id).is_satisfied(db, user).await
+                }
+                Policy::AnyOf(policies) => {
+                    for policy in policies {
+                        if policy.is_satisfied(db, user).await? {
+                            return Ok(true);
+                        }
+                    }
+                    Ok(false)
+                }
+            }
+        })
+    }
+}
+
+impl AuthenticatedUser {
+    pub async fn authorize(&self, db: &DbConn, policy: Policy) -> Result<(), CustomError> {
+        policy.authorize(db, self).await
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::database::models::UtilisateurModelEx;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Role {
+    Admin,
+    Shelter,
+    Foster,
+    User,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Admin, Role::Shelter, Role::Foster, Role::User];
+
+    /// Admins take precedence, then the profile attached to the account.
+    pub fn from_user(user: &UtilisateurModelEx) -> Self {
+        if user.administrateur {
+            Role::Admin
+        } else if !user.refuge.is_none() {
+            Role::Shelter
+        } else if !user.accueillant.is_none() {
+            Role::Foster
+        } else {
+            Role::User
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let role = match self {
+            Role::Admin => "ADMIN",
+            Role::Shelter => "SHELTER",
+            Role::Foster => "FOSTER",
+            Role::User => "USER",
+        };
+        write!(f, "{}", role)
+    }
+}
@@ -1,10 +1,19 @@
 use serde::Deserialize;
 use std::env;
+use std::str::FromStr;
+
+use crate::auth::is_dev_mode;
+use crate::config::ConfigError;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct AppConfig {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
+    pub rate_limit: RateLimitConfig,
+    pub retention: RetentionConfig,
+    pub public_stats: PublicStatsConfig,
+    pub archive: ArchiveConfig,
+    pub company_registry: CompanyRegistryConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -18,8 +27,201 @@ pub struct DatabaseConfig {
     pub url: String,
 }
 
-impl AppConfig {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum RateLimitBackend {
+    Memory,
+    Postgres,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    pub backend: RateLimitBackend,
+    pub trust_proxy: bool,
+    pub login: ScopeLimits,
+    pub register: ScopeLimits,
+    pub lockout: LockoutConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScopeLimits {
+    pub ip: BucketLimits,
+    pub account: BucketLimits,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct BucketLimits {
+    pub burst: u32,
+    pub per_minute: u32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct LockoutConfig {
+    pub threshold: u32,
+    pub base_seconds: u64,
+    pub max_seconds: u64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RetentionConfig {
+    pub inactive_days: u32,
+    pub interval_hours: u64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ArchiveConfig {
+    pub retention_days: u32,
+    pub interval_hours: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum CompanyRegistryBackend {
+    Sirene,
+    Stub,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompanyRegistryConfig {
+    pub backend: CompanyRegistryBackend,
+    pub sirene_url: String,
+    pub sirene_api_key: String,
+    pub timeout_seconds: u64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct PublicStatsConfig {
+    pub cache_seconds: u64,
+}
+
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    match env::var(key) {
+        Ok(value) => value
+            .parse()
+            .unwrap_or_else(|_| panic!("{} must be a valid value in the .env file", key)),
+        Err(_) => default,
+    }
+}
+
+impl BucketLimits {
+    fn from_env(prefix: &str, burst: u32, per_minute: u32) -> Self {
+        let limits = BucketLimits {
+            burst: env_or(&format!("{}_BURST", prefix), burst),
+            per_minute: env_or(&format!("{}_PER_MINUTE", prefix), per_minute),
+        };
+
+        if limits.burst == 0 || limits.per_minute == 0 {
+            panic!("{}_BURST and {}_PER_MINUTE must be greater than 0", prefix, prefix);
+        }
+
+        limits
+    }
+}
+
+impl RateLimitConfig {
     pub fn from_env() -> Self {
+        let backend = match env::var("RATE_LIMIT_BACKEND")
+            .unwrap_or_else(|_| "memory".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "memory" => RateLimitBackend::Memory,
+            "postgres" => RateLimitBackend::Postgres,
+            _ => panic!("RATE_LIMIT_BACKEND must be either 'memory' or 'postgres'"),
+        };
+
+        RateLimitConfig {
+            backend,
+            trust_proxy: env_or("RATE_LIMIT_TRUST_PROXY", false),
+            login: ScopeLimits {
+                ip: BucketLimits::from_env("RATE_LIMIT_LOGIN_IP", 20, 10),
+                account: BucketLimits::from_env("RATE_LIMIT_LOGIN_ACCOUNT", 5, 2),
+            },
+            register: ScopeLimits {
+                ip: BucketLimits::from_env("RATE_LIMIT_REGISTER_IP", 5, 1),
+                account: BucketLimits::from_env("RATE_LIMIT_REGISTER_ACCOUNT", 3, 1),
+            },
+            lockout: LockoutConfig {
+                threshold: env_or("LOGIN_LOCKOUT_THRESHOLD", 5),
+                base_seconds: env_or("LOGIN_LOCKOUT_BASE_SECONDS", 30),
+                max_seconds: env_or("LOGIN_LOCKOUT_MAX_SECONDS", 3600),
+            },
+        }
+    }
+}
+
+impl RetentionConfig {
+    pub fn from_env() -> Self {
+        RetentionConfig {
+            inactive_days: env_or("RETENTION_INACTIVE_DAYS", 0),
+            interval_hours: env_or("RETENTION_INTERVAL_HOURS", 24),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inactive_days > 0
+    }
+}
+
+impl ArchiveConfig {
+    pub fn from_env() -> Self {
+        ArchiveConfig {
+            retention_days: env_or("ARCHIVE_RETENTION_DAYS", 90),
+            interval_hours: env_or("ARCHIVE_PURGE_INTERVAL_HOURS", 24),
+        }
+    }
+}
+
+impl PublicStatsConfig {
+    pub fn from_env() -> Self {
+        PublicStatsConfig {
+            cache_seconds: env_or("PUBLIC_STATS_CACHE_SECONDS", 300),
+        }
+    }
+}
+
+impl CompanyRegistryConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let default_backend = if is_dev_mode() { "stub" } else { "sirene" };
+        let backend = match env::var("COMPANY_REGISTRY_BACKEND")
+            .unwrap_or_else(|_| default_backend.to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "sirene" => CompanyRegistryBackend::Sirene,
+            "stub" => CompanyRegistryBackend::Stub,
+            other => {
+                return Err(ConfigError::new(format!(
+                    "COMPANY_REGISTRY_BACKEND must be either 'sirene' or 'stub', got '{}'",
+                    other
+                )));
+            }
+        };
+
+        let sirene_api_key = env::var("SIRENE_API_KEY").unwrap_or_default();
+        if backend == CompanyRegistryBackend::Sirene && sirene_api_key.is_empty() {
+            return Err(ConfigError::new(
+                "SIRENE_API_KEY must be set in the .env file when using the Sirene company registry",
+            ));
+        }
+
+        let timeout_seconds = match env::var("SIRENE_TIMEOUT_SECONDS") {
+            Ok(value) => value
+                .parse()
+                .map_err(|_| ConfigError::new("SIRENE_TIMEOUT_SECONDS must be a number of seconds"))?,
+            Err(_) => 5,
+        };
+
+        Ok(CompanyRegistryConfig {
+            backend,
+            sirene_url: env::var("SIRENE_API_URL")
+                .unwrap_or_else(|_| "https://api.insee.fr/api-sirene/3.11".to_string()),
+            sirene_api_key,
+            timeout_seconds,
+        })
+    }
+}
+
+impl AppConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
         let host = env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
         let port = env::var("SERVER_PORT")
             .unwrap_or_else(|_| "8000".to_string())
@@ -27,9 +229,14 @@ impl AppConfig {
             .expect("SERVER_PORT must be set in the .env file and must be a number");
         let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in the .env file");
 
-        AppConfig {
+        Ok(AppConfig {
             server: ServerConfig { host, port },
             database: DatabaseConfig { url: database_url },
-        }
+            rate_limit: RateLimitConfig::from_env(),
+            retention: RetentionConfig::from_env(),
+            public_stats: PublicStatsConfig::from_env(),
+            archive: ArchiveConfig::from_env(),
+            company_registry: CompanyRegistryConfig::from_env()?,
+        })
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,14 @@
+use derive_more::{Display, Error};
+
+/// Missing or invalid setting, reported once at startup.
+#[derive(Debug, Display, Error)]
+#[display("{}", message)]
+pub struct ConfigError {
+    message: String,
+}
+
+impl ConfigError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into() }
+    }
+}
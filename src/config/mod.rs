@@ -1,5 +1,17 @@
 mod app_config;
+mod error;
 
 pub use app_config::AppConfig;
+pub use app_config::ArchiveConfig;
+pub use app_config::BucketLimits;
+pub use app_config::CompanyRegistryBackend;
+pub use app_config::CompanyRegistryConfig;
 pub use app_config::DatabaseConfig;
-pub use app_config::ServerConfig;
\ No newline at end of file
+pub use app_config::LockoutConfig;
+pub use app_config::PublicStatsConfig;
+pub use app_config::RateLimitBackend;
+pub use app_config::RateLimitConfig;
+pub use app_config::RetentionConfig;
+pub use app_config::ScopeLimits;
+pub use app_config::ServerConfig;
+pub use error::ConfigError;
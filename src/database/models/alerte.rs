@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "alerte")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub utilisateur_id: i32,
+    /// Set on alerts produced by a saved search.
+    pub recherche_id: Option<i32>,
+    pub famille_id: Option<i32>,
+    pub animal_id: Option<i32>,
+    /// Set on notices sent by the admins.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub titre: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub message: Option<String>,
+    pub cree_le: DateTime,
+    pub publiee_le: Option<DateTime>,
+    pub lue: bool,
+    #[sea_orm(
+        belongs_to,
+        from = "utilisateur_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub utilisateur: HasOne<super::utilisateur::Entity>,
+    #[sea_orm(
+        belongs_to,
+        from = "recherche_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub recherche: HasOne<super::recherche::Entity>,
+    #[sea_orm(
+        belongs_to,
+        from = "animal_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub animal: HasOne<super::animal::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
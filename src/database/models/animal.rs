@@ -1,5 +1,8 @@
+use super::sea_orm_active_enums::NiveauEnergie;
+use super::sea_orm_active_enums::PrecisionNaissance;
 use super::sea_orm_active_enums::Sexe;
 use super::sea_orm_active_enums::Statut;
+use super::sea_orm_active_enums::Taille;
 use sea_orm::entity::prelude::*;
 
 #[sea_orm::model]
@@ -12,9 +15,13 @@ pub struct Model {
     pub nom: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub race: Option<String>,
+    /// `None` for an unknown breed, `croise` marks mixed breeds.
+    pub race_id: Option<i32>,
+    pub croise: bool,
     #[sea_orm(column_type = "Text")]
     pub couleur: String,
-    pub age: i32,
+    pub date_naissance: Date,
+    pub precision_naissance: PrecisionNaissance,
     pub sexe: Sexe,
     #[sea_orm(column_type = "Text")]
     pub description: String,
@@ -22,6 +29,23 @@ pub struct Model {
     pub association_id: i32,
     pub famille_id: Option<i32>,
     pub espece_id: i32,
+    pub taille: Option<Taille>,
+    pub niveau_energie: Option<NiveauEnergie>,
+    /// Compatibilities are `None` until the shelter has been able to tell.
+    pub ok_enfants: Option<bool>,
+    pub ok_chiens: Option<bool>,
+    pub ok_chats: Option<bool>,
+    pub propre: Option<bool>,
+    pub besoins_speciaux: bool,
+    /// Derived from `fiche_soins.numero_puce`, see `SoinRepository::save_fiche`.
+    pub puce: bool,
+    /// Longer story of the animal, in Markdown.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub histoire: Option<String>,
+    pub cree_le: DateTime,
+    pub deleted_at: Option<DateTime>,
+    #[sea_orm(default_value = 1)]
+    pub version: i32,
     #[sea_orm(has_many, via = "animal_tag")]
     pub tags: HasMany<super::tag::Entity>,
     #[sea_orm(
@@ -52,6 +76,8 @@ pub struct Model {
     pub accueillant: HasOne<super::famille::Entity>,
     #[sea_orm(has_many)]
     pub images_animal: HasMany<super::media::Entity>,
+    #[sea_orm(has_many)]
+    pub pesees: HasMany<super::pesee::Entity>,
 }
 
 impl ActiveModelBehavior for ActiveModel {}
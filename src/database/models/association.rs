@@ -1,7 +1,8 @@
+use super::sea_orm_active_enums::{StatutInscription, StatutVerification};
 use sea_orm::entity::prelude::*;
 
 #[sea_orm::model]
-#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, serde::Serialize)]
 #[sea_orm(table_name = "association")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -26,6 +27,17 @@ pub struct Model {
     pub site: Option<String>,
     #[sea_orm(column_type = "Text", nullable)]
     pub description: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub verification: StatutVerification,
+    pub verifiee_le: Option<DateTime>,
+    pub inscription: StatutInscription,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub motif_rejet: Option<String>,
+    pub examinee_le: Option<DateTime>,
+    pub deleted_at: Option<DateTime>,
+    #[sea_orm(default_value = 1)]
+    pub version: i32,
     #[sea_orm(unique)]
     pub utilisateur_id: i32,
     #[sea_orm(has_many)]
@@ -43,3 +55,11 @@ pub struct Model {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl ModelEx {
+    /// Shelters and their animals are listed publicly once the SIRET was verified
+    /// and an admin approved the registration.
+    pub fn is_published(&self) -> bool {
+        self.verification == StatutVerification::Vérifiée && self.inscription == StatutInscription::Approuvée
+    }
+}
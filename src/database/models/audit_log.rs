@@ -0,0 +1,30 @@
+use super::sea_orm_active_enums::ActionAudit;
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "audit_log")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub cree_le: DateTime,
+    pub acteur_id: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub role: Option<String>,
+    pub action: ActionAudit,
+    #[sea_orm(column_type = "Text")]
+    pub type_entite: String,
+    pub entite_id: Option<i32>,
+    pub association_id: Option<i32>,
+    pub diff: Json,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub ip: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub user_agent: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub methode: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub chemin: Option<String>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -12,6 +12,8 @@ pub struct Model {
     pub statut_demande: StatutDemande,
     pub date_debut: Date,
     pub date_fin: Date,
+    #[sea_orm(default_value = 1)]
+    pub version: i32,
     #[sea_orm(
         belongs_to,
         from = "animal_id",
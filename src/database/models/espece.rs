@@ -10,6 +10,8 @@ pub struct Model {
     pub nom: String,
     #[sea_orm(has_many)]
     pub animals: HasMany<super::animal::Entity>,
+    #[sea_orm(has_many)]
+    pub races: HasMany<super::race::Entity>,
 }
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,7 +1,8 @@
+use super::sea_orm_active_enums::{Logement, NiveauExperience};
 use sea_orm::entity::prelude::*;
 
 #[sea_orm::model]
-#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, serde::Serialize)]
 #[sea_orm(table_name = "famille")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -24,12 +25,26 @@ pub struct Model {
     pub hebergement: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub terrain: Option<String>,
+    pub logement: Option<Logement>,
+    pub jardin: bool,
+    pub possede_chats: bool,
+    pub possede_chiens: bool,
+    pub enfants: bool,
+    pub experience: Option<NiveauExperience>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub anonymise_le: Option<DateTime>,
+    pub deleted_at: Option<DateTime>,
+    #[sea_orm(default_value = 1)]
+    pub version: i32,
     #[sea_orm(unique)]
     pub utilisateur_id: i32,
     #[sea_orm(has_many, via = "demande")]
     pub animals: HasMany<super::animal::Entity>,
     #[sea_orm(has_many)]
     pub demandes: HasMany<super::demande::Entity>,
+    #[sea_orm(has_many, via = "famille_espece")]
+    pub especes_acceptees: HasMany<super::espece::Entity>,
     #[sea_orm(
         belongs_to,
         from = "utilisateur_id",
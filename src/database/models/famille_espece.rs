@@ -0,0 +1,29 @@
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "famille_espece")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub famille_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub espece_id: i32,
+    #[sea_orm(
+        belongs_to,
+        from = "famille_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub famille: Option<super::famille::Entity>,
+    #[sea_orm(
+        belongs_to,
+        from = "espece_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub espece: Option<super::espece::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
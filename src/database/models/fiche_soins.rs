@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "fiche_soins")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub animal_id: i32,
+    pub sterilise: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub numero_puce: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub regime_alimentaire: Option<String>,
+    pub modifiee_le: DateTime,
+    #[sea_orm(
+        belongs_to,
+        from = "animal_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub animal: HasOne<super::animal::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,23 @@
+use super::sea_orm_active_enums::Statut;
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "animal_statut_historique")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub animal_id: i32,
+    pub statut: Statut,
+    pub change_le: DateTime,
+    #[sea_orm(
+        belongs_to,
+        from = "animal_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub animal: HasOne<super::animal::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -11,6 +11,10 @@ pub struct Model {
     pub ordre: i32,
     pub animal_id: Option<i32>,
     pub association_id: Option<i32>,
+    /// Registration document, only shown to the shelter and admins.
+    #[sea_orm(default_value = false)]
+    pub justificatif: bool,
+    pub visite_id: Option<i32>,
     #[sea_orm(
         belongs_to,
         from = "animal_id",
@@ -27,6 +31,14 @@ pub struct Model {
         on_delete = "NoAction"
     )]
     pub association: HasOne<super::association::Entity>,
+    #[sea_orm(
+        belongs_to,
+        from = "visite_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub visite: HasOne<super::visite_veterinaire::Entity>,
 }
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,15 +1,36 @@
 pub mod prelude;
 
+pub mod alerte;
 pub mod animal;
 pub mod animal_tag;
 pub mod association;
+pub mod audit_log;
 pub mod demande;
 pub mod espece;
 pub mod famille;
+pub mod famille_espece;
+pub mod fiche_soins;
+pub mod historique_statut;
 pub mod media;
+pub mod pesee;
+pub mod race;
+pub mod race_alias;
+pub mod recherche;
+pub mod recherche_tag;
 pub mod sea_orm_active_enums;
 pub mod tag;
+pub mod traitement;
 pub mod utilisateur;
+pub mod vaccination;
+pub mod visite_veterinaire;
+
+pub use alerte:: {
+ ActiveModel as AlerteActiveModel,
+ Column as AlerteColumn,
+ Entity as AlerteEntity,
+ Model as AlerteModel,
+ ModelEx as AlerteModelEx,
+};
 
 pub use animal:: {
  ActiveModel as AnimalActiveModel,
@@ -37,6 +58,13 @@ pub use association:: {
  ActiveModelEx as AssociationActiveModelEx,
 };
 
+pub use audit_log:: {
+ ActiveModel as AuditLogActiveModel,
+ Column as AuditLogColumn,
+ Entity as AuditLogEntity,
+ Model as AuditLogModel,
+};
+
 pub use demande:: {
  ActiveModel as DemandeActiveModel,
  Column as DemandeColumn,
@@ -63,6 +91,29 @@ pub use famille:: {
  ActiveModelEx as FamilleActiveModelEx,
 };
 
+pub use famille_espece:: {
+ ActiveModel as FamilleEspeceActiveModel,
+ Column as FamilleEspeceColumn,
+ Entity as FamilleEspeceEntity,
+ Model as FamilleEspeceModel,
+ ModelEx as FamilleEspeceModelEx,
+};
+
+pub use fiche_soins:: {
+ ActiveModel as FicheSoinsActiveModel,
+ Column as FicheSoinsColumn,
+ Entity as FicheSoinsEntity,
+ Model as FicheSoinsModel,
+};
+
+pub use historique_statut:: {
+ ActiveModel as HistoriqueStatutActiveModel,
+ Column as HistoriqueStatutColumn,
+ Entity as HistoriqueStatutEntity,
+ Model as HistoriqueStatutModel,
+ ModelEx as HistoriqueStatutModelEx,
+};
+
 pub use media:: {
  ActiveModel as MediaActiveModel,
  Column as MediaColumn,
@@ -71,6 +122,44 @@ pub use media:: {
  ModelEx as MediaModelEx,
 };
 
+pub use pesee:: {
+ ActiveModel as PeseeActiveModel,
+ Column as PeseeColumn,
+ Entity as PeseeEntity,
+ Model as PeseeModel,
+};
+
+pub use race:: {
+ ActiveModel as RaceActiveModel,
+ Column as RaceColumn,
+ Entity as RaceEntity,
+ Model as RaceModel,
+ ModelEx as RaceModelEx,
+};
+
+pub use race_alias:: {
+ ActiveModel as RaceAliasActiveModel,
+ Column as RaceAliasColumn,
+ Entity as RaceAliasEntity,
+ Model as RaceAliasModel,
+};
+
+pub use recherche:: {
+ ActiveModel as RechercheActiveModel,
+ Column as RechercheColumn,
+ Entity as RechercheEntity,
+ Model as RechercheModel,
+ ModelEx as RechercheModelEx,
+};
+
+pub use recherche_tag:: {
+ ActiveModel as RechercheTagActiveModel,
+ Column as RechercheTagColumn,
+ Entity as RechercheTagEntity,
+ Model as RechercheTagModel,
+ ModelEx as RechercheTagModelEx,
+};
+
 pub use tag:: {
  ActiveModel as TagActiveModel,
  Column as TagColumn,
@@ -79,6 +168,13 @@ pub use tag:: {
  ModelEx as TagModelEx, 
 };
 
+pub use traitement:: {
+ ActiveModel as TraitementActiveModel,
+ Column as TraitementColumn,
+ Entity as TraitementEntity,
+ Model as TraitementModel,
+};
+
 pub use utilisateur:: {
  ActiveModel as UtilisateurActiveModel,
  Column as UtilisateurColumn,
@@ -86,4 +182,19 @@ pub use utilisateur:: {
  Model as UtilisateurModel, 
  ModelEx as UtilisateurModelEx,
  ActiveModelEx as UtilisateurActiveModelEx,
-};
\ No newline at end of file
+};
+
+pub use vaccination:: {
+ ActiveModel as VaccinationActiveModel,
+ Column as VaccinationColumn,
+ Entity as VaccinationEntity,
+ Model as VaccinationModel,
+};
+
+pub use visite_veterinaire:: {
+ ActiveModel as VisiteVeterinaireActiveModel,
+ Column as VisiteVeterinaireColumn,
+ Entity as VisiteVeterinaireEntity,
+ Model as VisiteVeterinaireModel,
+ ModelEx as VisiteVeterinaireModelEx,
+};
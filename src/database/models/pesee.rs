@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "pesee")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub animal_id: i32,
+    pub poids_kg: f64,
+    pub pesee_le: Date,
+    pub ajoute_par: Option<i32>,
+    pub cree_le: DateTime,
+    #[sea_orm(
+        belongs_to,
+        from = "animal_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub animal: HasOne<super::animal::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
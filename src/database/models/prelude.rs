@@ -1,9 +1,22 @@
+pub use super::alerte::Entity as Alerte;
 pub use super::animal::Entity as Animal;
 pub use super::animal_tag::Entity as AnimalTag;
 pub use super::association::Entity as Association;
+pub use super::audit_log::Entity as AuditLog;
 pub use super::demande::Entity as Demande;
 pub use super::espece::Entity as Espece;
 pub use super::famille::Entity as Famille;
+pub use super::famille_espece::Entity as FamilleEspece;
+pub use super::fiche_soins::Entity as FicheSoins;
+pub use super::historique_statut::Entity as HistoriqueStatut;
 pub use super::media::Entity as Media;
+pub use super::pesee::Entity as Pesee;
+pub use super::race::Entity as Race;
+pub use super::race_alias::Entity as RaceAlias;
+pub use super::recherche::Entity as Recherche;
+pub use super::recherche_tag::Entity as RechercheTag;
 pub use super::tag::Entity as Tag;
+pub use super::traitement::Entity as Traitement;
 pub use super::utilisateur::Entity as Utilisateur;
+pub use super::vaccination::Entity as Vaccination;
+pub use super::visite_veterinaire::Entity as VisiteVeterinaire;
@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "race")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub espece_id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub nom: String,
+    #[sea_orm(
+        belongs_to,
+        from = "espece_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub espece: HasOne<super::espece::Entity>,
+    #[sea_orm(has_many)]
+    pub alias: HasMany<super::race_alias::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
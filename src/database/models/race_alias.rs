@@ -0,0 +1,21 @@
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "race_alias")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub race_id: i32,
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub alias: String,
+    #[sea_orm(
+        belongs_to,
+        from = "race_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub race: HasOne<super::race::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,38 @@
+use super::sea_orm_active_enums::{FrequenceAlerte, Sexe, Taille};
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "recherche_sauvegardee")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub famille_id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub nom: String,
+    pub espece_id: Option<i32>,
+    pub sexe: Option<Sexe>,
+    pub age_min: Option<i32>,
+    pub age_max: Option<i32>,
+    pub rayon_km: Option<i32>,
+    pub taille: Option<Taille>,
+    /// Only animals known to get along with children, dogs or cats when set.
+    pub ok_enfants: bool,
+    pub ok_chiens: bool,
+    pub ok_chats: bool,
+    pub frequence: FrequenceAlerte,
+    pub derniere_notification: Option<DateTime>,
+    pub cree_le: DateTime,
+    #[sea_orm(has_many, via = "recherche_tag")]
+    pub tags: HasMany<super::tag::Entity>,
+    #[sea_orm(
+        belongs_to,
+        from = "famille_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub famille: HasOne<super::famille::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,29 @@
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "recherche_tag")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub recherche_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub tag_id: i32,
+    #[sea_orm(
+        belongs_to,
+        from = "recherche_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub recherche: Option<super::recherche::Entity>,
+    #[sea_orm(
+        belongs_to,
+        from = "tag_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub tag: Option<super::tag::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -30,3 +30,119 @@ pub enum StatutDemande {
     #[sea_orm(string_value = "Refusée")]
     Refusée,
 }
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, serde::Serialize, serde::Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "frequence_alerte")]
+pub enum FrequenceAlerte {
+    #[sea_orm(string_value = "Immédiate")]
+    Immédiate,
+    #[sea_orm(string_value = "Quotidienne")]
+    Quotidienne,
+    #[sea_orm(string_value = "Hebdomadaire")]
+    Hebdomadaire,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, serde::Serialize, serde::Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "logement")]
+pub enum Logement {
+    #[sea_orm(string_value = "Appartement")]
+    Appartement,
+    #[sea_orm(string_value = "Maison")]
+    Maison,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, serde::Serialize, serde::Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "niveau_experience")]
+pub enum NiveauExperience {
+    #[sea_orm(string_value = "Débutant")]
+    Débutant,
+    #[sea_orm(string_value = "Intermédiaire")]
+    Intermédiaire,
+    #[sea_orm(string_value = "Expérimenté")]
+    Expérimenté,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, serde::Serialize, serde::Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "contrainte_tag")]
+pub enum ContrainteTag {
+    #[sea_orm(string_value = "Besoin de jardin")]
+    BesoinDeJardin,
+    #[sea_orm(string_value = "Pas en appartement")]
+    PasEnAppartement,
+    #[sea_orm(string_value = "Pas avec des chats")]
+    PasAvecDesChats,
+    #[sea_orm(string_value = "Pas avec des chiens")]
+    PasAvecDesChiens,
+    #[sea_orm(string_value = "Pas avec des enfants")]
+    PasAvecDesEnfants,
+    #[sea_orm(string_value = "Expérience requise")]
+    ExpérienceRequise,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, serde::Serialize, serde::Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "action_audit")]
+pub enum ActionAudit {
+    #[sea_orm(string_value = "Création")]
+    Création,
+    #[sea_orm(string_value = "Modification")]
+    Modification,
+    #[sea_orm(string_value = "Suppression")]
+    Suppression,
+    #[sea_orm(string_value = "Restauration")]
+    Restauration,
+    #[sea_orm(string_value = "Purge")]
+    Purge,
+    #[sea_orm(string_value = "Connexion")]
+    Connexion,
+    #[sea_orm(string_value = "Connexion échouée")]
+    ConnexionÉchouée,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, serde::Serialize, serde::Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "statut_verification")]
+pub enum StatutVerification {
+    #[sea_orm(string_value = "En attente")]
+    EnAttente,
+    #[sea_orm(string_value = "Vérifiée")]
+    Vérifiée,
+    #[sea_orm(string_value = "Refusée")]
+    Refusée,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, serde::Serialize, serde::Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "statut_inscription")]
+pub enum StatutInscription {
+    #[sea_orm(string_value = "En attente")]
+    EnAttente,
+    #[sea_orm(string_value = "Approuvée")]
+    Approuvée,
+    #[sea_orm(string_value = "Rejetée")]
+    Rejetée,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, serde::Serialize, serde::Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "precision_naissance")]
+pub enum PrecisionNaissance {
+    #[sea_orm(string_value = "Exacte")]
+    Exacte,
+    #[sea_orm(string_value = "Mois")]
+    Mois,
+    #[sea_orm(string_value = "Année")]
+    Année,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, serde::Serialize, serde::Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "taille")]
+pub enum Taille {
+    #[sea_orm(string_value = "Petite")]
+    Petite,
+    #[sea_orm(string_value = "Moyenne")]
+    Moyenne,
+    #[sea_orm(string_value = "Grande")]
+    Grande,
+    #[sea_orm(string_value = "Très grande")]
+    TrèsGrande,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, serde::Serialize, serde::Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "niveau_energie")]
+pub enum NiveauEnergie {
+    #[sea_orm(string_value = "Calme")]
+    Calme,
+    #[sea_orm(string_value = "Modéré")]
+    Modéré,
+    #[sea_orm(string_value = "Actif")]
+    Actif,
+    #[sea_orm(string_value = "Très actif")]
+    TrèsActif,
+}
@@ -1,3 +1,4 @@
+use super::sea_orm_active_enums::ContrainteTag;
 use sea_orm::entity::prelude::*;
 
 #[sea_orm::model]
@@ -10,6 +11,7 @@ pub struct Model {
     pub nom: String,
     #[sea_orm(column_type = "Text")]
     pub description: String,
+    pub contrainte: Option<ContrainteTag>,
 }
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,30 @@
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "traitement")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub animal_id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub medicament: String,
+    #[sea_orm(column_type = "Text")]
+    pub posologie: String,
+    pub debut: Date,
+    pub fin: Option<Date>,
+    /// Days between two intakes, 1 for a daily treatment.
+    pub frequence_jours: i32,
+    pub ajoute_par: Option<i32>,
+    pub cree_le: DateTime,
+    #[sea_orm(
+        belongs_to,
+        from = "animal_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub animal: HasOne<super::animal::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -9,7 +9,15 @@ pub struct Model {
     #[sea_orm(column_type = "Text", unique)]
     pub email: String,
     #[sea_orm(column_type = "Text")]
+    #[serde(skip_serializing)]
     pub mot_de_passe: String,
+    #[sea_orm(default_value = false)]
+    pub administrateur: bool,
+    pub derniere_connexion: DateTime,
+    /// Bumped to revoke every token issued to the account so far.
+    #[sea_orm(default_value = 0)]
+    #[serde(skip_serializing)]
+    pub version_jeton: i32,
     #[sea_orm(has_one)]
     pub refuge: HasOne<super::association::Entity>,
     #[sea_orm(has_one)]
@@ -17,3 +25,11 @@ pub struct Model {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl ModelEx {
+    /// Accounts whose profile was archived can no longer sign in until an admin restores it.
+    pub fn is_archived(&self) -> bool {
+        self.refuge.as_ref().is_some_and(|shelter| shelter.deleted_at.is_some())
+            || self.accueillant.as_ref().is_some_and(|foster| foster.deleted_at.is_some())
+    }
+}
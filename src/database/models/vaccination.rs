@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "vaccination")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub animal_id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub vaccin: String,
+    pub administre_le: Date,
+    pub rappel_le: Option<Date>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub veterinaire: Option<String>,
+    pub ajoute_par: Option<i32>,
+    pub cree_le: DateTime,
+    #[sea_orm(
+        belongs_to,
+        from = "animal_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub animal: HasOne<super::animal::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, serde::Serialize)]
+#[sea_orm(table_name = "visite_veterinaire")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub animal_id: i32,
+    pub prevue_le: DateTime,
+    #[sea_orm(column_type = "Text")]
+    pub motif: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub veterinaire: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub compte_rendu: Option<String>,
+    pub ajoute_par: Option<i32>,
+    pub cree_le: DateTime,
+    #[sea_orm(
+        belongs_to,
+        from = "animal_id",
+        to = "id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    pub animal: HasOne<super::animal::Entity>,
+    #[sea_orm(has_many)]
+    pub documents: HasMany<super::media::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,72 @@
+use crate::database::models::{AlerteActiveModel, AlerteColumn, AlerteEntity, AlerteModel, AlerteModelEx, AnimalEntity};
+use sea_orm::prelude::DateTime;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, DeleteResult, QueryFilter};
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, TransactionTrait,
+};
+
+pub struct AlerteRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
+}
+
+impl<'a, C: ConnectionTrait + TransactionTrait> AlerteRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
+        Self { db }
+    }
+
+    /// Inbox of an account, search alerts and admin notices alike, most recent first.
+    pub async fn find_published_by_user(&self, user_id: i32) -> Result<Vec<AlerteModelEx>, DbErr> {
+        let mut alerts = AlerteEntity::load()
+            .with(AnimalEntity)
+            .filter(AlerteColumn::UtilisateurId.eq(user_id))
+            .filter(AlerteColumn::PublieeLe.is_not_null())
+            .all(self.db)
+            .await?;
+
+        alerts.sort_by(|a, b| b.publiee_le.cmp(&a.publiee_le).then(b.id.cmp(&a.id)));
+
+        Ok(alerts)
+    }
+
+    pub async fn find_by_id(&self, id: i32) -> Result<Option<AlerteModel>, DbErr> {
+        AlerteEntity::find_by_id(id).one(self.db).await
+    }
+
+    pub async fn create(&self, model: AlerteActiveModel) -> Result<AlerteModel, DbErr> {
+        model.insert(self.db).await
+    }
+
+    pub async fn create_many(&self, models: Vec<AlerteActiveModel>) -> Result<(), DbErr> {
+        if models.is_empty() {
+            return Ok(());
+        }
+
+        AlerteEntity::insert_many(models).exec(self.db).await?;
+
+        Ok(())
+    }
+
+    /// Releases the alerts held back for digest searches into the inbox.
+    pub async fn publish_pending(&self, recherche_ids: Vec<i32>, now: DateTime) -> Result<u64, DbErr> {
+        let result = AlerteEntity::update_many()
+            .col_expr(AlerteColumn::PublieeLe, Expr::value(now))
+            .filter(AlerteColumn::RechercheId.is_in(recherche_ids))
+            .filter(AlerteColumn::PublieeLe.is_null())
+            .exec(self.db)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    pub async fn update(&self, model: AlerteActiveModel) -> Result<AlerteModel, DbErr> {
+        model.update(self.db).await
+    }
+
+    pub async fn delete_by_user(&self, user_id: i32) -> Result<DeleteResult, DbErr> {
+        AlerteEntity::delete_many()
+            .filter(AlerteColumn::UtilisateurId.eq(user_id))
+            .exec(self.db)
+            .await
+    }
+}
@@ -1,31 +1,98 @@
-use crate::database::models::animal::{self};
-use crate::database::models::{AnimalActiveModel, AnimalActiveModelEx, AnimalEntity, AnimalModel, AnimalModelEx, AssociationEntity, DemandeEntity, EspeceEntity, FamilleEntity, MediaEntity, TagEntity};
+use crate::audit::AuditEntry;
+use crate::database::models::{animal, association};
+use crate::database::models::{AnimalActiveModel, AnimalActiveModelEx, AnimalColumn, AnimalTagActiveModel, AnimalTagColumn, AnimalTagEntity, AnimalEntity, AnimalModel, AnimalModelEx, AssociationColumn, AssociationEntity, DemandeEntity, EspeceEntity, FamilleEntity, HistoriqueStatutActiveModel, MediaEntity, PeseeActiveModel, PeseeEntity, PeseeModel, TagColumn, TagEntity};
+use crate::database::models::sea_orm_active_enums::{ActionAudit, ContrainteTag, Statut, StatutInscription, StatutVerification};
+use chrono::Utc;
+use sea_orm::ActiveValue::{self, Set};
+use sea_orm::prelude::DateTime;
 use crate::database::models::sea_orm_active_enums::Statut::*;
-use sea_orm::{ColumnTrait, DeleteResult, EntityLoaderTrait, QueryFilter};
+use crate::database::repositories::association_repository::retain_public;
+use crate::database::repositories::audit_repository::AuditRepository;
+use crate::database::repositories::search_repository::{SearchKind, SearchRepository};
+use sea_orm::entity::prelude::HasMany;
+use sea_orm::sea_query::{Expr, ExprTrait, SimpleExpr};
+use sea_orm::{ColumnTrait, Condition, DeleteResult, EntityLoaderTrait, QueryFilter, QuerySelect, QueryTrait, TransactionSession, TransactionTrait, UpdateResult};
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait,
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
 };
 
-pub struct AnimalRepository<'a> {
-    db: &'a DatabaseConnection,
+pub struct AnimalRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
 }
 
-impl<'a> AnimalRepository<'a> {
-    pub fn new(db: &'a DatabaseConnection) -> Self {
+impl<'a, C: ConnectionTrait + TransactionTrait> AnimalRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
         Self { db }
     }
 
-    pub async fn find_all(&self) -> Result<Vec<AnimalModelEx>, DbErr> {
+    /// Published animals matching `filter`.
+    pub async fn find_all(&self, filter: Condition) -> Result<Vec<AnimalModelEx>, DbErr> {
         let animals= AnimalEntity::load()
             .with((AssociationEntity, MediaEntity))
             .with(MediaEntity)
             .with(FamilleEntity)
             .with(EspeceEntity)
             .with(TagEntity)
+            .with(PeseeEntity)
+            .filter(published_shelter())
+            .filter(animal::COLUMN.deleted_at.is_null())
+            .filter(filter)
             .all(self.db)
             .await?;
 
-        Ok(animals)
+        Ok(animals.into_iter().map(without_shelter_documents).collect())
+    }
+
+    pub async fn find_by_shelters(&self, shelter_ids: Vec<i32>, filter: Condition) -> Result<Vec<AnimalModelEx>, DbErr> {
+        let animals= AnimalEntity::load()
+            .with((AssociationEntity, MediaEntity))
+            .with(MediaEntity)
+            .with(FamilleEntity)
+            .with(EspeceEntity)
+            .with(TagEntity)
+            .with(PeseeEntity)
+            .filter(animal::COLUMN.association_id.is_in(shelter_ids))
+            .filter(animal::COLUMN.deleted_at.is_null())
+            .filter(filter)
+            .all(self.db)
+            .await?;
+
+        Ok(animals.into_iter().map(without_shelter_documents).collect())
+    }
+
+    /// Available animals of a species the foster accepts, any when `espece_ids` is empty,
+    /// leaving out those carrying one of the `unmet` constraints.
+    pub async fn find_suggestions(&self, espece_ids: Vec<i32>, unmet: Vec<ContrainteTag>) -> Result<Vec<AnimalModelEx>, DbErr> {
+        let constrained = AnimalTagEntity::find()
+            .select_only()
+            .column(AnimalTagColumn::AnimalId)
+            .filter(
+                AnimalTagColumn::TagId.in_subquery(
+                    TagEntity::find()
+                        .select_only()
+                        .column(TagColumn::Id)
+                        .filter(TagColumn::Contrainte.is_in(unmet))
+                        .into_query(),
+                ),
+            );
+
+        let mut query = AnimalEntity::load()
+            .with((AssociationEntity, MediaEntity))
+            .with(MediaEntity)
+            .with(EspeceEntity)
+            .with(TagEntity)
+            .with(PeseeEntity)
+            .filter(animal::COLUMN.statut.eq(EnRefuge))
+            .filter(AnimalColumn::Id.not_in_subquery(constrained.into_query()))
+            .filter(published_shelter())
+            .filter(animal::COLUMN.deleted_at.is_null());
+        if !espece_ids.is_empty() {
+            query = query.filter(animal::COLUMN.espece_id.is_in(espece_ids));
+        }
+
+        let animals = query.all(self.db).await?;
+
+        Ok(animals.into_iter().map(without_shelter_documents).collect())
     }
 
     pub async fn find_fostered(&self, id: i32) -> Result<Vec<AnimalModelEx>, DbErr> {
@@ -37,10 +104,11 @@ impl<'a> AnimalRepository<'a> {
             .with(TagEntity)
             .filter(animal::COLUMN.association_id.eq(id))
             .filter(animal::COLUMN.statut.eq(Accueilli))
+            .filter(animal::COLUMN.deleted_at.is_null())
             .all(self.db)
             .await?;
 
-        Ok(animals)
+        Ok(animals.into_iter().map(without_shelter_documents).collect())
     }
 
     pub async fn find_requested(&self, id: i32) -> Result<Vec<AnimalModelEx>, DbErr> {
@@ -52,38 +120,404 @@ impl<'a> AnimalRepository<'a> {
             .with(TagEntity)
             .with((DemandeEntity, FamilleEntity))
             .filter(animal::COLUMN.association_id.eq(id))
+            .filter(animal::COLUMN.deleted_at.is_null())
             .all(self.db)
             .await?;
 
         animals = animals.iter().filter(|&animals| !animals.demandes.is_empty()).cloned().collect();
 
-        Ok(animals)
+        Ok(animals.into_iter().map(without_shelter_documents).collect())
     }
 
     pub async fn find_by_id(&self, id: i32) -> Result<Option<AnimalModelEx>, DbErr> {
-        let animal = AnimalEntity::load()
+        let mut animal = AnimalEntity::load()
             .with((AssociationEntity, MediaEntity))
             .with((AssociationEntity, AnimalEntity))
             .with(MediaEntity)
             .with(FamilleEntity)
             .with(EspeceEntity)
             .with(TagEntity)
+            .with(PeseeEntity)
             .filter_by_id(id)
+            .filter(animal::COLUMN.deleted_at.is_null())
             .one(self.db)
             .await?;
 
+        if let Some(shelter) = animal.as_mut().and_then(|animal| animal.refuge.as_mut()) {
+            retain_active(&mut shelter.pensionnaires);
+            retain_public(&mut shelter.images_association);
+        }
+
         Ok(animal)
     }
 
+    pub async fn find_hosted_by(&self, foster_id: i32) -> Result<Vec<AnimalModel>, DbErr> {
+        AnimalEntity::find()
+            .filter(animal::COLUMN.famille_id.eq(foster_id))
+            .filter(animal::COLUMN.statut.eq(Accueilli))
+            .filter(animal::COLUMN.deleted_at.is_null())
+            .all(self.db)
+            .await
+    }
+
+    pub async fn find_model_by_id(&self, id: i32) -> Result<Option<AnimalModel>, DbErr> {
+        AnimalEntity::find_by_id(id)
+            .filter(animal::COLUMN.deleted_at.is_null())
+            .one(self.db)
+            .await
+    }
+
+    pub async fn find_archived_by_id(&self, id: i32) -> Result<Option<AnimalModel>, DbErr> {
+        AnimalEntity::find_by_id(id)
+            .filter(animal::COLUMN.deleted_at.is_not_null())
+            .one(self.db)
+            .await
+    }
+
+    pub async fn find_archived_before(&self, cutoff: DateTime) -> Result<Vec<AnimalModel>, DbErr> {
+        AnimalEntity::find()
+            .filter(animal::COLUMN.deleted_at.lt(cutoff))
+            .all(self.db)
+            .await
+    }
+
+    /// Every animal of the shelter, archived or not.
+    pub async fn find_all_by_shelter(&self, shelter_id: i32) -> Result<Vec<AnimalModel>, DbErr> {
+        AnimalEntity::find()
+            .filter(animal::COLUMN.association_id.eq(shelter_id))
+            .all(self.db)
+            .await
+    }
+
     pub async fn create(&self, model: AnimalActiveModel) -> Result<AnimalModel, DbErr> {
-        model.insert(self.db).await
+        let txn = self.db.begin().await?;
+        let animal = model.insert(&txn).await?;
+        AnimalRepository::new(&txn)
+            .record_status(animal.id, animal.statut.clone(), animal.cree_le)
+            .await?;
+        SearchRepository::new(&txn).index_animal(&animal).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("animal", animal.id, &animal).for_shelter(Some(animal.association_id)))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(animal)
     }
 
-    pub async fn update(&self, model: AnimalActiveModelEx) -> Result<AnimalModelEx, DbErr> {
-        model.update(self.db).await
+    pub async fn add_weight(&self, model: PeseeActiveModel) -> Result<PeseeModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let weight = model.insert(&txn).await?;
+        let shelter_id = AnimalRepository::new(&txn)
+            .find_model_by_id(weight.animal_id)
+            .await?
+            .map(|animal| animal.association_id);
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("pesee", weight.id, &weight).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(weight)
     }
 
-    pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
-        AnimalEntity::delete_by_id(id).exec(self.db).await
+    /// Inserts and indexes every animal with its tags in a single transaction.
+    pub async fn import(&self, animals: Vec<(AnimalActiveModel, Vec<i32>)>) -> Result<Vec<AnimalModel>, DbErr> {
+        let txn = self.db.begin().await?;
+        let mut created = Vec::with_capacity(animals.len());
+
+        for (model, tags) in animals {
+            let animal = model.insert(&txn).await?;
+
+            HistoriqueStatutActiveModel {
+                animal_id: Set(animal.id),
+                statut: Set(animal.statut.clone()),
+                change_le: Set(animal.cree_le),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+
+            for tag_id in tags {
+                AnimalTagActiveModel {
+                    animal_id: Set(animal.id),
+                    tag_id: Set(tag_id),
+                }
+                .insert(&txn)
+                .await?;
+            }
+
+            SearchRepository::new(&txn).index_animal(&animal).await?;
+            AuditRepository::new(&txn)
+                .record(AuditEntry::created("animal", animal.id, &animal).for_shelter(Some(animal.association_id)))
+                .await?;
+
+            created.push(animal);
+        }
+
+        txn.commit().await?;
+
+        Ok(created)
+    }
+
+    pub async fn update(&self, mut model: AnimalActiveModelEx) -> Result<AnimalModelEx, DbErr> {
+        let status_change = match &model.statut {
+            ActiveValue::Set(statut) => Some(statut.clone()),
+            _ => None,
+        };
+
+        let txn = self.db.begin().await?;
+        let repo = AnimalRepository::new(&txn);
+
+        let before = match model.id.try_as_ref() {
+            Some(id) => AnimalEntity::find_by_id(*id).one(&txn).await?,
+            None => None,
+        };
+        if let (Some(id), Some(version)) = (model.id.try_as_ref().copied(), model.version.try_as_ref().copied()) {
+            model.version = Set(repo.claim_version(id, version).await?);
+        }
+
+        let animal = model.update(&txn).await?;
+        if let Some(statut) = status_change {
+            repo.record_status(animal.id, statut, Utc::now().naive_utc()).await?;
+        }
+        let after: AnimalModel = animal.clone().into();
+        SearchRepository::new(&txn).index_animal(&after).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("animal", after.id, before.as_ref(), &after).for_shelter(Some(after.association_id)))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(animal)
     }
-}
\ No newline at end of file
+
+    /// Moves the animal to the next version, failing with `RecordNotUpdated` when it was saved since `version` was read.
+    async fn claim_version(&self, id: i32, version: i32) -> Result<i32, DbErr> {
+        let result = AnimalEntity::update_many()
+            .col_expr(AnimalColumn::Version, Expr::value(version + 1))
+            .filter(AnimalColumn::Id.eq(id))
+            .filter(AnimalColumn::Version.eq(version))
+            .exec(self.db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(DbErr::RecordNotUpdated);
+        }
+
+        Ok(version + 1)
+    }
+
+    /// Status history feeding the shelter dashboard.
+    async fn record_status(&self, animal_id: i32, statut: Statut, change_le: DateTime) -> Result<(), DbErr> {
+        HistoriqueStatutActiveModel {
+            animal_id: Set(animal_id),
+            statut: Set(statut),
+            change_le: Set(change_le),
+            ..Default::default()
+        }
+        .insert(self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Archives the animal; its requests and status history are kept until the purge job runs.
+    pub async fn delete(&self, id: i32) -> Result<UpdateResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let before = AnimalEntity::find_by_id(id).one(&txn).await?;
+        let result = AnimalEntity::update_many()
+            .col_expr(AnimalColumn::DeletedAt, Expr::value(Utc::now().naive_utc()))
+            .filter(animal::COLUMN.id.eq(id))
+            .filter(animal::COLUMN.deleted_at.is_null())
+            .exec(&txn)
+            .await?;
+        SearchRepository::new(&txn).remove(SearchKind::Animal, id).await?;
+
+        if let Some(before) = before.filter(|_| result.rows_affected > 0) {
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("animal", id, Some(&before)).for_shelter(Some(before.association_id)))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Animals of an archived shelter only come back with the shelter.
+    pub async fn restore(&self, id: i32) -> Result<Option<AnimalModel>, DbErr> {
+        let Some(animal) = self.find_archived_by_id(id).await? else {
+            return Ok(None);
+        };
+        let shelter = AssociationEntity::find_by_id(animal.association_id)
+            .filter(association::COLUMN.deleted_at.is_null())
+            .one(self.db)
+            .await?;
+        if shelter.is_none() {
+            return Ok(None);
+        }
+
+        let txn = self.db.begin().await?;
+        let before = animal.clone();
+        let mut animal_active_model: AnimalActiveModel = animal.into();
+        animal_active_model.deleted_at = Set(None);
+        let animal = animal_active_model.update(&txn).await?;
+        SearchRepository::new(&txn).index_animal(&animal).await?;
+        AuditRepository::new(&txn)
+            .record(
+                AuditEntry::updated("animal", id, Some(&before), &animal)
+                    .with_action(ActionAudit::Restauration)
+                    .for_shelter(Some(animal.association_id)),
+            )
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(Some(animal))
+    }
+
+    /// Archives the active animals of a shelter being archived, stamped with the shelter's own
+    /// archive date so that restoring the shelter brings back these animals and no others.
+    /// Runs on the connection of the shelter's transaction.
+    pub async fn archive_by_shelter(&self, shelter_id: i32, archived_at: DateTime) -> Result<(), DbErr> {
+        let animals = AnimalEntity::find()
+            .filter(animal::COLUMN.association_id.eq(shelter_id))
+            .filter(animal::COLUMN.deleted_at.is_null())
+            .all(self.db)
+            .await?;
+
+        AnimalEntity::update_many()
+            .col_expr(AnimalColumn::DeletedAt, Expr::value(archived_at))
+            .filter(animal::COLUMN.association_id.eq(shelter_id))
+            .filter(animal::COLUMN.deleted_at.is_null())
+            .exec(self.db)
+            .await?;
+
+        let search = SearchRepository::new(self.db);
+        let audit = AuditRepository::new(self.db);
+        for animal in animals {
+            search.remove(SearchKind::Animal, animal.id).await?;
+            audit
+                .record(AuditEntry::deleted("animal", animal.id, Some(&animal)).for_shelter(Some(shelter_id)))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Keeps the public microchip flag in step with the number recorded on the health sheet.
+    /// Runs on the connection of the sheet's transaction.
+    pub async fn set_microchipped(&self, id: i32, puce: bool) -> Result<(), DbErr> {
+        AnimalEntity::update_many()
+            .col_expr(AnimalColumn::Puce, Expr::value(puce))
+            .col_expr(AnimalColumn::Version, Expr::col(AnimalColumn::Version).add(1))
+            .filter(AnimalColumn::Id.eq(id))
+            .filter(AnimalColumn::Puce.ne(puce))
+            .exec(self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Keeps the breed name stored with the active animals in step with a renamed breed,
+    /// moving them to a new version so that cached copies are refreshed.
+    /// Runs on the connection of the breed's transaction.
+    pub async fn rename_breed(&self, race_id: i32, nom: &str) -> Result<(), DbErr> {
+        AnimalEntity::update_many()
+            .col_expr(AnimalColumn::Race, Expr::value(nom))
+            .col_expr(AnimalColumn::Version, Expr::col(AnimalColumn::Version).add(1))
+            .filter(animal::COLUMN.race_id.eq(race_id))
+            .filter(animal::COLUMN.deleted_at.is_null())
+            .exec(self.db)
+            .await?;
+
+        let animals = AnimalEntity::find()
+            .filter(animal::COLUMN.race_id.eq(race_id))
+            .filter(animal::COLUMN.deleted_at.is_null())
+            .all(self.db)
+            .await?;
+
+        let search = SearchRepository::new(self.db);
+        for animal in animals {
+            search.index_animal(&animal).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Restores the animals archived along with their shelter.
+    pub async fn restore_by_shelter(&self, shelter_id: i32, archived_at: DateTime) -> Result<(), DbErr> {
+        let animals = AnimalEntity::find()
+            .filter(animal::COLUMN.association_id.eq(shelter_id))
+            .filter(animal::COLUMN.deleted_at.eq(archived_at))
+            .all(self.db)
+            .await?;
+
+        let search = SearchRepository::new(self.db);
+        let audit = AuditRepository::new(self.db);
+        for animal in animals {
+            let before = animal.clone();
+            let mut animal_active_model: AnimalActiveModel = animal.into();
+            animal_active_model.deleted_at = Set(None);
+            let animal = animal_active_model.update(self.db).await?;
+            search.index_animal(&animal).await?;
+            audit
+                .record(
+                    AuditEntry::updated("animal", animal.id, Some(&before), &animal)
+                        .with_action(ActionAudit::Restauration)
+                        .for_shelter(Some(shelter_id)),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Hard-deletes the animal along with its requests, tags, alerts and status history.
+    pub async fn purge(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let before = AnimalEntity::find_by_id(id).one(&txn).await?;
+        let result = AnimalEntity::delete_by_id(id).exec(&txn).await?;
+        SearchRepository::new(&txn).remove(SearchKind::Animal, id).await?;
+
+        if let Some(before) = before.filter(|_| result.rows_affected > 0) {
+            AuditRepository::new(&txn)
+                .record(
+                    AuditEntry::deleted("animal", id, Some(&before))
+                        .with_action(ActionAudit::Purge)
+                        .for_shelter(Some(before.association_id)),
+                )
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+}
+
+/// Drops archived animals from an eagerly loaded relation.
+pub fn retain_active(animals: &mut HasMany<AnimalEntity>) {
+    if let HasMany::Loaded(animals) = animals {
+        animals.retain(|animal| animal.deleted_at.is_none());
+    }
+}
+
+fn without_shelter_documents(mut animal: AnimalModelEx) -> AnimalModelEx {
+    if let Some(shelter) = animal.refuge.as_mut() {
+        retain_public(&mut shelter.images_association);
+    }
+    animal
+}
+
+/// Animals are only listed publicly once their shelter is published, see `association::ModelEx::is_published`.
+fn published_shelter() -> SimpleExpr {
+    AnimalColumn::AssociationId.in_subquery(
+        AssociationEntity::find()
+            .select_only()
+            .column(AssociationColumn::Id)
+            .filter(association::COLUMN.verification.eq(StatutVerification::Vérifiée))
+            .filter(association::COLUMN.inscription.eq(StatutInscription::Approuvée))
+            .into_query(),
+    )
+}
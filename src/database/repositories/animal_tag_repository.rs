@@ -1,16 +1,33 @@
-use crate::database::models::{AnimalTagActiveModel, AnimalTagModel};
-use sea_orm::{ActiveModelTrait, DatabaseConnection, DbErr};
+use crate::audit::AuditEntry;
+use crate::database::models::sea_orm_active_enums::ActionAudit;
+use crate::database::models::{AnimalEntity, AnimalTagActiveModel, AnimalTagModel};
+use crate::database::repositories::audit_repository::AuditRepository;
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, TransactionSession, TransactionTrait};
+use serde_json::json;
 
-pub struct AnimalTagRepository<'a> {
-    db: &'a DatabaseConnection,
+pub struct AnimalTagRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
 }
 
-impl<'a> AnimalTagRepository<'a> {
-    pub fn new(db: &'a DatabaseConnection) -> Self {
+impl<'a, C: ConnectionTrait + TransactionTrait> AnimalTagRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
         Self { db }
     }
 
     pub async fn create(&self, model: AnimalTagActiveModel) -> Result<AnimalTagModel, DbErr> {
-        model.insert(self.db).await
+        let txn = self.db.begin().await?;
+        let animal_tag = model.insert(&txn).await?;
+        let animal = AnimalEntity::find_by_id(animal_tag.animal_id).one(&txn).await?;
+        AuditRepository::new(&txn)
+            .record(
+                AuditEntry::new(ActionAudit::Modification, "animal", Some(animal_tag.animal_id))
+                    .with_diff(json!({ "tags": { "ajout": animal_tag.tag_id } }))
+                    .for_shelter(animal.map(|animal| animal.association_id)),
+            )
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(animal_tag)
     }
 }
\ No newline at end of file
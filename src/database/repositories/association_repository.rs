@@ -1,26 +1,64 @@
+use crate::audit::AuditEntry;
 use crate::database::models::association::{self};
-use crate::database::models::{AnimalEntity, AssociationActiveModel, AssociationActiveModelEx, AssociationEntity, AssociationModel, AssociationModelEx, EspeceEntity, MediaEntity};
-use sea_orm::{DeleteResult, EntityLoaderTrait, QueryFilter};
+use crate::database::models::sea_orm_active_enums::{ActionAudit, StatutInscription, StatutVerification};
+use crate::database::repositories::audit_repository::AuditRepository;
+use crate::database::repositories::search_repository::{SearchKind, SearchRepository};
+use crate::geo::BoundingBox;
+use crate::database::models::{AnimalEntity, AssociationActiveModel, AssociationActiveModelEx, AssociationColumn, AssociationEntity, AssociationModel, AssociationModelEx, EspeceEntity, MediaEntity};
+use crate::database::repositories::animal_repository::{AnimalRepository, retain_active};
+use chrono::Utc;
+use sea_orm::ActiveValue::Set;
+use sea_orm::prelude::{DateTime, HasMany};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, DeleteResult, EntityLoaderTrait, QueryFilter, UpdateResult};
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait,
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, TransactionSession, TransactionTrait,
 };
-pub struct AssociationRepository<'a> {
-    db: &'a DatabaseConnection,
+
+pub struct AssociationRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
 }
 
-impl<'a> AssociationRepository<'a> {
-    pub fn new(db: &'a DatabaseConnection) -> Self {
+impl<'a, C: ConnectionTrait + TransactionTrait> AssociationRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
         Self { db }
     }
 
+    /// Shelters listed publicly, those whose SIRET was verified and registration approved.
     pub async fn find_all(&self) -> Result<Vec<AssociationModelEx>, DbErr> {
         let shelters = AssociationEntity::load()
             .with(AnimalEntity)
             .with(MediaEntity)
+            .filter(association::COLUMN.verification.eq(StatutVerification::Vérifiée))
+            .filter(association::COLUMN.inscription.eq(StatutInscription::Approuvée))
+            .filter(association::COLUMN.deleted_at.is_null())
+            .all(self.db)
+            .await?;
+
+        Ok(shelters.into_iter().map(without_archived_animals).map(without_documents).collect())
+    }
+
+    pub async fn find_within(&self, bounds: BoundingBox) -> Result<Vec<AssociationModelEx>, DbErr> {
+        let shelters = AssociationEntity::load()
+            .with(AnimalEntity)
+            .with(MediaEntity)
+            .filter(association::COLUMN.latitude.between(bounds.min_latitude, bounds.max_latitude))
+            .filter(association::COLUMN.longitude.between(bounds.min_longitude, bounds.max_longitude))
+            .filter(association::COLUMN.verification.eq(StatutVerification::Vérifiée))
+            .filter(association::COLUMN.inscription.eq(StatutInscription::Approuvée))
+            .filter(association::COLUMN.deleted_at.is_null())
             .all(self.db)
             .await?;
 
-        Ok(shelters)
+        Ok(shelters.into_iter().map(without_archived_animals).map(without_documents).collect())
+    }
+
+    pub async fn find_without_coordinates(&self) -> Result<Vec<AssociationModel>, DbErr> {
+        AssociationEntity::find()
+            .filter(association::COLUMN.latitude.is_null())
+            .filter(association::COLUMN.deleted_at.is_null())
+            .all(self.db)
+            .await
     }
 
     pub async fn find_by_id(&self, id: i32) -> Result<Option<AssociationModelEx>, DbErr> {
@@ -30,31 +68,222 @@ impl<'a> AssociationRepository<'a> {
             .with((AnimalEntity, AssociationEntity))
             .with(MediaEntity)
             .filter_by_id(id)
+            .filter(association::COLUMN.deleted_at.is_null())
             .one(self.db)
             .await?;
 
-        Ok(shelter)
+        Ok(shelter.map(without_archived_animals).map(without_documents))
+    }
+
+    pub async fn find_model_by_id(&self, id: i32) -> Result<Option<AssociationModel>, DbErr> {
+        AssociationEntity::find_by_id(id)
+            .filter(association::COLUMN.deleted_at.is_null())
+            .one(self.db)
+            .await
+    }
+
+    pub async fn find_by_verification(&self, statuses: Vec<StatutVerification>) -> Result<Vec<AssociationModel>, DbErr> {
+        AssociationEntity::find()
+            .filter(association::COLUMN.verification.is_in(statuses))
+            .filter(association::COLUMN.deleted_at.is_null())
+            .all(self.db)
+            .await
+    }
+
+    pub async fn find_by_inscription(&self, status: StatutInscription) -> Result<Vec<AssociationModel>, DbErr> {
+        AssociationEntity::find()
+            .filter(association::COLUMN.inscription.eq(status))
+            .filter(association::COLUMN.deleted_at.is_null())
+            .all(self.db)
+            .await
     }
 
     pub async fn find_by_user_id(&self, id: i32) -> Result<Option<AssociationModelEx>, DbErr> {
         let foster = AssociationEntity::load()
             .with(AnimalEntity)
             .filter(association::COLUMN.utilisateur_id.eq(id))
+            .filter(association::COLUMN.deleted_at.is_null())
             .one(self.db)
             .await?;
 
-        Ok(foster)
+        Ok(foster.map(without_archived_animals))
+    }
+
+    pub async fn find_archived_by_id(&self, id: i32) -> Result<Option<AssociationModel>, DbErr> {
+        AssociationEntity::find_by_id(id)
+            .filter(association::COLUMN.deleted_at.is_not_null())
+            .one(self.db)
+            .await
+    }
+
+    pub async fn find_archived_before(&self, cutoff: DateTime) -> Result<Vec<AssociationModel>, DbErr> {
+        AssociationEntity::find()
+            .filter(association::COLUMN.deleted_at.lt(cutoff))
+            .all(self.db)
+            .await
     }
 
     pub async fn create(&self, model: AssociationActiveModel) -> Result<AssociationModel, DbErr> {
-        model.insert(self.db).await
+        let txn = self.db.begin().await?;
+        let shelter = model.insert(&txn).await?;
+        SearchRepository::new(&txn).index_shelter(&shelter).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("association", shelter.id, &shelter).for_shelter(Some(shelter.id)))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(shelter)
     }
 
-    pub async fn update(&self, model: AssociationActiveModelEx) -> Result<AssociationModelEx, DbErr> {
-        model.update(self.db).await
+    pub async fn update_model(&self, mut model: AssociationActiveModel) -> Result<AssociationModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = AssociationRepository::new(&txn);
+        let before = repo.find_model(model.id.try_as_ref().copied()).await?;
+        if let (Some(id), Some(version)) = (model.id.try_as_ref().copied(), model.version.try_as_ref().copied()) {
+            model.version = Set(repo.claim_version(id, version).await?);
+        }
+        let shelter = model.update(&txn).await?;
+        SearchRepository::new(&txn).index_shelter(&shelter).await?;
+        repo.record_update(before, &shelter).await?;
+
+        txn.commit().await?;
+
+        Ok(shelter)
     }
 
-    pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
-        AssociationEntity::delete_by_id(id).exec(self.db).await
+    pub async fn update(&self, mut model: AssociationActiveModelEx) -> Result<AssociationModelEx, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = AssociationRepository::new(&txn);
+        let before = repo.find_model(model.id.try_as_ref().copied()).await?;
+        if let (Some(id), Some(version)) = (model.id.try_as_ref().copied(), model.version.try_as_ref().copied()) {
+            model.version = Set(repo.claim_version(id, version).await?);
+        }
+        let shelter = model.update(&txn).await?;
+        let after: AssociationModel = shelter.clone().into();
+        SearchRepository::new(&txn).index_shelter(&after).await?;
+        repo.record_update(before, &after).await?;
+
+        txn.commit().await?;
+
+        Ok(shelter)
+    }
+
+    async fn find_model(&self, id: Option<i32>) -> Result<Option<AssociationModel>, DbErr> {
+        match id {
+            Some(id) => AssociationEntity::find_by_id(id).one(self.db).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Moves the shelter to the next version, failing with `RecordNotUpdated` when it was saved since `version` was read.
+    async fn claim_version(&self, id: i32, version: i32) -> Result<i32, DbErr> {
+        let result = AssociationEntity::update_many()
+            .col_expr(AssociationColumn::Version, Expr::value(version + 1))
+            .filter(association::COLUMN.id.eq(id))
+            .filter(association::COLUMN.version.eq(version))
+            .exec(self.db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(DbErr::RecordNotUpdated);
+        }
+
+        Ok(version + 1)
+    }
+
+    async fn record_update(&self, before: Option<AssociationModel>, after: &AssociationModel) -> Result<(), DbErr> {
+        AuditRepository::new(self.db)
+            .record(AuditEntry::updated("association", after.id, before.as_ref(), after).for_shelter(Some(after.id)))
+            .await
     }
-}
\ No newline at end of file
+
+    /// Archives the shelter with its animals; its media are kept until the purge job runs.
+    pub async fn delete(&self, id: i32) -> Result<UpdateResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let archived_at = Utc::now().naive_utc();
+        let before = AssociationRepository::new(&txn).find_model(Some(id)).await?;
+        let result = AssociationEntity::update_many()
+            .col_expr(AssociationColumn::DeletedAt, Expr::value(archived_at))
+            .filter(association::COLUMN.id.eq(id))
+            .filter(association::COLUMN.deleted_at.is_null())
+            .exec(&txn)
+            .await?;
+        SearchRepository::new(&txn).remove(SearchKind::Association, id).await?;
+
+        if result.rows_affected > 0 {
+            AnimalRepository::new(&txn).archive_by_shelter(id, archived_at).await?;
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("association", id, before.as_ref()).for_shelter(Some(id)))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    pub async fn restore(&self, id: i32) -> Result<Option<AssociationModel>, DbErr> {
+        let txn = self.db.begin().await?;
+        let Some(shelter) = AssociationRepository::new(&txn).find_archived_by_id(id).await? else {
+            return Ok(None);
+        };
+
+        let before = shelter.clone();
+        let mut shelter_active_model: AssociationActiveModel = shelter.into();
+        shelter_active_model.deleted_at = Set(None);
+        let shelter = shelter_active_model.update(&txn).await?;
+        if let Some(archived_at) = before.deleted_at {
+            AnimalRepository::new(&txn).restore_by_shelter(id, archived_at).await?;
+        }
+        SearchRepository::new(&txn).index_shelter(&shelter).await?;
+        AuditRepository::new(&txn)
+            .record(
+                AuditEntry::updated("association", id, Some(&before), &shelter)
+                    .with_action(ActionAudit::Restauration)
+                    .for_shelter(Some(id)),
+            )
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(Some(shelter))
+    }
+
+    pub async fn purge(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let before = AssociationRepository::new(&txn).find_model(Some(id)).await?;
+        let result = AssociationEntity::delete_by_id(id).exec(&txn).await?;
+        SearchRepository::new(&txn).remove(SearchKind::Association, id).await?;
+
+        if result.rows_affected > 0 {
+            AuditRepository::new(&txn)
+                .record(
+                    AuditEntry::deleted("association", id, before.as_ref())
+                        .with_action(ActionAudit::Purge)
+                        .for_shelter(Some(id)),
+                )
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+}
+
+fn without_archived_animals(mut shelter: AssociationModelEx) -> AssociationModelEx {
+    retain_active(&mut shelter.pensionnaires);
+    shelter
+}
+
+fn without_documents(mut shelter: AssociationModelEx) -> AssociationModelEx {
+    retain_public(&mut shelter.images_association);
+    shelter
+}
+
+/// Registration documents are only listed through their own endpoints.
+pub fn retain_public(medias: &mut HasMany<MediaEntity>) {
+    if let HasMany::Loaded(medias) = medias {
+        medias.retain(|media| !media.justificatif);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::audit::{self, AuditEntry};
+use crate::database::models::sea_orm_active_enums::ActionAudit;
+use crate::database::models::{AuditLogActiveModel, AuditLogColumn, AuditLogEntity, AuditLogModel};
+use chrono::Utc;
+use sea_orm::ActiveValue::Set;
+use sea_orm::prelude::DateTime;
+use sea_orm::{ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait};
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub association_id: Option<i32>,
+    pub acteur_id: Option<i32>,
+    pub action: Option<ActionAudit>,
+    pub type_entite: Option<String>,
+    pub entite_id: Option<i32>,
+    pub depuis: Option<DateTime>,
+    pub avant_id: Option<i64>,
+    pub limit: u64,
+}
+
+/// Append-only: entries are never updated nor deleted.
+pub struct AuditRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
+}
+
+impl<'a, C: ConnectionTrait + TransactionTrait> AuditRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
+        Self { db }
+    }
+
+    /// Stores the entry along with the actor and metadata of the current request.
+    /// Repositories build it on the transaction of the audited mutation so both commit together.
+    pub async fn record(&self, entry: AuditEntry) -> Result<(), DbErr> {
+        let (actor, metadata) = audit::current();
+        let actor = entry.acteur.or(actor);
+
+        AuditLogActiveModel {
+            cree_le: Set(Utc::now().naive_utc()),
+            acteur_id: Set(actor.map(|actor| actor.user_id)),
+            role: Set(actor.map(|actor| actor.role.to_string())),
+            action: Set(entry.action),
+            type_entite: Set(entry.type_entite.to_string()),
+            entite_id: Set(entry.entite_id),
+            association_id: Set(entry.association_id),
+            diff: Set(entry.diff),
+            ip: Set(metadata.as_ref().and_then(|metadata| metadata.ip.clone())),
+            user_agent: Set(metadata.as_ref().and_then(|metadata| metadata.user_agent.clone())),
+            methode: Set(metadata.as_ref().map(|metadata| metadata.methode.clone())),
+            chemin: Set(metadata.as_ref().map(|metadata| metadata.chemin.clone())),
+            ..Default::default()
+        }
+        .insert(self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Most recent entries first, `avant_id` paging backwards.
+    pub async fn find(&self, filter: AuditFilter) -> Result<Vec<AuditLogModel>, DbErr> {
+        let mut query = AuditLogEntity::find();
+
+        if let Some(association_id) = filter.association_id {
+            query = query.filter(AuditLogColumn::AssociationId.eq(association_id));
+        }
+        if let Some(acteur_id) = filter.acteur_id {
+            query = query.filter(AuditLogColumn::ActeurId.eq(acteur_id));
+        }
+        if let Some(action) = filter.action {
+            query = query.filter(AuditLogColumn::Action.eq(action));
+        }
+        if let Some(type_entite) = filter.type_entite {
+            query = query.filter(AuditLogColumn::TypeEntite.eq(type_entite));
+        }
+        if let Some(entite_id) = filter.entite_id {
+            query = query.filter(AuditLogColumn::EntiteId.eq(entite_id));
+        }
+        if let Some(depuis) = filter.depuis {
+            query = query.filter(AuditLogColumn::CreeLe.gte(depuis));
+        }
+        if let Some(avant_id) = filter.avant_id {
+            query = query.filter(AuditLogColumn::Id.lt(avant_id));
+        }
+
+        query
+            .order_by_desc(AuditLogColumn::Id)
+            .limit(filter.limit)
+            .all(self.db)
+            .await
+    }
+}
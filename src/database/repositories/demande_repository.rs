@@ -1,20 +1,27 @@
-use crate::database::models::{AnimalEntity, AssociationEntity, DemandeActiveModel, DemandeActiveModelEx, DemandeEntity, DemandeModel, DemandeModelEx, EspeceEntity, FamilleEntity, MediaEntity, demande};
-use sea_orm::{DeleteResult, EntityLoaderTrait, QueryFilter};
+use crate::database::models::{AnimalColumn, AnimalEntity, AssociationEntity, DemandeActiveModel, DemandeActiveModelEx, DemandeColumn, DemandeEntity, DemandeModel, DemandeModelEx, EspeceEntity, FamilleEntity, MediaEntity, demande};
+use crate::audit::AuditEntry;
+use crate::database::repositories::audit_repository::AuditRepository;
+use sea_orm::ActiveValue::Set;
+use sea_orm::sea_query::{Expr, SimpleExpr};
+use sea_orm::{ColumnTrait, DeleteResult, EntityLoaderTrait, QueryFilter, QuerySelect, QueryTrait};
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait,
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, TransactionSession, TransactionTrait,
 };
 
-pub struct DemandeRepository<'a> {
-    db: &'a DatabaseConnection,
+pub struct DemandeRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
 }
 
-impl<'a> DemandeRepository<'a> {
-    pub fn new(db: &'a DatabaseConnection) -> Self {
+impl<'a, C: ConnectionTrait + TransactionTrait> DemandeRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
         Self { db }
     }
 
     pub async fn find_all(&self) -> Result<Vec<DemandeModel>, DbErr> {
-        DemandeEntity::find().all(self.db).await
+        DemandeEntity::find()
+            .filter(on_active_animal())
+            .all(self.db)
+            .await
     }
 
     pub async fn find_current_requests(&self, id: i32) -> Result<Vec<DemandeModelEx>, DbErr> {
@@ -23,12 +30,23 @@ impl<'a> DemandeRepository<'a> {
             .with(FamilleEntity)
             .with((AnimalEntity, AssociationEntity))
             .filter(sea_orm::ColumnTrait::eq(&demande::Column::FamilleId,id))
+            .filter(on_active_animal())
             .all(self.db)
             .await?;
 
         Ok(requests)
     }
 
+    /// Every request of the foster, including those on archived animals, for the data export.
+    pub async fn find_all_by_foster(&self, id: i32) -> Result<Vec<DemandeModelEx>, DbErr> {
+        DemandeEntity::load()
+            .with(FamilleEntity)
+            .with((AnimalEntity, AssociationEntity))
+            .filter(demande::COLUMN.famille_id.eq(id))
+            .all(self.db)
+            .await
+    }
+
     pub async fn find_requests(&self, id: i32) -> Result<Vec<DemandeModelEx>, DbErr> {
         let requests = DemandeEntity::load()
             .with((AnimalEntity, AssociationEntity))
@@ -46,12 +64,17 @@ impl<'a> DemandeRepository<'a> {
             .with((AnimalEntity, MediaEntity))
             .with((AnimalEntity, EspeceEntity))
             .filter_by_id(id)
+            .filter(on_active_animal())
             .one(self.db)
             .await?;
 
         Ok(request)
     }
     
+    pub async fn find_model_by_id(&self, id: i32) -> Result<Option<DemandeModel>, DbErr> {
+        DemandeEntity::find_by_id(id).one(self.db).await
+    }
+
     pub async fn find_existing(&self, animal_id: i32, foster_id: i32) -> Result<Option<DemandeModelEx>, DbErr> {
         let existing = DemandeEntity::load()
             .filter(demande::COLUMN.animal_id.eq(animal_id))
@@ -63,14 +86,87 @@ impl<'a> DemandeRepository<'a> {
     }
 
     pub async fn create(&self, model: DemandeActiveModel) -> Result<DemandeModel, DbErr> {
-        model.insert(self.db).await
+        let txn = self.db.begin().await?;
+        let request = model.insert(&txn).await?;
+        let shelter_id = DemandeRepository::new(&txn).shelter_of(request.animal_id).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("demande", request.id, &request).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(request)
     }
 
-    pub async fn update(&self, model: DemandeActiveModelEx) -> Result<DemandeModelEx, DbErr> {
-        model.update(self.db).await
+    pub async fn update(&self, mut model: DemandeActiveModelEx) -> Result<DemandeModelEx, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = DemandeRepository::new(&txn);
+        let before = match model.id.try_as_ref() {
+            Some(id) => DemandeEntity::find_by_id(*id).one(&txn).await?,
+            None => None,
+        };
+        if let (Some(id), Some(version)) = (model.id.try_as_ref().copied(), model.version.try_as_ref().copied()) {
+            model.version = Set(repo.claim_version(id, version).await?);
+        }
+        let request = model.update(&txn).await?;
+        let after: DemandeModel = request.clone().into();
+        let shelter_id = repo.shelter_of(after.animal_id).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("demande", after.id, before.as_ref(), &after).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(request)
     }
 
     pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
-        DemandeEntity::delete_by_id(id).exec(self.db).await
+        let txn = self.db.begin().await?;
+        let before = DemandeEntity::find_by_id(id).one(&txn).await?;
+        let result = DemandeEntity::delete_by_id(id).exec(&txn).await?;
+
+        if let Some(before) = before.filter(|_| result.rows_affected > 0) {
+            let shelter_id = DemandeRepository::new(&txn).shelter_of(before.animal_id).await?;
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("demande", id, Some(&before)).for_shelter(shelter_id))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Moves the request to the next version, failing with `RecordNotUpdated` when it was saved since `version` was read.
+    async fn claim_version(&self, id: i32, version: i32) -> Result<i32, DbErr> {
+        let result = DemandeEntity::update_many()
+            .col_expr(DemandeColumn::Version, Expr::value(version + 1))
+            .filter(DemandeColumn::Id.eq(id))
+            .filter(DemandeColumn::Version.eq(version))
+            .exec(self.db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(DbErr::RecordNotUpdated);
+        }
+
+        Ok(version + 1)
+    }
+
+    /// Requests are audited on behalf of the shelter owning the animal.
+    async fn shelter_of(&self, animal_id: i32) -> Result<Option<i32>, DbErr> {
+        let animal = AnimalEntity::find_by_id(animal_id).one(self.db).await?;
+
+        Ok(animal.map(|animal| animal.association_id))
     }
-}
\ No newline at end of file
+}
+
+/// Requests on archived animals are hidden along with the animal until it is restored or purged.
+fn on_active_animal() -> SimpleExpr {
+    DemandeColumn::AnimalId.in_subquery(
+        AnimalEntity::find()
+            .select_only()
+            .column(AnimalColumn::Id)
+            .filter(AnimalColumn::DeletedAt.is_null())
+            .into_query(),
+    )
+}
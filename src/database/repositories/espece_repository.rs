@@ -1,15 +1,17 @@
-use crate::database::models::{EspeceActiveModel, EspeceEntity, EspeceModel};
-use sea_orm::DeleteResult;
+use crate::audit::AuditEntry;
+use crate::database::models::{AnimalColumn, AnimalEntity, EspeceActiveModel, EspeceEntity, EspeceModel};
+use crate::database::repositories::audit_repository::AuditRepository;
+use sea_orm::{ColumnTrait, DeleteResult, QueryFilter};
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait,
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, TransactionSession, TransactionTrait,
 };
 
-pub struct EspeceRepository<'a> {
-    db: &'a DatabaseConnection,
+pub struct EspeceRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
 }
 
-impl<'a> EspeceRepository<'a> {
-    pub fn new(db: &'a DatabaseConnection) -> Self {
+impl<'a, C: ConnectionTrait + TransactionTrait> EspeceRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
         Self { db }
     }
 
@@ -21,15 +23,57 @@ impl<'a> EspeceRepository<'a> {
         EspeceEntity::find_by_id(id).one(self.db).await
     }
 
+    /// Archived animals count too, they still reference their species.
+    pub async fn has_animals(&self, id: i32) -> Result<bool, DbErr> {
+        let animal = AnimalEntity::find()
+            .filter(AnimalColumn::EspeceId.eq(id))
+            .one(self.db)
+            .await?;
+
+        Ok(animal.is_some())
+    }
+
     pub async fn create(&self, model: EspeceActiveModel) -> Result<EspeceModel, DbErr> {
-        model.insert(self.db).await
+        let txn = self.db.begin().await?;
+        let espece = model.insert(&txn).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("espece", espece.id, &espece))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(espece)
     }
 
     pub async fn update(&self, model: EspeceActiveModel) -> Result<EspeceModel, DbErr> {
-        model.update(self.db).await
+        let txn = self.db.begin().await?;
+        let before = match model.id.try_as_ref() {
+            Some(id) => EspeceEntity::find_by_id(*id).one(&txn).await?,
+            None => None,
+        };
+        let espece = model.update(&txn).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("espece", espece.id, before.as_ref(), &espece))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(espece)
     }
 
     pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
-        EspeceEntity::delete_by_id(id).exec(self.db).await
+        let txn = self.db.begin().await?;
+        let before = EspeceEntity::find_by_id(id).one(&txn).await?;
+        let result = EspeceEntity::delete_by_id(id).exec(&txn).await?;
+
+        if result.rows_affected > 0 {
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("espece", id, before.as_ref()))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
     }
 }
\ No newline at end of file
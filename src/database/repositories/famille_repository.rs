@@ -1,27 +1,39 @@
-use crate::database::models::famille::{self};
-use crate::database::models::{AnimalEntity, FamilleActiveModel, FamilleActiveModelEx, FamilleEntity, FamilleModel, FamilleModelEx};
-use sea_orm::{DeleteResult, EntityLoaderTrait, QueryFilter};
+use crate::audit::AuditEntry;
+use crate::database::models::{animal, famille, famille_espece};
+use crate::database::models::sea_orm_active_enums::{ActionAudit, ContrainteTag, Logement, NiveauExperience};
+use crate::database::repositories::audit_repository::AuditRepository;
+use crate::database::models::{AnimalEntity, EspeceEntity, FamilleActiveModel, FamilleActiveModelEx, FamilleEntity, FamilleEspeceActiveModel, FamilleEspeceEntity, FamilleModel, FamilleModelEx};
+use chrono::Utc;
+use serde_json::json;
+use sea_orm::ActiveValue::Set;
+use sea_orm::prelude::DateTime;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, Condition, DeleteResult, EntityLoaderTrait, ExprTrait, QueryFilter, QuerySelect, QueryTrait, UpdateResult};
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait,
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, TransactionSession, TransactionTrait,
 };
 
-pub struct FamilleRepository<'a> {
-    db: &'a DatabaseConnection,
+pub struct FamilleRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
 }
 
-impl<'a> FamilleRepository<'a> {
-    pub fn new(db: &'a DatabaseConnection) -> Self {
+impl<'a, C: ConnectionTrait + TransactionTrait> FamilleRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
         Self { db }
     }
 
     pub async fn find_all(&self) -> Result<Vec<FamilleModel>, DbErr> {
-        FamilleEntity::find().all(self.db).await
+        FamilleEntity::find()
+            .filter(famille::COLUMN.deleted_at.is_null())
+            .all(self.db)
+            .await
     }
 
     pub async fn find_by_id(&self, id: i32) -> Result<Option<FamilleModelEx>, DbErr> {
         let foster = FamilleEntity::load()
             .with(AnimalEntity)
             .filter_by_id(id)
+            .filter(famille::COLUMN.deleted_at.is_null())
             .one(self.db)
             .await?;
 
@@ -32,21 +44,247 @@ impl<'a> FamilleRepository<'a> {
         let foster = FamilleEntity::load()
             .with(AnimalEntity)
             .filter(famille::COLUMN.utilisateur_id.eq(id))
+            .filter(famille::COLUMN.deleted_at.is_null())
             .one(self.db)
             .await?;
 
         Ok(foster)
     }
 
+    /// Fosters accepting the species and meeting every constraint, as candidates for compatibility matching.
+    /// Mirrors the exclusions of `matching_service::score`, which then ranks them.
+    pub async fn find_match_candidates(&self, espece_id: i32, constraints: &[ContrainteTag]) -> Result<Vec<FamilleModelEx>, DbErr> {
+        let with_preferences = FamilleEspeceEntity::find()
+            .select_only()
+            .column(famille_espece::COLUMN.famille_id.0);
+        let accepting = with_preferences
+            .clone()
+            .filter(famille_espece::COLUMN.espece_id.eq(espece_id));
+        let accepts_species = Condition::any()
+            .add(famille::Column::Id.not_in_subquery(with_preferences.into_query()))
+            .add(famille::Column::Id.in_subquery(accepting.into_query()));
+
+        let mut query = FamilleEntity::load()
+            .with(EspeceEntity)
+            .filter(accepts_species)
+            .filter(famille::COLUMN.anonymise_le.is_null())
+            .filter(famille::COLUMN.deleted_at.is_null());
+        for constraint in constraints {
+            query = query.filter(match constraint {
+                ContrainteTag::BesoinDeJardin => famille::COLUMN.jardin.eq(true),
+                ContrainteTag::PasEnAppartement => famille::Column::Logement
+                    .is_null()
+                    .or(famille::Column::Logement.ne(Logement::Appartement)),
+                ContrainteTag::PasAvecDesChats => famille::COLUMN.possede_chats.eq(false),
+                ContrainteTag::PasAvecDesChiens => famille::COLUMN.possede_chiens.eq(false),
+                ContrainteTag::PasAvecDesEnfants => famille::COLUMN.enfants.eq(false),
+                ContrainteTag::ExpérienceRequise => famille::Column::Experience
+                    .is_null()
+                    .or(famille::Column::Experience.ne(NiveauExperience::Débutant)),
+            });
+        }
+
+        query.all(self.db).await
+    }
+
+    pub async fn find_with_preferences(&self, id: i32) -> Result<Option<FamilleModelEx>, DbErr> {
+        let foster = FamilleEntity::load()
+            .with(EspeceEntity)
+            .filter_by_id(id)
+            .filter(famille::COLUMN.deleted_at.is_null())
+            .one(self.db)
+            .await?;
+
+        Ok(foster)
+    }
+
+    /// Replaces the accepted species of the foster, on the connection of the transaction saving the profile.
+    pub async fn set_accepted_species(&self, foster_id: i32, espece_ids: Vec<i32>) -> Result<(), DbErr> {
+        FamilleEspeceEntity::delete_many()
+            .filter(famille_espece::COLUMN.famille_id.eq(foster_id))
+            .exec(self.db)
+            .await?;
+
+        let diff = json!({ "especes_acceptees": { "apres": &espece_ids } });
+        let models: Vec<FamilleEspeceActiveModel> = espece_ids
+            .into_iter()
+            .map(|espece_id| FamilleEspeceActiveModel {
+                famille_id: Set(foster_id),
+                espece_id: Set(espece_id),
+            })
+            .collect();
+        if !models.is_empty() {
+            FamilleEspeceEntity::insert_many(models).exec(self.db).await?;
+        }
+
+        AuditRepository::new(self.db)
+            .record(AuditEntry::new(ActionAudit::Modification, "famille", Some(foster_id)).with_diff(diff))
+            .await
+    }
+
+    pub async fn find_not_anonymized_by_user_ids(&self, ids: Vec<i32>) -> Result<Vec<FamilleModel>, DbErr> {
+        FamilleEntity::find()
+            .filter(famille::COLUMN.utilisateur_id.is_in(ids))
+            .filter(famille::COLUMN.anonymise_le.is_null())
+            .filter(famille::COLUMN.deleted_at.is_null())
+            .all(self.db)
+            .await
+    }
+
+    pub async fn find_without_coordinates(&self) -> Result<Vec<FamilleModel>, DbErr> {
+        FamilleEntity::find()
+            .filter(famille::COLUMN.latitude.is_null())
+            .filter(famille::COLUMN.anonymise_le.is_null())
+            .filter(famille::COLUMN.deleted_at.is_null())
+            .all(self.db)
+            .await
+    }
+
     pub async fn create(&self, model: FamilleActiveModel) -> Result<FamilleModel, DbErr> {
-        model.insert(self.db).await
+        let txn = self.db.begin().await?;
+        let foster = model.insert(&txn).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("famille", foster.id, &foster))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(foster)
     }
 
-    pub async fn update(&self, model: FamilleActiveModelEx) -> Result<FamilleModelEx, DbErr> {
-        model.update(self.db).await
+    pub async fn update_model(&self, mut model: FamilleActiveModel) -> Result<FamilleModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = FamilleRepository::new(&txn);
+        let before = repo.find_model(model.id.try_as_ref().copied()).await?;
+        if let (Some(id), Some(version)) = (model.id.try_as_ref().copied(), model.version.try_as_ref().copied()) {
+            model.version = Set(repo.claim_version(id, version).await?);
+        }
+        let foster = model.update(&txn).await?;
+        repo.record_update(before, &foster).await?;
+
+        txn.commit().await?;
+
+        Ok(foster)
     }
 
-    pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
-        FamilleEntity::delete_by_id(id).exec(self.db).await
+    pub async fn update(&self, mut model: FamilleActiveModelEx) -> Result<FamilleModelEx, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = FamilleRepository::new(&txn);
+        let before = repo.find_model(model.id.try_as_ref().copied()).await?;
+        if let (Some(id), Some(version)) = (model.id.try_as_ref().copied(), model.version.try_as_ref().copied()) {
+            model.version = Set(repo.claim_version(id, version).await?);
+        }
+        let foster = model.update(&txn).await?;
+        repo.record_update(before, &foster.clone().into()).await?;
+
+        txn.commit().await?;
+
+        Ok(foster)
+    }
+
+    async fn find_model(&self, id: Option<i32>) -> Result<Option<FamilleModel>, DbErr> {
+        match id {
+            Some(id) => FamilleEntity::find_by_id(id).one(self.db).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Moves the foster to the next version, failing with `RecordNotUpdated` when it was saved since `version` was read.
+    async fn claim_version(&self, id: i32, version: i32) -> Result<i32, DbErr> {
+        let result = FamilleEntity::update_many()
+            .col_expr(famille::COLUMN.version, Expr::value(version + 1))
+            .filter(famille::COLUMN.id.eq(id))
+            .filter(famille::COLUMN.version.eq(version))
+            .exec(self.db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(DbErr::RecordNotUpdated);
+        }
+
+        Ok(version + 1)
+    }
+
+    async fn record_update(&self, before: Option<FamilleModel>, after: &FamilleModel) -> Result<(), DbErr> {
+        AuditRepository::new(self.db)
+            .record(AuditEntry::updated("famille", after.id, before.as_ref(), after))
+            .await
+    }
+
+    pub async fn find_archived_by_id(&self, id: i32) -> Result<Option<FamilleModel>, DbErr> {
+        FamilleEntity::find_by_id(id)
+            .filter(famille::COLUMN.deleted_at.is_not_null())
+            .one(self.db)
+            .await
+    }
+
+    pub async fn find_archived_before(&self, cutoff: DateTime) -> Result<Vec<FamilleModel>, DbErr> {
+        FamilleEntity::find()
+            .filter(famille::COLUMN.deleted_at.lt(cutoff))
+            .all(self.db)
+            .await
+    }
+
+    /// Archives the foster; their requests stay visible to shelters until the purge job runs.
+    pub async fn delete(&self, id: i32) -> Result<UpdateResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let before = FamilleRepository::new(&txn).find_model(Some(id)).await?;
+        let result = FamilleEntity::update_many()
+            .col_expr(famille::COLUMN.deleted_at, Expr::value(Utc::now().naive_utc()))
+            .filter(famille::COLUMN.id.eq(id))
+            .filter(famille::COLUMN.deleted_at.is_null())
+            .exec(&txn)
+            .await?;
+
+        if result.rows_affected > 0 {
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("famille", id, before.as_ref()))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    pub async fn restore(&self, id: i32) -> Result<Option<FamilleModel>, DbErr> {
+        let txn = self.db.begin().await?;
+        let Some(foster) = FamilleRepository::new(&txn).find_archived_by_id(id).await? else {
+            return Ok(None);
+        };
+
+        let before = foster.clone();
+        let mut foster_active_model: FamilleActiveModel = foster.into();
+        foster_active_model.deleted_at = Set(None);
+        let foster = foster_active_model.update(&txn).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("famille", id, Some(&before), &foster).with_action(ActionAudit::Restauration))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(Some(foster))
+    }
+
+    /// Hard-deletes the foster, detaching the animals they hosted in the past.
+    pub async fn purge(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = self.db.begin().await?;
+        AnimalEntity::update_many()
+            .col_expr(animal::COLUMN.famille_id, Expr::value(Option::<i32>::None))
+            .filter(animal::COLUMN.famille_id.eq(id))
+            .exec(&txn)
+            .await?;
+
+        let before = FamilleRepository::new(&txn).find_model(Some(id)).await?;
+        let result = FamilleEntity::delete_by_id(id).exec(&txn).await?;
+
+        if result.rows_affected > 0 {
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("famille", id, before.as_ref()).with_action(ActionAudit::Purge))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
     }
 }
\ No newline at end of file
@@ -1,35 +1,160 @@
-use crate::database::models::{MediaActiveModel, MediaEntity, MediaModel};
-use sea_orm::DeleteResult;
+use crate::audit::AuditEntry;
+use crate::database::models::{AnimalEntity, MediaActiveModel, MediaEntity, MediaModel, media};
+use crate::database::repositories::audit_repository::AuditRepository;
+use sea_orm::{DeleteResult, QueryFilter};
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait,
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, TransactionSession, TransactionTrait,
 };
 
-pub struct MediaRepository<'a> {
-    db: &'a DatabaseConnection,
+pub struct MediaRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
 }
 
-impl<'a> MediaRepository<'a> {
-    pub fn new(db: &'a DatabaseConnection) -> Self {
+impl<'a, C: ConnectionTrait + TransactionTrait> MediaRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
         Self { db }
     }
 
     pub async fn find_all(&self) -> Result<Vec<MediaModel>, DbErr> {
-        MediaEntity::find().all(self.db).await
+        MediaEntity::find()
+            .filter(media::COLUMN.justificatif.eq(false))
+            .filter(media::COLUMN.visite_id.is_null())
+            .all(self.db)
+            .await
     }
 
     pub async fn find_by_id(&self, id: i32) -> Result<Option<MediaModel>, DbErr> {
         MediaEntity::find_by_id(id).one(self.db).await
     }
 
+    pub async fn find_by_association(&self, id: i32) -> Result<Vec<MediaModel>, DbErr> {
+        MediaEntity::find()
+            .filter(media::COLUMN.association_id.eq(id))
+            .all(self.db)
+            .await
+    }
+
+    pub async fn find_documents_by_association(&self, id: i32) -> Result<Vec<MediaModel>, DbErr> {
+        MediaEntity::find()
+            .filter(media::COLUMN.association_id.eq(id))
+            .filter(media::COLUMN.justificatif.eq(true))
+            .all(self.db)
+            .await
+    }
+
+    pub async fn find_by_animal(&self, id: i32) -> Result<Vec<MediaModel>, DbErr> {
+        MediaEntity::find()
+            .filter(media::COLUMN.animal_id.eq(id))
+            .all(self.db)
+            .await
+    }
+
+    /// Public photos of the given animals, leaving out registration documents and vet visit files.
+    pub async fn find_public_by_animals(&self, ids: Vec<i32>) -> Result<Vec<MediaModel>, DbErr> {
+        MediaEntity::find()
+            .filter(media::COLUMN.animal_id.is_in(ids))
+            .filter(media::COLUMN.justificatif.eq(false))
+            .filter(media::COLUMN.visite_id.is_null())
+            .all(self.db)
+            .await
+    }
+
     pub async fn create(&self, model: MediaActiveModel) -> Result<MediaModel, DbErr> {
-        model.insert(self.db).await
+        let txn = self.db.begin().await?;
+        let media = model.insert(&txn).await?;
+        let shelter_id = MediaRepository::new(&txn).shelter_of(&media).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("media", media.id, &media).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(media)
     }
 
     pub async fn update(&self, model: MediaActiveModel) -> Result<MediaModel, DbErr> {
-        model.update(self.db).await
+        let txn = self.db.begin().await?;
+        let before = match model.id.try_as_ref() {
+            Some(id) => MediaEntity::find_by_id(*id).one(&txn).await?,
+            None => None,
+        };
+        let media = model.update(&txn).await?;
+        let shelter_id = MediaRepository::new(&txn).shelter_of(&media).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("media", media.id, before.as_ref(), &media).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(media)
     }
 
     pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
-        MediaEntity::delete_by_id(id).exec(self.db).await
+        let txn = self.db.begin().await?;
+        let before = MediaEntity::find_by_id(id).one(&txn).await?;
+        let result = MediaEntity::delete_by_id(id).exec(&txn).await?;
+        if result.rows_affected > 0 {
+            MediaRepository::new(&txn).record_deleted(before.into_iter().collect()).await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    pub async fn delete_by_association(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = MediaRepository::new(&txn);
+        let before = repo.find_by_association(id).await?;
+        let result = MediaEntity::delete_many()
+            .filter(media::COLUMN.association_id.eq(id))
+            .exec(&txn)
+            .await?;
+        repo.record_deleted(before).await?;
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    pub async fn delete_by_animal(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = MediaRepository::new(&txn);
+        let before = repo.find_by_animal(id).await?;
+        let result = MediaEntity::delete_many()
+            .filter(media::COLUMN.animal_id.eq(id))
+            .exec(&txn)
+            .await?;
+        repo.record_deleted(before).await?;
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn record_deleted(&self, medias: Vec<MediaModel>) -> Result<(), DbErr> {
+        let audit = AuditRepository::new(self.db);
+        for media in medias {
+            let shelter_id = self.shelter_of(&media).await?;
+            audit
+                .record(AuditEntry::deleted("media", media.id, Some(&media)).for_shelter(shelter_id))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Animal pictures belong to the shelter owning the animal.
+    async fn shelter_of(&self, media: &MediaModel) -> Result<Option<i32>, DbErr> {
+        if media.association_id.is_some() {
+            return Ok(media.association_id);
+        }
+
+        let Some(animal_id) = media.animal_id else {
+            return Ok(None);
+        };
+        let animal = AnimalEntity::find_by_id(animal_id).one(self.db).await?;
+
+        Ok(animal.map(|animal| animal.association_id))
     }
 }
\ No newline at end of file
@@ -1,19 +1,33 @@
+pub mod alerte_repository;
 pub mod animal_repository;
 pub mod animal_tag_repository;
 pub mod association_repository;
+pub mod audit_repository;
 pub mod demande_repository;
 pub mod espece_repository;
 pub mod famille_repository;
 pub mod media_repository;
+pub mod race_repository;
+pub mod recherche_repository;
+pub mod search_repository;
+pub mod soin_repository;
+pub mod stats_repository;
 pub mod tag_repository;
 pub mod utilisateur_repository;
 
+pub use alerte_repository::AlerteRepository;
 pub use animal_repository::AnimalRepository;
 pub use animal_tag_repository::AnimalTagRepository;
 pub use association_repository::AssociationRepository;
+pub use audit_repository::{AuditFilter, AuditRepository};
 pub use demande_repository::DemandeRepository;
 pub use espece_repository::EspeceRepository;
 pub use famille_repository::FamilleRepository;
 pub use media_repository::MediaRepository;
+pub use race_repository::RaceRepository;
+pub use recherche_repository::RechercheRepository;
+pub use search_repository::{SearchHit, SearchKind, SearchRepository};
+pub use soin_repository::SoinRepository;
+pub use stats_repository::StatsRepository;
 pub use tag_repository::TagRepository;
 pub use utilisateur_repository::UtilisateurRepository;
\ No newline at end of file
@@ -0,0 +1,118 @@
+use crate::audit::AuditEntry;
+use crate::database::models::sea_orm_active_enums::ActionAudit;
+use crate::database::models::{
+    RaceActiveModel, RaceAliasActiveModel, RaceAliasColumn, RaceAliasEntity, RaceColumn, RaceEntity, RaceModel, RaceModelEx,
+};
+use crate::database::repositories::animal_repository::AnimalRepository;
+use crate::database::repositories::audit_repository::AuditRepository;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, DeleteResult, EntityLoaderTrait, EntityTrait, QueryFilter, TransactionSession, TransactionTrait};
+use serde_json::json;
+
+/// Breeds of each species, with their aliases.
+pub struct RaceRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
+}
+
+impl<'a, C: ConnectionTrait + TransactionTrait> RaceRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<RaceModelEx>, DbErr> {
+        RaceEntity::load().with(RaceAliasEntity).all(self.db).await
+    }
+
+    pub async fn find_by_species(&self, espece_id: i32) -> Result<Vec<RaceModelEx>, DbErr> {
+        RaceEntity::load()
+            .with(RaceAliasEntity)
+            .filter(RaceColumn::EspeceId.eq(espece_id))
+            .all(self.db)
+            .await
+    }
+
+    pub async fn find_by_id(&self, id: i32) -> Result<Option<RaceModel>, DbErr> {
+        RaceEntity::find_by_id(id).one(self.db).await
+    }
+
+    pub async fn find_with_aliases(&self, id: i32) -> Result<Option<RaceModelEx>, DbErr> {
+        RaceEntity::load()
+            .with(RaceAliasEntity)
+            .filter_by_id(id)
+            .one(self.db)
+            .await
+    }
+
+    pub async fn create(&self, model: RaceActiveModel) -> Result<RaceModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let race = model.insert(&txn).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("race", race.id, &race))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(race)
+    }
+
+    /// Animals of the breed follow its new name.
+    pub async fn update(&self, model: RaceActiveModel) -> Result<RaceModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let before = match model.id.try_as_ref() {
+            Some(id) => RaceRepository::new(&txn).find_by_id(*id).await?,
+            None => None,
+        };
+        let race = model.update(&txn).await?;
+        if before.as_ref().is_some_and(|before| before.nom != race.nom) {
+            AnimalRepository::new(&txn).rename_breed(race.id, &race.nom).await?;
+        }
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("race", race.id, before.as_ref(), &race))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(race)
+    }
+
+    /// Animals of a deleted breed become of unknown breed, keeping its name as free text.
+    pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let before = RaceRepository::new(&txn).find_by_id(id).await?;
+        let result = RaceEntity::delete_by_id(id).exec(&txn).await?;
+
+        if result.rows_affected > 0 {
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("race", id, before.as_ref()))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Replaces the aliases of the breed, on the connection of the transaction saving the breed.
+    pub async fn set_aliases(&self, race_id: i32, aliases: Vec<String>) -> Result<(), DbErr> {
+        RaceAliasEntity::delete_many()
+            .filter(RaceAliasColumn::RaceId.eq(race_id))
+            .exec(self.db)
+            .await?;
+
+        let diff = json!({ "alias": { "apres": &aliases } });
+        let models: Vec<RaceAliasActiveModel> = aliases
+            .into_iter()
+            .map(|alias| RaceAliasActiveModel {
+                race_id: Set(race_id),
+                alias: Set(alias),
+            })
+            .collect();
+        if !models.is_empty() {
+            RaceAliasEntity::insert_many(models).exec(self.db).await?;
+        }
+
+        AuditRepository::new(self.db)
+            .record(AuditEntry::new(ActionAudit::Modification, "race", Some(race_id)).with_diff(diff))
+            .await
+    }
+}
@@ -0,0 +1,108 @@
+use crate::database::models::sea_orm_active_enums::{FrequenceAlerte, Sexe};
+use crate::database::models::{FamilleEntity, RechercheActiveModel, RechercheColumn, RechercheEntity, RechercheModel, RechercheModelEx, RechercheTagActiveModel, RechercheTagModel, TagEntity};
+use crate::audit::AuditEntry;
+use crate::database::repositories::audit_repository::AuditRepository;
+use sea_orm::prelude::DateTime;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, Condition, DeleteResult, QueryFilter};
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, TransactionSession, TransactionTrait,
+};
+
+pub struct RechercheRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
+}
+
+impl<'a, C: ConnectionTrait + TransactionTrait> RechercheRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_by_foster(&self, foster_id: i32) -> Result<Vec<RechercheModelEx>, DbErr> {
+        let searches = RechercheEntity::load()
+            .with(TagEntity)
+            .filter(RechercheColumn::FamilleId.eq(foster_id))
+            .all(self.db)
+            .await?;
+
+        Ok(searches)
+    }
+
+    pub async fn find_by_id(&self, id: i32) -> Result<Option<RechercheModel>, DbErr> {
+        RechercheEntity::find_by_id(id).one(self.db).await
+    }
+
+    /// Searches of non-archived fosters whose species, sex and age criteria accept the animal.
+    /// Tags and distance are checked by the caller.
+    pub async fn find_candidates(&self, espece_id: i32, sexe: Sexe, age: i32) -> Result<Vec<RechercheModelEx>, DbErr> {
+        let searches = RechercheEntity::load()
+            .with(TagEntity)
+            .with(FamilleEntity)
+            .filter(Condition::any().add(RechercheColumn::EspeceId.is_null()).add(RechercheColumn::EspeceId.eq(espece_id)))
+            .filter(Condition::any().add(RechercheColumn::Sexe.is_null()).add(RechercheColumn::Sexe.eq(sexe)))
+            .filter(Condition::any().add(RechercheColumn::AgeMin.is_null()).add(RechercheColumn::AgeMin.lte(age)))
+            .filter(Condition::any().add(RechercheColumn::AgeMax.is_null()).add(RechercheColumn::AgeMax.gte(age)))
+            .all(self.db)
+            .await?;
+
+        Ok(searches
+            .into_iter()
+            .filter(|search| search.famille.as_ref().is_none_or(|foster| foster.deleted_at.is_none()))
+            .collect())
+    }
+
+    /// Digest searches of the given frequency last notified before `cutoff`.
+    pub async fn find_due(&self, frequence: FrequenceAlerte, cutoff: DateTime) -> Result<Vec<RechercheModel>, DbErr> {
+        RechercheEntity::find()
+            .filter(RechercheColumn::Frequence.eq(frequence))
+            .filter(
+                Condition::any()
+                    .add(RechercheColumn::DerniereNotification.is_null())
+                    .add(RechercheColumn::DerniereNotification.lt(cutoff)),
+            )
+            .all(self.db)
+            .await
+    }
+
+    pub async fn mark_notified(&self, ids: Vec<i32>, now: DateTime) -> Result<u64, DbErr> {
+        let result = RechercheEntity::update_many()
+            .col_expr(RechercheColumn::DerniereNotification, Expr::value(now))
+            .filter(RechercheColumn::Id.is_in(ids))
+            .exec(self.db)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    pub async fn create(&self, model: RechercheActiveModel) -> Result<RechercheModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let search = model.insert(&txn).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("recherche", search.id, &search))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(search)
+    }
+
+    pub async fn add_tag(&self, model: RechercheTagActiveModel) -> Result<RechercheTagModel, DbErr> {
+        model.insert(self.db).await
+    }
+
+    pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let before = RechercheEntity::find_by_id(id).one(&txn).await?;
+        let result = RechercheEntity::delete_by_id(id).exec(&txn).await?;
+
+        if result.rows_affected > 0 {
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("recherche", id, before.as_ref()))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+}
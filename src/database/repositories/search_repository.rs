@@ -0,0 +1,210 @@
+use crate::database::models::{AnimalModel, AssociationModel, TagModel};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, Statement, TransactionTrait};
+
+/// Text search configuration created by `migrations/005_search.sql`.
+const SEARCH_CONFIG: &str = "french_unaccent";
+
+/// Shelters, and the animals they host, only show up in results once their SIRET was verified
+/// and their registration approved.
+const PUBLISHED_SHELTERS: &str =
+    "SELECT id FROM association WHERE verification = 'Vérifiée' AND inscription = 'Approuvée'";
+
+/// Private-use characters marking the matches in `ts_headline`, swapped for `<mark>` once the excerpt is escaped.
+const START_SEL: char = '\u{E000}';
+const STOP_SEL: char = '\u{E001}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchKind {
+    Animal,
+    Association,
+    Tag,
+}
+
+impl SearchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchKind::Animal => "animal",
+            SearchKind::Association => "association",
+            SearchKind::Tag => "tag",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "animal" => Some(SearchKind::Animal),
+            "association" => Some(SearchKind::Association),
+            "tag" => Some(SearchKind::Tag),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    #[serde(rename = "type")]
+    pub kind: SearchKind,
+    pub id: i32,
+    pub titre: String,
+    pub extrait: String,
+    pub score: f32,
+}
+
+/// Keeps `search_document` in sync with the indexed tables and queries it.
+pub struct SearchRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
+}
+
+impl<'a, C: ConnectionTrait + TransactionTrait> SearchRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
+        Self { db }
+    }
+
+    pub async fn index_animal(&self, animal: &AnimalModel) -> Result<(), DbErr> {
+        let details = [animal.race.as_deref().unwrap_or_default(), &animal.couleur].join(" ");
+        let text = [animal.description.as_str(), animal.histoire.as_deref().unwrap_or_default()].join(" ");
+
+        self.upsert(SearchKind::Animal, animal.id, &animal.nom, &details, &text)
+            .await
+    }
+
+    pub async fn index_shelter(&self, shelter: &AssociationModel) -> Result<(), DbErr> {
+        let description = shelter.description.as_deref().unwrap_or_default();
+
+        self.upsert(SearchKind::Association, shelter.id, &shelter.nom, "", description)
+            .await
+    }
+
+    pub async fn index_tag(&self, tag: &TagModel) -> Result<(), DbErr> {
+        self.upsert(SearchKind::Tag, tag.id, &tag.nom, "", "").await
+    }
+
+    pub async fn remove(&self, kind: SearchKind, id: i32) -> Result<(), DbErr> {
+        self.db
+            .execute_raw(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "DELETE FROM search_document WHERE type_entite = $1 AND entite_id = $2",
+                [kind.as_str().into(), id.into()],
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Weighs the title above the details and the details above the free text.
+    async fn upsert(&self, kind: SearchKind, id: i32, titre: &str, details: &str, texte: &str) -> Result<(), DbErr> {
+        let contenu = [details, texte]
+            .into_iter()
+            .filter(|part| !part.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let contenu = if contenu.is_empty() { titre.to_string() } else { contenu };
+
+        let sql = format!(
+            "INSERT INTO search_document (type_entite, entite_id, titre, contenu, document) \
+             VALUES ($1, $2, $3, $4, \
+                setweight(to_tsvector('{config}', $3), 'A') \
+                || setweight(to_tsvector('{config}', $5), 'B') \
+                || setweight(to_tsvector('{config}', $6), 'C')) \
+             ON CONFLICT (type_entite, entite_id) DO UPDATE SET \
+                titre = EXCLUDED.titre, contenu = EXCLUDED.contenu, document = EXCLUDED.document",
+            config = SEARCH_CONFIG
+        );
+
+        self.db
+            .execute_raw(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [
+                    kind.as_str().into(),
+                    id.into(),
+                    titre.into(),
+                    contenu.into(),
+                    details.into(),
+                    texte.into(),
+                ],
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Animals born outside `born_after`..=`born_before` are left out, the other kinds are not affected.
+    pub async fn search(
+        &self,
+        query: &str,
+        kind: Option<SearchKind>,
+        (born_after, born_before): (Option<NaiveDate>, Option<NaiveDate>),
+        limit: u64,
+    ) -> Result<Vec<SearchHit>, DbErr> {
+        let sql = format!(
+            "SELECT type_entite, entite_id, titre, \
+                ts_headline('{config}', contenu, query, 'StartSel={start}, StopSel={stop}, MaxWords=30, MinWords=10') AS extrait, \
+                ts_rank(document, query) AS score \
+             FROM search_document, websearch_to_tsquery('{config}', $1) AS query \
+             WHERE document @@ query AND ($2::text IS NULL OR type_entite = $2) \
+                AND (type_entite <> 'association' OR entite_id IN ({published_shelters})) \
+                AND (type_entite <> 'animal' OR entite_id IN \
+                    (SELECT id FROM animal WHERE association_id IN ({published_shelters}) \
+                        AND ($4::date IS NULL OR date_naissance >= $4) \
+                        AND ($5::date IS NULL OR date_naissance <= $5))) \
+             ORDER BY score DESC, type_entite, entite_id \
+             LIMIT $3",
+            config = SEARCH_CONFIG,
+            published_shelters = PUBLISHED_SHELTERS,
+            start = START_SEL,
+            stop = STOP_SEL
+        );
+
+        let rows = self
+            .db
+            .query_all_raw(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [
+                    query.into(),
+                    kind.map(|kind| kind.as_str().to_string()).into(),
+                    (limit as i64).into(),
+                    born_after.into(),
+                    born_before.into(),
+                ],
+            ))
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let kind: String = row.try_get("", "type_entite")?;
+                let extrait: String = row.try_get("", "extrait")?;
+
+                Ok(SearchHit {
+                    kind: SearchKind::parse(&kind).ok_or_else(|| DbErr::Type(format!("Unknown search type {}", kind)))?,
+                    id: row.try_get("", "entite_id")?,
+                    titre: row.try_get("", "titre")?,
+                    extrait: highlight(&extrait),
+                    score: row.try_get("", "score")?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Escapes the indexed text, which is user input, and only then turns the match markers into `<mark>` tags.
+fn highlight(extrait: &str) -> String {
+    let mut html = String::with_capacity(extrait.len());
+    for c in extrait.chars() {
+        match c {
+            START_SEL => html.push_str("<mark>"),
+            STOP_SEL => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            _ => html.push(c),
+        }
+    }
+
+    html
+}
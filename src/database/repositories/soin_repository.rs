@@ -0,0 +1,249 @@
+use crate::audit::AuditEntry;
+use crate::database::models::{
+    AnimalEntity, FicheSoinsActiveModel, FicheSoinsEntity, FicheSoinsModel, MediaEntity, TraitementActiveModel, TraitementColumn,
+    TraitementEntity, TraitementModel, VaccinationActiveModel, VaccinationColumn, VaccinationEntity, VaccinationModel,
+    VisiteVeterinaireActiveModel, VisiteVeterinaireColumn, VisiteVeterinaireEntity, VisiteVeterinaireModel, VisiteVeterinaireModelEx,
+};
+use crate::database::repositories::animal_repository::AnimalRepository;
+use crate::database::repositories::audit_repository::AuditRepository;
+use sea_orm::{ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, DeleteResult, EntityTrait, QueryFilter, QueryOrder, TransactionSession, TransactionTrait};
+
+/// Care records of animals: health sheet, vaccinations, treatments and vet visits.
+pub struct SoinRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
+}
+
+impl<'a, C: ConnectionTrait + TransactionTrait> SoinRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_fiche(&self, animal_id: i32) -> Result<Option<FicheSoinsModel>, DbErr> {
+        FicheSoinsEntity::find_by_id(animal_id).one(self.db).await
+    }
+
+    /// Creates the health sheet of the animal on its first save.
+    /// The animal's microchip flag follows `numero_puce`.
+    pub async fn save_fiche(&self, model: FicheSoinsActiveModel) -> Result<FicheSoinsModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = SoinRepository::new(&txn);
+        let before = match model.animal_id.try_as_ref() {
+            Some(animal_id) => repo.find_fiche(*animal_id).await?,
+            None => None,
+        };
+
+        let fiche = match before {
+            Some(_) => model.update(&txn).await?,
+            None => model.insert(&txn).await?,
+        };
+        AnimalRepository::new(&txn)
+            .set_microchipped(fiche.animal_id, fiche.numero_puce.is_some())
+            .await?;
+
+        let shelter_id = repo.shelter_of(fiche.animal_id).await?;
+        let entry = match &before {
+            Some(before) => AuditEntry::updated("fiche_soins", fiche.animal_id, Some(before), &fiche),
+            None => AuditEntry::created("fiche_soins", fiche.animal_id, &fiche),
+        };
+        AuditRepository::new(&txn).record(entry.for_shelter(shelter_id)).await?;
+
+        txn.commit().await?;
+
+        Ok(fiche)
+    }
+
+    pub async fn find_vaccinations(&self, animal_ids: Vec<i32>) -> Result<Vec<VaccinationModel>, DbErr> {
+        VaccinationEntity::find()
+            .filter(VaccinationColumn::AnimalId.is_in(animal_ids))
+            .order_by_desc(VaccinationColumn::AdministreLe)
+            .all(self.db)
+            .await
+    }
+
+    pub async fn find_vaccination(&self, id: i32) -> Result<Option<VaccinationModel>, DbErr> {
+        VaccinationEntity::find_by_id(id).one(self.db).await
+    }
+
+    pub async fn create_vaccination(&self, model: VaccinationActiveModel) -> Result<VaccinationModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let vaccination = model.insert(&txn).await?;
+        let shelter_id = SoinRepository::new(&txn).shelter_of(vaccination.animal_id).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("vaccination", vaccination.id, &vaccination).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(vaccination)
+    }
+
+    pub async fn update_vaccination(&self, model: VaccinationActiveModel) -> Result<VaccinationModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = SoinRepository::new(&txn);
+        let before = match model.id.try_as_ref() {
+            Some(id) => repo.find_vaccination(*id).await?,
+            None => None,
+        };
+        let vaccination = model.update(&txn).await?;
+        let shelter_id = repo.shelter_of(vaccination.animal_id).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("vaccination", vaccination.id, before.as_ref(), &vaccination).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(vaccination)
+    }
+
+    pub async fn delete_vaccination(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = SoinRepository::new(&txn);
+        let before = repo.find_vaccination(id).await?;
+        let result = VaccinationEntity::delete_by_id(id).exec(&txn).await?;
+
+        if let Some(before) = before.filter(|_| result.rows_affected > 0) {
+            let shelter_id = repo.shelter_of(before.animal_id).await?;
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("vaccination", id, Some(&before)).for_shelter(shelter_id))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    pub async fn find_treatments(&self, animal_ids: Vec<i32>) -> Result<Vec<TraitementModel>, DbErr> {
+        TraitementEntity::find()
+            .filter(TraitementColumn::AnimalId.is_in(animal_ids))
+            .order_by_desc(TraitementColumn::Debut)
+            .all(self.db)
+            .await
+    }
+
+    pub async fn find_treatment(&self, id: i32) -> Result<Option<TraitementModel>, DbErr> {
+        TraitementEntity::find_by_id(id).one(self.db).await
+    }
+
+    pub async fn create_treatment(&self, model: TraitementActiveModel) -> Result<TraitementModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let treatment = model.insert(&txn).await?;
+        let shelter_id = SoinRepository::new(&txn).shelter_of(treatment.animal_id).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("traitement", treatment.id, &treatment).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(treatment)
+    }
+
+    pub async fn update_treatment(&self, model: TraitementActiveModel) -> Result<TraitementModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = SoinRepository::new(&txn);
+        let before = match model.id.try_as_ref() {
+            Some(id) => repo.find_treatment(*id).await?,
+            None => None,
+        };
+        let treatment = model.update(&txn).await?;
+        let shelter_id = repo.shelter_of(treatment.animal_id).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("traitement", treatment.id, before.as_ref(), &treatment).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(treatment)
+    }
+
+    pub async fn delete_treatment(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = SoinRepository::new(&txn);
+        let before = repo.find_treatment(id).await?;
+        let result = TraitementEntity::delete_by_id(id).exec(&txn).await?;
+
+        if let Some(before) = before.filter(|_| result.rows_affected > 0) {
+            let shelter_id = repo.shelter_of(before.animal_id).await?;
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("traitement", id, Some(&before)).for_shelter(shelter_id))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Visits with their documents, most recent first.
+    pub async fn find_visits(&self, animal_ids: Vec<i32>) -> Result<Vec<VisiteVeterinaireModelEx>, DbErr> {
+        let mut visits = VisiteVeterinaireEntity::load()
+            .with(MediaEntity)
+            .filter(VisiteVeterinaireColumn::AnimalId.is_in(animal_ids))
+            .all(self.db)
+            .await?;
+
+        visits.sort_by(|a, b| b.prevue_le.cmp(&a.prevue_le).then(b.id.cmp(&a.id)));
+
+        Ok(visits)
+    }
+
+    pub async fn find_visit(&self, id: i32) -> Result<Option<VisiteVeterinaireModel>, DbErr> {
+        VisiteVeterinaireEntity::find_by_id(id).one(self.db).await
+    }
+
+    pub async fn create_visit(&self, model: VisiteVeterinaireActiveModel) -> Result<VisiteVeterinaireModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let visit = model.insert(&txn).await?;
+        let shelter_id = SoinRepository::new(&txn).shelter_of(visit.animal_id).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("visite_veterinaire", visit.id, &visit).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(visit)
+    }
+
+    pub async fn update_visit(&self, model: VisiteVeterinaireActiveModel) -> Result<VisiteVeterinaireModel, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = SoinRepository::new(&txn);
+        let before = match model.id.try_as_ref() {
+            Some(id) => repo.find_visit(*id).await?,
+            None => None,
+        };
+        let visit = model.update(&txn).await?;
+        let shelter_id = repo.shelter_of(visit.animal_id).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("visite_veterinaire", visit.id, before.as_ref(), &visit).for_shelter(shelter_id))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(visit)
+    }
+
+    /// The visit documents are removed along with their rows by the foreign key.
+    pub async fn delete_visit(&self, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = self.db.begin().await?;
+        let repo = SoinRepository::new(&txn);
+        let before = repo.find_visit(id).await?;
+        let result = VisiteVeterinaireEntity::delete_by_id(id).exec(&txn).await?;
+
+        if let Some(before) = before.filter(|_| result.rows_affected > 0) {
+            let shelter_id = repo.shelter_of(before.animal_id).await?;
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("visite_veterinaire", id, Some(&before)).for_shelter(shelter_id))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn shelter_of(&self, animal_id: i32) -> Result<Option<i32>, DbErr> {
+        let animal = AnimalEntity::find_by_id(animal_id).one(self.db).await?;
+
+        Ok(animal.map(|animal| animal.association_id))
+    }
+}
@@ -0,0 +1,320 @@
+use sea_orm::prelude::DateTime;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, QueryResult, Statement, TransactionTrait};
+use serde::Serialize;
+
+/// Public counts only cover the shelters listed publicly, see `association::ModelEx::is_published`.
+const PUBLISHED_SHELTER: &str = "verification = 'Vérifiée' AND inscription = 'Approuvée'";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatutCount {
+    pub statut: String,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EspeceCount {
+    pub espece_id: i32,
+    pub espece: String,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestCounts {
+    pub en_attente: i64,
+    pub validees: i64,
+    pub refusees: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlacementDelays {
+    pub jours_avant_placement: Option<f64>,
+    pub jours_avant_adoption: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthlyStats {
+    pub mois: String,
+    pub arrivees: i64,
+    pub placements: i64,
+    pub adoptions: i64,
+    pub demandes: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnrequestedAnimal {
+    pub id: i32,
+    pub nom: String,
+    pub cree_le: DateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct PostalCodeCount {
+    pub pays: String,
+    pub code_postal: String,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimalImpactCount {
+    pub espece_id: i32,
+    pub espece: String,
+    pub pays: String,
+    pub code_postal: String,
+    pub total: i64,
+}
+
+/// Dashboard aggregates for one shelter and for the whole platform, computed in SQL.
+pub struct StatsRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
+}
+
+impl<'a, C: ConnectionTrait + TransactionTrait> StatsRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
+        Self { db }
+    }
+
+    async fn query(&self, sql: &str, values: Vec<sea_orm::Value>) -> Result<Vec<QueryResult>, DbErr> {
+        self.db
+            .query_all_raw(Statement::from_sql_and_values(DbBackend::Postgres, sql, values))
+            .await
+    }
+
+    pub async fn count_by_statut(&self, shelter_id: i32) -> Result<Vec<StatutCount>, DbErr> {
+        let rows = self
+            .query(
+                "SELECT statut::text AS statut, count(*) AS total FROM animal \
+                 WHERE association_id = $1 AND deleted_at IS NULL GROUP BY statut ORDER BY statut",
+                vec![shelter_id.into()],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(StatutCount {
+                    statut: row.try_get("", "statut")?,
+                    total: row.try_get("", "total")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn count_by_espece(&self, shelter_id: i32) -> Result<Vec<EspeceCount>, DbErr> {
+        let rows = self
+            .query(
+                "SELECT e.id AS espece_id, e.nom AS espece, count(*) AS total \
+                 FROM animal a JOIN espece e ON e.id = a.espece_id \
+                 WHERE a.association_id = $1 AND a.deleted_at IS NULL GROUP BY e.id, e.nom ORDER BY total DESC, e.nom",
+                vec![shelter_id.into()],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(EspeceCount {
+                    espece_id: row.try_get("", "espece_id")?,
+                    espece: row.try_get("", "espece")?,
+                    total: row.try_get("", "total")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn count_requests(&self, shelter_id: i32) -> Result<RequestCounts, DbErr> {
+        let rows = self
+            .query(
+                "SELECT \
+                    count(*) FILTER (WHERE d.statut_demande = 'En attente') AS en_attente, \
+                    count(*) FILTER (WHERE d.statut_demande = 'Validée') AS validees, \
+                    count(*) FILTER (WHERE d.statut_demande = 'Refusée') AS refusees \
+                 FROM demande d JOIN animal a ON a.id = d.animal_id \
+                 WHERE a.association_id = $1",
+                vec![shelter_id.into()],
+            )
+            .await?;
+        let row = rows.first().ok_or(DbErr::RecordNotFound("demande".to_string()))?;
+
+        Ok(RequestCounts {
+            en_attente: row.try_get("", "en_attente")?,
+            validees: row.try_get("", "validees")?,
+            refusees: row.try_get("", "refusees")?,
+        })
+    }
+
+    /// Average number of days from intake to the first placement and to adoption.
+    pub async fn placement_delays(&self, shelter_id: i32) -> Result<PlacementDelays, DbErr> {
+        let rows = self
+            .query(
+                "SELECT \
+                    avg(EXTRACT(EPOCH FROM (h.premier_placement - a.cree_le)) / 86400)::float8 AS jours_avant_placement, \
+                    avg(EXTRACT(EPOCH FROM (h.adoption - a.cree_le)) / 86400)::float8 AS jours_avant_adoption \
+                 FROM animal a \
+                 JOIN ( \
+                    SELECT animal_id, \
+                        min(change_le) FILTER (WHERE statut = 'Accueilli') AS premier_placement, \
+                        min(change_le) FILTER (WHERE statut = 'Adopté') AS adoption \
+                    FROM animal_statut_historique GROUP BY animal_id \
+                 ) h ON h.animal_id = a.id \
+                 WHERE a.association_id = $1",
+                vec![shelter_id.into()],
+            )
+            .await?;
+        let row = rows.first().ok_or(DbErr::RecordNotFound("animal".to_string()))?;
+
+        Ok(PlacementDelays {
+            jours_avant_placement: row.try_get("", "jours_avant_placement")?,
+            jours_avant_adoption: row.try_get("", "jours_avant_adoption")?,
+        })
+    }
+
+    pub async fn monthly(&self, shelter_id: i32, from: DateTime, to: DateTime) -> Result<Vec<MonthlyStats>, DbErr> {
+        let rows = self
+            .query(
+                "WITH mois AS ( \
+                    SELECT generate_series(date_trunc('month', $2::timestamp), date_trunc('month', $3::timestamp), interval '1 month') AS debut \
+                 ), \
+                 arrivees AS ( \
+                    SELECT date_trunc('month', cree_le) AS debut, count(*) AS total FROM animal \
+                    WHERE association_id = $1 GROUP BY 1 \
+                 ), \
+                 changements AS ( \
+                    SELECT date_trunc('month', h.change_le) AS debut, \
+                        count(*) FILTER (WHERE h.statut = 'Accueilli') AS placements, \
+                        count(*) FILTER (WHERE h.statut = 'Adopté') AS adoptions \
+                    FROM animal_statut_historique h JOIN animal a ON a.id = h.animal_id \
+                    WHERE a.association_id = $1 GROUP BY 1 \
+                 ), \
+                 demandes AS ( \
+                    SELECT date_trunc('month', d.date_debut::timestamp) AS debut, count(*) AS total \
+                    FROM demande d JOIN animal a ON a.id = d.animal_id \
+                    WHERE a.association_id = $1 GROUP BY 1 \
+                 ) \
+                 SELECT to_char(m.debut, 'YYYY-MM') AS mois, \
+                    coalesce(ar.total, 0) AS arrivees, \
+                    coalesce(c.placements, 0) AS placements, \
+                    coalesce(c.adoptions, 0) AS adoptions, \
+                    coalesce(d.total, 0) AS demandes \
+                 FROM mois m \
+                 LEFT JOIN arrivees ar ON ar.debut = m.debut \
+                 LEFT JOIN changements c ON c.debut = m.debut \
+                 LEFT JOIN demandes d ON d.debut = m.debut \
+                 ORDER BY m.debut",
+                vec![shelter_id.into(), from.into(), to.into()],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(MonthlyStats {
+                    mois: row.try_get("", "mois")?,
+                    arrivees: row.try_get("", "arrivees")?,
+                    placements: row.try_get("", "placements")?,
+                    adoptions: row.try_get("", "adoptions")?,
+                    demandes: row.try_get("", "demandes")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Animals still in the shelter that received no request during the last `days` days.
+    pub async fn find_unrequested(&self, shelter_id: i32, days: i32) -> Result<Vec<UnrequestedAnimal>, DbErr> {
+        let rows = self
+            .query(
+                "SELECT a.id, a.nom, a.cree_le FROM animal a \
+                 WHERE a.association_id = $1 AND a.statut = 'En refuge' AND a.deleted_at IS NULL \
+                    AND a.cree_le <= now() - make_interval(days => $2) \
+                    AND NOT EXISTS ( \
+                        SELECT 1 FROM demande d \
+                        WHERE d.animal_id = a.id AND d.date_debut >= (now() - make_interval(days => $2))::date \
+                    ) \
+                 ORDER BY a.cree_le",
+                vec![shelter_id.into(), days.into()],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(UnrequestedAnimal {
+                    id: row.try_get("", "id")?,
+                    nom: row.try_get("", "nom")?,
+                    cree_le: row.try_get("", "cree_le")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn count_shelters_by_postal_code(&self) -> Result<Vec<PostalCodeCount>, DbErr> {
+        let sql = format!(
+            "SELECT pays, code_postal, count(*) AS total FROM association \
+             WHERE deleted_at IS NULL AND {published} \
+             GROUP BY pays, code_postal",
+            published = PUBLISHED_SHELTER
+        );
+        let rows = self.query(&sql, vec![]).await?;
+
+        rows.iter().map(postal_code_count).collect()
+    }
+
+    /// Fosters who logged in during the last year and were neither anonymized nor archived.
+    pub async fn count_active_fosters_by_postal_code(&self) -> Result<Vec<PostalCodeCount>, DbErr> {
+        let rows = self
+            .query(
+                "SELECT f.pays, f.code_postal, count(*) AS total \
+                 FROM famille f JOIN utilisateur u ON u.id = f.utilisateur_id \
+                 WHERE f.anonymise_le IS NULL AND f.deleted_at IS NULL AND u.derniere_connexion >= now() - interval '1 year' \
+                 GROUP BY f.pays, f.code_postal",
+                vec![],
+            )
+            .await?;
+
+        rows.iter().map(postal_code_count).collect()
+    }
+
+    pub async fn count_fostered_animals(&self) -> Result<Vec<AnimalImpactCount>, DbErr> {
+        let sql = format!(
+            "SELECT a.espece_id, e.nom AS espece, s.pays, s.code_postal, count(*) AS total \
+             FROM animal a \
+             JOIN espece e ON e.id = a.espece_id \
+             JOIN association s ON s.id = a.association_id \
+             WHERE a.statut = 'Accueilli' AND a.deleted_at IS NULL AND {published} \
+             GROUP BY a.espece_id, e.nom, s.pays, s.code_postal",
+            published = PUBLISHED_SHELTER
+        );
+        let rows = self.query(&sql, vec![]).await?;
+
+        rows.iter().map(animal_impact_count).collect()
+    }
+
+    pub async fn count_adoptions_this_year(&self) -> Result<Vec<AnimalImpactCount>, DbErr> {
+        let sql = format!(
+            "SELECT a.espece_id, e.nom AS espece, s.pays, s.code_postal, count(DISTINCT a.id) AS total \
+             FROM animal_statut_historique h \
+             JOIN animal a ON a.id = h.animal_id \
+             JOIN espece e ON e.id = a.espece_id \
+             JOIN association s ON s.id = a.association_id \
+             WHERE h.statut = 'Adopté' AND h.change_le >= date_trunc('year', now()) AND {published} \
+             GROUP BY a.espece_id, e.nom, s.pays, s.code_postal",
+            published = PUBLISHED_SHELTER
+        );
+        let rows = self.query(&sql, vec![]).await?;
+
+        rows.iter().map(animal_impact_count).collect()
+    }
+}
+
+fn postal_code_count(row: &QueryResult) -> Result<PostalCodeCount, DbErr> {
+    Ok(PostalCodeCount {
+        pays: row.try_get("", "pays")?,
+        code_postal: row.try_get("", "code_postal")?,
+        total: row.try_get("", "total")?,
+    })
+}
+
+fn animal_impact_count(row: &QueryResult) -> Result<AnimalImpactCount, DbErr> {
+    Ok(AnimalImpactCount {
+        espece_id: row.try_get("", "espece_id")?,
+        espece: row.try_get("", "espece")?,
+        pays: row.try_get("", "pays")?,
+        code_postal: row.try_get("", "code_postal")?,
+        total: row.try_get("", "total")?,
+    })
+}
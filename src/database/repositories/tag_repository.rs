@@ -1,15 +1,18 @@
+use crate::audit::AuditEntry;
 use crate::database::models::{TagActiveModel, TagEntity, TagModel};
+use crate::database::repositories::audit_repository::AuditRepository;
+use crate::database::repositories::search_repository::{SearchKind, SearchRepository};
 use sea_orm::DeleteResult;
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait,
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, TransactionSession, TransactionTrait,
 };
 
-pub struct TagRepository<'a> {
-    db: &'a DatabaseConnection,
+pub struct TagRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
 }
 
-impl<'a> TagRepository<'a> {
-    pub fn new(db: &'a DatabaseConnection) -> Self {
+impl<'a, C: ConnectionTrait + TransactionTrait> TagRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
         Self { db }
     }
 
@@ -22,14 +25,49 @@ impl<'a> TagRepository<'a> {
     }
 
     pub async fn create(&self, model: TagActiveModel) -> Result<TagModel, DbErr> {
-        model.insert(self.db).await
+        let txn = self.db.begin().await?;
+        let tag = model.insert(&txn).await?;
+        SearchRepository::new(&txn).index_tag(&tag).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("tag", tag.id, &tag))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(tag)
     }
 
     pub async fn update(&self, model: TagActiveModel) -> Result<TagModel, DbErr> {
-        model.update(self.db).await
+        let txn = self.db.begin().await?;
+        let before = match model.id.try_as_ref() {
+            Some(id) => TagEntity::find_by_id(*id).one(&txn).await?,
+            None => None,
+        };
+        let tag = model.update(&txn).await?;
+        SearchRepository::new(&txn).index_tag(&tag).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("tag", tag.id, before.as_ref(), &tag))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(tag)
     }
 
     pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
-        TagEntity::delete_by_id(id).exec(self.db).await
+        let txn = self.db.begin().await?;
+        let before = TagEntity::find_by_id(id).one(&txn).await?;
+        let result = TagEntity::delete_by_id(id).exec(&txn).await?;
+        SearchRepository::new(&txn).remove(SearchKind::Tag, id).await?;
+
+        if result.rows_affected > 0 {
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("tag", id, before.as_ref()))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
     }
 }
\ No newline at end of file
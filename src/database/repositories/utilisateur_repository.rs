@@ -1,15 +1,19 @@
-use crate::database::models::{AssociationEntity, FamilleEntity, UtilisateurActiveModel, UtilisateurActiveModelEx, UtilisateurEntity, UtilisateurModel, UtilisateurModelEx};
-use sea_orm::{DeleteResult, EntityLoaderTrait};
+use crate::database::models::{AssociationEntity, FamilleEntity, UtilisateurActiveModel, UtilisateurActiveModelEx, UtilisateurEntity, UtilisateurModel, UtilisateurModelEx, utilisateur};
+use crate::audit::AuditEntry;
+use crate::database::repositories::audit_repository::AuditRepository;
+use sea_orm::prelude::DateTime;
+use sea_orm::sea_query::Expr;
+use sea_orm::{DeleteResult, EntityLoaderTrait, QueryFilter, QuerySelect};
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait,
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, TransactionSession, TransactionTrait,
 };
 
-pub struct UtilisateurRepository<'a> {
-    db: &'a DatabaseConnection,
+pub struct UtilisateurRepository<'a, C = DatabaseConnection> {
+    db: &'a C,
 }
 
-impl<'a> UtilisateurRepository<'a> {
-    pub fn new(db: &'a DatabaseConnection) -> Self {
+impl<'a, C: ConnectionTrait + TransactionTrait> UtilisateurRepository<'a, C> {
+    pub fn new(db: &'a C) -> Self {
         Self { db }
     }
 
@@ -39,15 +43,68 @@ impl<'a> UtilisateurRepository<'a> {
         Ok(user)
     }
 
+    pub async fn find_inactive_ids(&self, cutoff: DateTime) -> Result<Vec<i32>, DbErr> {
+        UtilisateurEntity::find()
+            .select_only()
+            .column(utilisateur::COLUMN.id.0)
+            .filter(utilisateur::COLUMN.derniere_connexion.lt(cutoff))
+            .into_tuple()
+            .all(self.db)
+            .await
+    }
+
+    pub async fn touch_last_login(&self, id: i32, now: DateTime) -> Result<(), DbErr> {
+        UtilisateurEntity::update_many()
+            .col_expr(utilisateur::COLUMN.derniere_connexion, Expr::value(now))
+            .filter(utilisateur::COLUMN.id.eq(id))
+            .exec(self.db)
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn create(&self, model: UtilisateurActiveModel) -> Result<UtilisateurModel, DbErr> {
-        model.insert(self.db).await
+        let txn = self.db.begin().await?;
+        let user = model.insert(&txn).await?;
+        AuditRepository::new(&txn)
+            .record(AuditEntry::created("utilisateur", user.id, &user))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(user)
     }
 
     pub async fn update(&self, model: UtilisateurActiveModelEx) -> Result<UtilisateurModelEx, DbErr> {
-        model.update(self.db).await
+        let txn = self.db.begin().await?;
+        let before = match model.id.try_as_ref() {
+            Some(id) => UtilisateurEntity::find_by_id(*id).one(&txn).await?,
+            None => None,
+        };
+        let user = model.update(&txn).await?;
+        let after: UtilisateurModel = user.clone().into();
+        AuditRepository::new(&txn)
+            .record(AuditEntry::updated("utilisateur", after.id, before.as_ref(), &after))
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(user)
     }
 
     pub async fn delete(&self, id: i32) -> Result<DeleteResult, DbErr> {
-        UtilisateurEntity::delete_by_id(id).exec(self.db).await
+        let txn = self.db.begin().await?;
+        let before = UtilisateurEntity::find_by_id(id).one(&txn).await?;
+        let result = UtilisateurEntity::delete_by_id(id).exec(&txn).await?;
+
+        if result.rows_affected > 0 {
+            AuditRepository::new(&txn)
+                .record(AuditEntry::deleted("utilisateur", id, before.as_ref()))
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result)
     }
 }
\ No newline at end of file
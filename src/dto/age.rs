@@ -0,0 +1,131 @@
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::CustomError;
+use crate::database::models::animal;
+use sea_orm::Condition;
+
+/// Age computed from a birth date, in full years and in full months.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Age {
+    pub annees: i32,
+    pub mois: i32,
+}
+
+impl Age {
+    pub fn on(date_naissance: NaiveDate, today: NaiveDate) -> Self {
+        let mut mois = (today.year() - date_naissance.year()) * 12 + today.month() as i32 - date_naissance.month() as i32;
+        if today.day() < date_naissance.day() {
+            mois -= 1;
+        }
+        let mois = mois.max(0);
+
+        Age { annees: mois / 12, mois }
+    }
+
+    pub fn today(date_naissance: NaiveDate) -> Self {
+        Age::on(date_naissance, Utc::now().date_naive())
+    }
+}
+
+/// Age range in full years on listings.
+#[derive(Debug, Deserialize)]
+pub struct AgeQuery {
+    pub age_min: Option<u32>,
+    pub age_max: Option<u32>,
+}
+
+impl AgeQuery {
+    /// Earliest and latest birth dates of the animals within the range.
+    pub fn birth_dates(&self) -> Result<(Option<NaiveDate>, Option<NaiveDate>), CustomError> {
+        self.birth_dates_on(Utc::now().date_naive())
+    }
+
+    fn birth_dates_on(&self, today: NaiveDate) -> Result<(Option<NaiveDate>, Option<NaiveDate>), CustomError> {
+        if let (Some(age_min), Some(age_max)) = (self.age_min, self.age_max)
+            && age_min > age_max
+        {
+            return Err(CustomError::ValidationError {
+                error_messages: "age_min: The minimum age cannot be above the maximum age".to_string(),
+            });
+        }
+
+        let years_ago = |years: u32| today.checked_sub_months(Months::new(years.saturating_mul(12)));
+
+        let latest = self.age_min.and_then(years_ago);
+        let earliest = self
+            .age_max
+            .and_then(|age_max| years_ago(age_max.saturating_add(1)))
+            .and_then(|date| date.succ_opt());
+
+        Ok((earliest, latest))
+    }
+
+    /// Birth date bounds of the range, applied by the animal queries.
+    pub fn condition(&self) -> Result<Condition, CustomError> {
+        let (earliest, latest) = self.birth_dates()?;
+
+        Ok(Condition::all()
+            .add_option(earliest.map(|earliest| animal::COLUMN.date_naissance.gte(earliest)))
+            .add_option(latest.map(|latest| animal::COLUMN.date_naissance.lte(latest))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn counts_full_months() {
+        assert_eq!(Age::on(date(2023, 5, 15), date(2024, 5, 14)), Age { annees: 0, mois: 11 });
+        assert_eq!(Age::on(date(2023, 5, 15), date(2024, 5, 15)), Age { annees: 1, mois: 12 });
+        assert_eq!(Age::on(date(2024, 5, 15), date(2024, 5, 1)), Age { annees: 0, mois: 0 });
+    }
+
+    #[test]
+    fn month_end_birthdays_wait_for_the_day() {
+        assert_eq!(Age::on(date(2024, 1, 31), date(2024, 2, 29)).mois, 0);
+        assert_eq!(Age::on(date(2024, 1, 31), date(2024, 3, 1)).mois, 1);
+        assert_eq!(Age::on(date(2024, 2, 29), date(2025, 2, 28)).annees, 0);
+        assert_eq!(Age::on(date(2024, 2, 29), date(2025, 3, 1)).annees, 1);
+    }
+
+    #[test]
+    fn rejects_inverted_ranges() {
+        let query = AgeQuery { age_min: Some(3), age_max: Some(1) };
+
+        assert!(query.birth_dates_on(date(2025, 6, 1)).is_err());
+    }
+
+    #[test]
+    fn age_max_includes_the_whole_last_year() {
+        let query = AgeQuery { age_min: None, age_max: Some(2) };
+
+        assert_eq!(query.birth_dates_on(date(2025, 6, 15)).unwrap(), (Some(date(2022, 6, 16)), None));
+    }
+
+    #[test]
+    fn bounds_agree_with_the_displayed_age() {
+        for today in [date(2025, 2, 28), date(2024, 2, 29), date(2025, 3, 31), date(2025, 12, 31)] {
+            for (age_min, age_max) in [(Some(1), None), (None, Some(0)), (Some(1), Some(2)), (Some(2), Some(2))] {
+                let query = AgeQuery { age_min, age_max };
+                let (earliest, latest) = query.birth_dates_on(today).unwrap();
+
+                let mut date_naissance = date(today.year() - 5, 1, 1);
+                while date_naissance <= today {
+                    let annees = Age::on(date_naissance, today).annees as u32;
+                    let in_range = age_min.is_none_or(|min| annees >= min) && age_max.is_none_or(|max| annees <= max);
+                    let in_bounds = earliest.is_none_or(|earliest| date_naissance >= earliest)
+                        && latest.is_none_or(|latest| date_naissance <= latest);
+                    assert_eq!(in_range, in_bounds, "born {} on {} with {:?}", date_naissance, today, (age_min, age_max));
+
+                    date_naissance = date_naissance.succ_opt().unwrap();
+                }
+            }
+        }
+    }
+}
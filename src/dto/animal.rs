@@ -0,0 +1,144 @@
+use sea_orm::{ColumnTrait, Condition};
+use sea_orm::entity::prelude::{HasMany, HasOne};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::AuthenticatedUser;
+use chrono::NaiveDate;
+
+use crate::database::models::sea_orm_active_enums::{NiveauEnergie, PrecisionNaissance, Sexe, Statut, Taille};
+use crate::database::models::animal;
+use crate::database::models::{AnimalModelEx, AssociationEntity, EspeceEntity, MediaEntity, PeseeModel, TagEntity};
+use crate::dto::{Age, Audience, DemandeResponse, FosterResponse};
+
+/// Profile filters on listings, an animal whose compatibility is unknown does not match.
+#[derive(Debug, Deserialize)]
+pub struct ProfileQuery {
+    pub taille: Option<Taille>,
+    pub niveau_energie: Option<NiveauEnergie>,
+    pub ok_enfants: Option<bool>,
+    pub ok_chiens: Option<bool>,
+    pub ok_chats: Option<bool>,
+    pub propre: Option<bool>,
+    pub besoins_speciaux: Option<bool>,
+    pub puce: Option<bool>,
+}
+
+impl ProfileQuery {
+    /// Filters on the profile columns, a compatibility left `NULL` never equals the wanted value.
+    pub fn condition(&self) -> Condition {
+        Condition::all()
+            .add_option(self.taille.clone().map(|taille| animal::COLUMN.taille.eq(taille)))
+            .add_option(self.niveau_energie.clone().map(|niveau| animal::COLUMN.niveau_energie.eq(niveau)))
+            .add_option(self.ok_enfants.map(|ok| animal::COLUMN.ok_enfants.eq(ok)))
+            .add_option(self.ok_chiens.map(|ok| animal::COLUMN.ok_chiens.eq(ok)))
+            .add_option(self.ok_chats.map(|ok| animal::COLUMN.ok_chats.eq(ok)))
+            .add_option(self.propre.map(|propre| animal::COLUMN.propre.eq(propre)))
+            .add_option(self.besoins_speciaux.map(|besoins| animal::COLUMN.besoins_speciaux.eq(besoins)))
+            .add_option(self.puce.map(|puce| animal::COLUMN.puce.eq(puce)))
+    }
+}
+
+/// Animal with its host and requests filtered for the viewer.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnimalResponse {
+    pub id: i32,
+    pub nom: String,
+    pub race: Option<String>,
+    pub race_id: Option<i32>,
+    pub croise: bool,
+    pub couleur: String,
+    pub date_naissance: NaiveDate,
+    pub precision_naissance: PrecisionNaissance,
+    pub age: Age,
+    pub sexe: Sexe,
+    pub description: String,
+    pub taille: Option<Taille>,
+    pub niveau_energie: Option<NiveauEnergie>,
+    pub ok_enfants: Option<bool>,
+    pub ok_chiens: Option<bool>,
+    pub ok_chats: Option<bool>,
+    pub propre: Option<bool>,
+    pub besoins_speciaux: bool,
+    pub puce: bool,
+    pub histoire: Option<String>,
+    /// Latest weigh-in.
+    pub poids_kg: Option<f64>,
+    pub pesees: Vec<PeseeModel>,
+    pub statut: Statut,
+    pub association_id: i32,
+    pub famille_id: Option<i32>,
+    pub espece_id: i32,
+    pub tags: HasMany<TagEntity>,
+    pub refuge: HasOne<AssociationEntity>,
+    pub demandes: Vec<DemandeResponse>,
+    pub espece: HasOne<EspeceEntity>,
+    pub accueillant: Option<FosterResponse>,
+    pub images_animal: HasMany<MediaEntity>,
+}
+
+impl AnimalResponse {
+    pub fn new(animal: AnimalModelEx, viewer: Option<&AuthenticatedUser>) -> Self {
+        let shelter_id = animal.association_id;
+
+        let accueillant = animal.accueillant.into_option().map(|foster| {
+            let audience = Audience::for_host(viewer, foster.id, shelter_id);
+            FosterResponse::new(foster.into(), audience)
+        });
+
+        let demandes = match animal.demandes {
+            HasMany::Loaded(requests) => requests
+                .into_iter()
+                .map(|request| DemandeResponse::new(request, viewer, Some(shelter_id)))
+                .collect(),
+            HasMany::Unloaded => vec![],
+        };
+
+        let mut pesees = match animal.pesees {
+            HasMany::Loaded(weights) => weights.into_iter().map(PeseeModel::from).collect(),
+            HasMany::Unloaded => vec![],
+        };
+        pesees.sort_by_key(|pesee: &PeseeModel| (pesee.pesee_le, pesee.id));
+
+        AnimalResponse {
+            id: animal.id,
+            nom: animal.nom,
+            race: animal.race,
+            race_id: animal.race_id,
+            croise: animal.croise,
+            couleur: animal.couleur,
+            date_naissance: animal.date_naissance,
+            precision_naissance: animal.precision_naissance,
+            age: Age::today(animal.date_naissance),
+            sexe: animal.sexe,
+            description: animal.description,
+            taille: animal.taille,
+            niveau_energie: animal.niveau_energie,
+            ok_enfants: animal.ok_enfants,
+            ok_chiens: animal.ok_chiens,
+            ok_chats: animal.ok_chats,
+            propre: animal.propre,
+            besoins_speciaux: animal.besoins_speciaux,
+            puce: animal.puce,
+            histoire: animal.histoire,
+            poids_kg: pesees.last().map(|pesee| pesee.poids_kg),
+            pesees,
+            statut: animal.statut,
+            association_id: animal.association_id,
+            famille_id: animal.famille_id,
+            espece_id: animal.espece_id,
+            tags: animal.tags,
+            refuge: animal.refuge,
+            demandes,
+            espece: animal.espece,
+            accueillant,
+            images_animal: animal.images_animal,
+        }
+    }
+
+    pub fn from_list(animals: Vec<AnimalModelEx>, viewer: Option<&AuthenticatedUser>) -> Vec<Self> {
+        animals
+            .into_iter()
+            .map(|animal| AnimalResponse::new(animal, viewer))
+            .collect()
+    }
+}
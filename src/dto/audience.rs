@@ -0,0 +1,45 @@
+use crate::auth::AuthenticatedUser;
+use crate::database::models::sea_orm_active_enums::StatutDemande;
+
+/// Who a response is built for, from the least to the most privileged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Audience {
+    Public,
+    /// A shelter which accepted a request from this foster.
+    AcceptedShelter,
+    Owner,
+    Admin,
+}
+
+impl Audience {
+    /// Audience for the foster data attached to a request on an animal of `shelter_id`.
+    pub fn for_request(
+        viewer: Option<&AuthenticatedUser>,
+        famille_id: i32,
+        shelter_id: Option<i32>,
+        statut: &StatutDemande,
+    ) -> Self {
+        match viewer {
+            Some(user) if user.is_admin() => Audience::Admin,
+            Some(user) if user.famille_id == Some(famille_id) => Audience::Owner,
+            Some(user)
+                if *statut == StatutDemande::Validée
+                    && shelter_id.is_some()
+                    && user.association_id == shelter_id =>
+            {
+                Audience::AcceptedShelter
+            }
+            _ => Audience::Public,
+        }
+    }
+
+    /// Audience for the foster currently hosting an animal of `shelter_id`.
+    pub fn for_host(viewer: Option<&AuthenticatedUser>, famille_id: i32, shelter_id: i32) -> Self {
+        match viewer {
+            Some(user) if user.is_admin() => Audience::Admin,
+            Some(user) if user.famille_id == Some(famille_id) => Audience::Owner,
+            Some(user) if user.association_id == Some(shelter_id) => Audience::AcceptedShelter,
+            _ => Audience::Public,
+        }
+    }
+}
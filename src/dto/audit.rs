@@ -0,0 +1,35 @@
+use sea_orm::prelude::DateTime;
+use serde::Deserialize;
+
+use crate::database::models::sea_orm_active_enums::ActionAudit;
+use crate::database::repositories::AuditFilter;
+
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub acteur_id: Option<i32>,
+    pub action: Option<ActionAudit>,
+    pub type_entite: Option<String>,
+    pub entite_id: Option<i32>,
+    pub depuis: Option<DateTime>,
+    pub avant_id: Option<i64>,
+    pub limit: Option<u64>,
+}
+
+impl AuditQuery {
+    /// `association_id` restricts the entries to a single shelter.
+    pub fn into_filter(self, association_id: Option<i32>) -> AuditFilter {
+        AuditFilter {
+            association_id,
+            acteur_id: self.acteur_id,
+            action: self.action,
+            type_entite: self.type_entite,
+            entite_id: self.entite_id,
+            depuis: self.depuis,
+            avant_id: self.avant_id,
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use sea_orm::prelude::Date;
+use serde::Serialize;
+
+use crate::auth::AuthenticatedUser;
+use crate::database::models::DemandeModelEx;
+use crate::database::models::sea_orm_active_enums::StatutDemande;
+use crate::dto::{AnimalResponse, Audience, FosterResponse};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DemandeResponse {
+    pub id: i32,
+    pub famille_id: i32,
+    pub animal_id: i32,
+    pub statut_demande: StatutDemande,
+    pub date_debut: Date,
+    pub date_fin: Date,
+    pub animal: Option<AnimalResponse>,
+    pub famille: Option<FosterResponse>,
+}
+
+impl DemandeResponse {
+    /// `shelter_id` is the shelter of the requested animal, when the caller already knows it.
+    pub fn new(request: DemandeModelEx, viewer: Option<&AuthenticatedUser>, shelter_id: Option<i32>) -> Self {
+        let animal = request.animal.into_option();
+        let shelter_id = shelter_id.or(animal.as_ref().map(|animal| animal.association_id));
+        let audience = Audience::for_request(viewer, request.famille_id, shelter_id, &request.statut_demande);
+
+        DemandeResponse {
+            id: request.id,
+            famille_id: request.famille_id,
+            animal_id: request.animal_id,
+            statut_demande: request.statut_demande,
+            date_debut: request.date_debut,
+            date_fin: request.date_fin,
+            animal: animal.map(|animal| AnimalResponse::new(animal, viewer)),
+            famille: request
+                .famille
+                .into_option()
+                .map(|foster| FosterResponse::new(foster.into(), audience)),
+        }
+    }
+
+    pub fn from_list(requests: Vec<DemandeModelEx>, viewer: Option<&AuthenticatedUser>) -> Vec<Self> {
+        requests
+            .into_iter()
+            .map(|request| DemandeResponse::new(request, viewer, None))
+            .collect()
+    }
+}
@@ -0,0 +1,62 @@
+use serde::Serialize;
+
+use crate::database::models::FamilleModel;
+use crate::dto::Audience;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FosterPublic {
+    pub id: i32,
+    pub prenom: Option<String>,
+    pub commune: String,
+    pub hebergement: String,
+    pub terrain: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FosterContact {
+    pub id: i32,
+    pub prenom: Option<String>,
+    pub nom: String,
+    pub telephone: String,
+    pub rue: String,
+    pub commune: String,
+    pub code_postal: String,
+    pub pays: String,
+    pub hebergement: String,
+    pub terrain: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum FosterResponse {
+    Public(FosterPublic),
+    Contact(FosterContact),
+    Full(FamilleModel),
+}
+
+impl FosterResponse {
+    pub fn new(foster: FamilleModel, audience: Audience) -> Self {
+        match audience {
+            Audience::Public => FosterResponse::Public(FosterPublic {
+                id: foster.id,
+                prenom: foster.prenom,
+                commune: foster.commune,
+                hebergement: foster.hebergement,
+                terrain: foster.terrain,
+            }),
+            Audience::AcceptedShelter => FosterResponse::Contact(FosterContact {
+                id: foster.id,
+                prenom: foster.prenom,
+                nom: foster.nom,
+                telephone: foster.telephone,
+                rue: foster.rue,
+                commune: foster.commune,
+                code_postal: foster.code_postal,
+                pays: foster.pays,
+                hebergement: foster.hebergement,
+                terrain: foster.terrain,
+            }),
+            Audience::Owner | Audience::Admin => FosterResponse::Full(foster),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+pub mod age;
+pub mod animal;
+pub mod audit;
+pub mod audience;
+pub mod demande;
+pub mod famille;
+pub mod nearby;
+pub mod ranked;
+pub mod soin;
+pub mod stats;
+pub mod utilisateur;
+
+pub use age::{Age, AgeQuery};
+pub use animal::{AnimalResponse, ProfileQuery};
+pub use audit::AuditQuery;
+pub use audience::Audience;
+pub use demande::DemandeResponse;
+pub use famille::{FosterContact, FosterPublic, FosterResponse};
+pub use nearby::Nearby;
+pub use ranked::{RankQuery, Ranked};
+pub use soin::{DossierSoins, Rappel, RappelQuery, TypeSoin};
+pub use stats::{PublicStats, ShelterStats};
+pub use utilisateur::UserResponse;
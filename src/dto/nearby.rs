@@ -0,0 +1,17 @@
+use serde::Serialize;
+
+/// Search hit annotated with its distance from the requested point.
+#[derive(Debug, Clone, Serialize)]
+pub struct Nearby<T> {
+    #[serde(flatten)]
+    pub item: T,
+    pub distance_km: f64,
+}
+
+impl<T> Nearby<T> {
+    /// Sorts hits from the closest to the farthest.
+    pub fn sorted(mut hits: Vec<Nearby<T>>) -> Vec<Nearby<T>> {
+        hits.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km));
+        hits
+    }
+}
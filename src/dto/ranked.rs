@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::MatchScore;
+
+/// Candidate annotated with its compatibility score.
+#[derive(Debug, Clone, Serialize)]
+pub struct Ranked<T> {
+    #[serde(flatten)]
+    pub item: T,
+    pub compatibilite: MatchScore,
+}
+
+impl<T> Ranked<T> {
+    /// Keeps compatible candidates only, best scores first.
+    pub fn best(candidates: Vec<Ranked<T>>, limit: usize) -> Vec<Ranked<T>> {
+        let mut ranked: Vec<Ranked<T>> = candidates
+            .into_iter()
+            .filter(|candidate| candidate.compatibilite.compatible)
+            .collect();
+
+        ranked.sort_by_key(|candidate| std::cmp::Reverse(candidate.compatibilite.score));
+        ranked.truncate(limit);
+        ranked
+    }
+}
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct RankQuery {
+    pub limit: Option<usize>,
+}
+
+impl RankQuery {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
@@ -0,0 +1,39 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::database::models::{FicheSoinsModel, TraitementModel, VaccinationModel, VisiteVeterinaireModelEx};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DossierSoins {
+    pub animal_id: i32,
+    pub fiche: Option<FicheSoinsModel>,
+    pub vaccinations: Vec<VaccinationModel>,
+    pub traitements: Vec<TraitementModel>,
+    pub visites: Vec<VisiteVeterinaireModelEx>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeSoin {
+    Vaccination,
+    Traitement,
+    Visite,
+}
+
+/// Upcoming or overdue care item.
+#[derive(Debug, Clone, Serialize)]
+pub struct Rappel {
+    pub animal_id: i32,
+    pub animal_nom: String,
+    pub type_soin: TypeSoin,
+    pub soin_id: i32,
+    pub libelle: String,
+    pub echeance: NaiveDate,
+    pub en_retard: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RappelQuery {
+    /// Days ahead to look at, 30 when missing.
+    pub jours: Option<u32>,
+}
@@ -0,0 +1,54 @@
+use chrono::NaiveDate;
+use sea_orm::prelude::DateTime;
+use serde::Serialize;
+
+use crate::database::repositories::stats_repository::{
+    EspeceCount, MonthlyStats, PlacementDelays, RequestCounts, StatutCount, UnrequestedAnimal,
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShelterStats {
+    pub animaux_par_statut: Vec<StatutCount>,
+    pub animaux_par_espece: Vec<EspeceCount>,
+    pub demandes: RequestCounts,
+    pub delais: PlacementDelays,
+    pub periode_debut: NaiveDate,
+    pub periode_fin: NaiveDate,
+    pub par_mois: Vec<MonthlyStats>,
+    pub sans_demande_jours: i32,
+    pub sans_demande: Vec<UnrequestedAnimal>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PublicTotals {
+    pub refuges: i64,
+    pub familles_actives: i64,
+    pub animaux_en_accueil: i64,
+    pub adoptions_annee: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicSpeciesStats {
+    pub espece_id: i32,
+    pub espece: String,
+    pub animaux_en_accueil: i64,
+    pub adoptions_annee: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PublicDepartmentStats {
+    pub departement: String,
+    pub refuges: i64,
+    pub familles_actives: i64,
+    pub animaux_en_accueil: i64,
+    pub adoptions_annee: i64,
+}
+
+/// Platform-wide figures shown on the public impact page.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicStats {
+    pub genere_le: DateTime,
+    pub totaux: PublicTotals,
+    pub par_espece: Vec<PublicSpeciesStats>,
+    pub par_departement: Vec<PublicDepartmentStats>,
+}
@@ -0,0 +1,28 @@
+use serde::Serialize;
+
+use crate::auth::Role;
+use crate::database::models::{AssociationModel, FamilleModel, UtilisateurModelEx};
+
+/// Account as seen by its owner. Never carries the password hash.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserResponse {
+    pub id: i32,
+    pub email: String,
+    pub role: Role,
+    pub refuge: Option<AssociationModel>,
+    pub accueillant: Option<FamilleModel>,
+}
+
+impl From<UtilisateurModelEx> for UserResponse {
+    fn from(user: UtilisateurModelEx) -> Self {
+        let role = Role::from_user(&user);
+
+        UserResponse {
+            id: user.id,
+            email: user.email,
+            role,
+            refuge: user.refuge.into_option().map(Into::into),
+            accueillant: user.accueillant.into_option().map(Into::into),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::auth::CustomError;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+const KM_PER_DEGREE: f64 = 111.32;
+const DEFAULT_RADIUS_KM: f64 = 50.0;
+/// Shelters and fosters are located at the centroid of their department (see `PostalCentroidGeocoder`),
+/// tens of kilometres from their actual address, so smaller distances cannot be told apart.
+pub const MIN_RADIUS_KM: f64 = 50.0;
+const MAX_RADIUS_KM: f64 = 500.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    pub fn from_columns(latitude: Option<f64>, longitude: Option<f64>) -> Option<Self> {
+        Some(Coordinates {
+            latitude: latitude?,
+            longitude: longitude?,
+        })
+    }
+
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let d_lat = (other.latitude - self.latitude).to_radians();
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2)
+            + self.latitude.to_radians().cos() * other.latitude.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+/// Rectangle enclosing a search circle, used to pre-filter rows in SQL.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingBox {
+    pub min_latitude: f64,
+    pub max_latitude: f64,
+    pub min_longitude: f64,
+    pub max_longitude: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SearchArea {
+    pub center: Coordinates,
+    pub radius_km: f64,
+}
+
+impl SearchArea {
+    pub fn bounding_box(&self) -> BoundingBox {
+        let lat_delta = self.radius_km / KM_PER_DEGREE;
+        let lon_delta = self.radius_km / (KM_PER_DEGREE * self.center.latitude.to_radians().cos().max(0.01));
+
+        BoundingBox {
+            min_latitude: self.center.latitude - lat_delta,
+            max_latitude: self.center.latitude + lat_delta,
+            min_longitude: self.center.longitude - lon_delta,
+            max_longitude: self.center.longitude + lon_delta,
+        }
+    }
+
+    /// Distance to `point` when it lies within the radius.
+    pub fn distance_to(&self, point: Option<Coordinates>) -> Option<f64> {
+        let distance = self.center.distance_km(&point?);
+        (distance <= self.radius_km).then_some(distance)
+    }
+}
+
+/// `?near=lat,lon&radius_km=` query parameters. Radii below `MIN_RADIUS_KM` are rejected.
+#[derive(Debug, Deserialize)]
+pub struct NearQuery {
+    pub near: Option<String>,
+    pub radius_km: Option<f64>,
+}
+
+impl NearQuery {
+    pub fn search_area(&self) -> Result<Option<SearchArea>, CustomError> {
+        let Some(near) = &self.near else {
+            return Ok(None);
+        };
+
+        let (latitude, longitude) = near.split_once(',').ok_or(CustomError::BadClientData)?;
+        let latitude: f64 = latitude.trim().parse().map_err(|_e| CustomError::BadClientData)?;
+        let longitude: f64 = longitude.trim().parse().map_err(|_e| CustomError::BadClientData)?;
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(CustomError::BadClientData);
+        }
+
+        let radius_km = self.radius_km.unwrap_or(DEFAULT_RADIUS_KM);
+        if !(MIN_RADIUS_KM..=MAX_RADIUS_KM).contains(&radius_km) {
+            return Err(CustomError::ValidationError {
+                error_messages: format!(
+                    "radius_km: Distance must be between {} and {} km",
+                    MIN_RADIUS_KM, MAX_RADIUS_KM
+                ),
+            });
+        }
+
+        Ok(Some(SearchArea {
+            center: Coordinates { latitude, longitude },
+            radius_km,
+        }))
+    }
+}
@@ -0,0 +1,73 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+use crate::geo::Coordinates;
+
+const CENTROIDS: &str = include_str!("departements.csv");
+
+pub static GEOCODER: Lazy<PostalCentroidGeocoder> = Lazy::new(PostalCentroidGeocoder::bundled);
+
+/// Resolves a postal address to coordinates.
+pub trait Geocoder: Send + Sync {
+    fn geocode(&self, code_postal: &str, commune: &str, pays: &str) -> Option<Coordinates>;
+}
+
+/// Offline geocoder backed by a table of French centroids.
+/// Rows are keyed either by full postal code or by department code, the former taking precedence.
+/// The bundled table only lists departments, hence the `MIN_RADIUS_KM` of distance searches.
+pub struct PostalCentroidGeocoder {
+    centroids: HashMap<String, Coordinates>,
+}
+
+fn is_france(pays: &str) -> bool {
+    matches!(pays.trim().to_lowercase().as_str(), "france" | "fr" | "fra")
+}
+
+/// Department code of a French postal code, including Corsica (2A/2B) and overseas departments.
+pub fn department_code(code_postal: &str) -> Option<String> {
+    let code = code_postal.trim();
+    if code.len() != 5 || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    match &code[..2] {
+        "20" => Some(if code.as_bytes()[2] < b'2' { "2A" } else { "2B" }.to_string()),
+        "97" | "98" => Some(code[..3].to_string()),
+        prefix => Some(prefix.to_string()),
+    }
+}
+
+impl PostalCentroidGeocoder {
+    pub fn bundled() -> Self {
+        Self::from_csv(CENTROIDS)
+    }
+
+    pub fn from_csv(csv: &str) -> Self {
+        let centroids = csv
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let mut columns = line.split(',');
+                let code = columns.next()?.trim().to_string();
+                let latitude = columns.next()?.trim().parse().ok()?;
+                let longitude = columns.next()?.trim().parse().ok()?;
+                Some((code, Coordinates { latitude, longitude }))
+            })
+            .collect();
+
+        PostalCentroidGeocoder { centroids }
+    }
+}
+
+impl Geocoder for PostalCentroidGeocoder {
+    fn geocode(&self, code_postal: &str, _commune: &str, pays: &str) -> Option<Coordinates> {
+        if !is_france(pays) {
+            return None;
+        }
+
+        self.centroids
+            .get(code_postal.trim())
+            .or_else(|| department_code(code_postal).and_then(|code| self.centroids.get(&code)))
+            .copied()
+    }
+}
@@ -0,0 +1,5 @@
+pub mod distance;
+pub mod geocoder;
+
+pub use distance::{BoundingBox, Coordinates, MIN_RADIUS_KM, NearQuery, SearchArea};
+pub use geocoder::{GEOCODER, Geocoder, PostalCentroidGeocoder, department_code};
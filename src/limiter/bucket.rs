@@ -0,0 +1,80 @@
+use crate::config::{BucketLimits, LockoutConfig};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BucketState {
+    pub tokens: f64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LockoutState {
+    pub failures: u32,
+    pub locked_until: Option<i64>,
+    pub updated_at: i64,
+}
+
+/// Refills the bucket up to `now` and tries to take one token.
+/// Returns the new state and, when the request is rejected, the number of seconds to wait.
+pub fn consume(limits: &BucketLimits, state: Option<BucketState>, now: i64) -> (BucketState, Option<u64>) {
+    let capacity = limits.burst as f64;
+    let refill_per_ms = limits.per_minute as f64 / 60_000.0;
+
+    let mut state = state.unwrap_or(BucketState { tokens: capacity, updated_at: now });
+    let elapsed = (now - state.updated_at).max(0) as f64;
+    state.tokens = (state.tokens + elapsed * refill_per_ms).min(capacity);
+    state.updated_at = now;
+
+    if state.tokens >= 1.0 {
+        state.tokens -= 1.0;
+        return (state, None);
+    }
+
+    let retry_after = ((1.0 - state.tokens) / refill_per_ms / 1000.0).ceil() as u64;
+
+    (state, Some(retry_after.max(1)))
+}
+
+/// Milliseconds an untouched bucket needs to refill completely.
+/// Past that delay a stored bucket is equivalent to a missing one and can be dropped.
+pub fn refill_duration(limits: &BucketLimits) -> i64 {
+    (limits.burst as u64 * 60_000).div_ceil(limits.per_minute.max(1) as u64) as i64
+}
+
+/// Whether a bucket last touched at `updated_at` can be forgotten.
+pub fn is_bucket_stale(state: &BucketState, ttl: i64, now: i64) -> bool {
+    now - state.updated_at >= ttl
+}
+
+/// Whether a lockout entry is neither active nor recent enough to escalate the next one.
+pub fn is_lockout_stale(config: &LockoutConfig, state: &LockoutState, now: i64) -> bool {
+    let ttl = (config.max_seconds as i64).saturating_mul(1000);
+    let last_seen = state.locked_until.unwrap_or(state.updated_at).max(state.updated_at);
+
+    now - last_seen >= ttl
+}
+
+/// Seconds left before a locked account can try again, if any.
+pub fn remaining_lockout(state: &LockoutState, now: i64) -> Option<u64> {
+    match state.locked_until {
+        Some(until) if until > now => Some(((until - now) as f64 / 1000.0).ceil() as u64),
+        _ => None,
+    }
+}
+
+/// Counts one more failed attempt and, past the threshold, locks the account
+/// for a delay that doubles with every further failure.
+pub fn register_failure(config: &LockoutConfig, mut state: LockoutState, now: i64) -> LockoutState {
+    state.failures = state.failures.saturating_add(1);
+    state.updated_at = now;
+
+    if config.threshold > 0 && state.failures >= config.threshold {
+        let exponent = (state.failures - config.threshold).min(32);
+        let delay = config
+            .base_seconds
+            .saturating_mul(1u64 << exponent)
+            .min(config.max_seconds);
+        state.locked_until = Some(now + (delay as i64) * 1000);
+    }
+
+    state
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::config::{BucketLimits, LockoutConfig};
+use crate::limiter::bucket::{self, BucketState, LockoutState};
+
+const MAX_ENTRIES: usize = 10_000;
+
+pub struct MemoryStore {
+    bucket_ttl: i64,
+    buckets: Mutex<HashMap<String, BucketState>>,
+    lockouts: Mutex<HashMap<String, LockoutState>>,
+}
+
+impl MemoryStore {
+    /// `bucket_ttl` is the longest time any configured bucket needs to refill.
+    pub fn new(bucket_ttl: i64) -> Self {
+        Self {
+            bucket_ttl,
+            buckets: Mutex::new(HashMap::new()),
+            lockouts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn consume(&self, key: &str, limits: &BucketLimits, now: i64) -> Option<u64> {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() > MAX_ENTRIES {
+            buckets.retain(|_, state| !bucket::is_bucket_stale(state, self.bucket_ttl, now));
+        }
+
+        let (state, retry_after) = bucket::consume(limits, buckets.get(key).copied(), now);
+        buckets.insert(key.to_string(), state);
+
+        retry_after
+    }
+
+    pub fn remaining_lockout(&self, key: &str, now: i64) -> Option<u64> {
+        let lockouts = self.lockouts.lock().unwrap();
+
+        lockouts
+            .get(key)
+            .and_then(|state| bucket::remaining_lockout(state, now))
+    }
+
+    pub fn register_failure(&self, key: &str, config: &LockoutConfig, now: i64) {
+        let mut lockouts = self.lockouts.lock().unwrap();
+
+        if lockouts.len() > MAX_ENTRIES {
+            lockouts.retain(|_, state| !bucket::is_lockout_stale(config, state, now));
+        }
+
+        let state = lockouts.get(key).copied().unwrap_or_default();
+        lockouts.insert(key.to_string(), bucket::register_failure(config, state, now));
+    }
+
+    pub fn clear_failures(&self, key: &str) {
+        self.lockouts.lock().unwrap().remove(key);
+    }
+
+    /// Drops the buckets that have refilled and the lockouts that expired.
+    pub fn sweep(&self, config: &LockoutConfig, now: i64) -> u64 {
+        let mut buckets = self.buckets.lock().unwrap();
+        let before = buckets.len();
+        buckets.retain(|_, state| !bucket::is_bucket_stale(state, self.bucket_ttl, now));
+        let mut removed = before - buckets.len();
+        drop(buckets);
+
+        let mut lockouts = self.lockouts.lock().unwrap();
+        let before = lockouts.len();
+        lockouts.retain(|_, state| !bucket::is_lockout_stale(config, state, now));
+        removed += before - lockouts.len();
+
+        removed as u64
+    }
+}
@@ -0,0 +1,6 @@
+pub mod bucket;
+pub mod memory_store;
+pub mod postgres_store;
+pub mod rate_limiter;
+
+pub use rate_limiter::{RateLimitScope, RateLimiter};
@@ -0,0 +1,127 @@
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, Statement, TransactionTrait};
+
+use crate::config::{BucketLimits, LockoutConfig};
+use crate::limiter::bucket::{self, BucketState, LockoutState};
+
+/// Limiter state shared between several API instances.
+/// Tables are created by `migrations/001_rate_limit.sql`.
+pub struct PostgresStore {
+    db: DatabaseConnection,
+}
+
+impl PostgresStore {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn consume(&self, key: &str, limits: &BucketLimits, now: i64) -> Result<Option<u64>, DbErr> {
+        let txn = self.db.begin().await?;
+
+        let current = txn
+            .query_one_raw(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT tokens, updated_at FROM rate_limit_bucket WHERE key = $1 FOR UPDATE",
+                [key.into()],
+            ))
+            .await?
+            .map(|row| -> Result<BucketState, DbErr> {
+                Ok(BucketState {
+                    tokens: row.try_get("", "tokens")?,
+                    updated_at: row.try_get("", "updated_at")?,
+                })
+            })
+            .transpose()?;
+
+        let (state, retry_after) = bucket::consume(limits, current, now);
+
+        txn.execute_raw(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "INSERT INTO rate_limit_bucket (key, tokens, updated_at) VALUES ($1, $2, $3) \
+             ON CONFLICT (key) DO UPDATE SET tokens = EXCLUDED.tokens, updated_at = EXCLUDED.updated_at",
+            [key.into(), state.tokens.into(), state.updated_at.into()],
+        ))
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(retry_after)
+    }
+
+    pub async fn remaining_lockout(&self, key: &str, now: i64) -> Result<Option<u64>, DbErr> {
+        let state = self.find_lockout(&self.db, key).await?;
+
+        Ok(state.and_then(|state| bucket::remaining_lockout(&state, now)))
+    }
+
+    pub async fn register_failure(&self, key: &str, config: &LockoutConfig, now: i64) -> Result<(), DbErr> {
+        let txn = self.db.begin().await?;
+
+        let state = self.find_lockout(&txn, key).await?.unwrap_or_default();
+        let state = bucket::register_failure(config, state, now);
+
+        txn.execute_raw(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "INSERT INTO login_lockout (key, failures, locked_until, updated_at) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (key) DO UPDATE SET failures = EXCLUDED.failures, locked_until = EXCLUDED.locked_until, \
+             updated_at = EXCLUDED.updated_at",
+            [key.into(), (state.failures as i32).into(), state.locked_until.into(), state.updated_at.into()],
+        ))
+        .await?;
+
+        txn.commit().await
+    }
+
+    pub async fn clear_failures(&self, key: &str) -> Result<(), DbErr> {
+        self.db
+            .execute_raw(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "DELETE FROM login_lockout WHERE key = $1",
+                [key.into()],
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Drops the buckets idle for longer than `bucket_ttl` and the expired lockouts.
+    pub async fn sweep(&self, bucket_ttl: i64, config: &LockoutConfig, now: i64) -> Result<u64, DbErr> {
+        let buckets = self
+            .db
+            .execute_raw(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "DELETE FROM rate_limit_bucket WHERE updated_at <= $1",
+                [(now - bucket_ttl).into()],
+            ))
+            .await?;
+
+        let lockout_ttl = (config.max_seconds as i64).saturating_mul(1000);
+        let lockouts = self
+            .db
+            .execute_raw(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "DELETE FROM login_lockout WHERE GREATEST(COALESCE(locked_until, updated_at), updated_at) <= $1",
+                [(now - lockout_ttl).into()],
+            ))
+            .await?;
+
+        Ok(buckets.rows_affected() + lockouts.rows_affected())
+    }
+
+    async fn find_lockout<C: ConnectionTrait>(&self, conn: &C, key: &str) -> Result<Option<LockoutState>, DbErr> {
+        conn.query_one_raw(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT failures, locked_until, updated_at FROM login_lockout WHERE key = $1 FOR UPDATE",
+            [key.into()],
+        ))
+        .await?
+        .map(|row| -> Result<LockoutState, DbErr> {
+            let failures: i32 = row.try_get("", "failures")?;
+            Ok(LockoutState {
+                failures: failures.max(0) as u32,
+                locked_until: row.try_get("", "locked_until")?,
+                updated_at: row.try_get("", "updated_at")?,
+            })
+        })
+        .transpose()
+    }
+}
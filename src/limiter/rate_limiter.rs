@@ -0,0 +1,215 @@
+use actix_web::{HttpRequest, rt};
+use chrono::Utc;
+use sea_orm::{DatabaseConnection, DbErr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::auth::CustomError;
+use crate::config::{BucketLimits, LockoutConfig, RateLimitBackend, RateLimitConfig, ScopeLimits};
+use crate::limiter::bucket;
+use crate::limiter::memory_store::MemoryStore;
+use crate::limiter::postgres_store::PostgresStore;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitScope {
+    Login,
+    Register,
+}
+
+impl RateLimitScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitScope::Login => "login",
+            RateLimitScope::Register => "register",
+        }
+    }
+}
+
+pub enum LimiterStore {
+    Memory(MemoryStore),
+    Postgres(PostgresStore),
+}
+
+impl LimiterStore {
+    async fn consume(&self, key: &str, limits: &BucketLimits, now: i64) -> Result<Option<u64>, DbErr> {
+        match self {
+            LimiterStore::Memory(store) => Ok(store.consume(key, limits, now)),
+            LimiterStore::Postgres(store) => store.consume(key, limits, now).await,
+        }
+    }
+
+    async fn sweep(&self, bucket_ttl: i64, config: &LockoutConfig, now: i64) -> Result<u64, DbErr> {
+        match self {
+            LimiterStore::Memory(store) => Ok(store.sweep(config, now)),
+            LimiterStore::Postgres(store) => store.sweep(bucket_ttl, config, now).await,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: Arc<LimiterStore>,
+    config: Arc<RateLimitConfig>,
+    bucket_ttl: i64,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, db: DatabaseConnection) -> Self {
+        let bucket_ttl = [config.login.ip, config.login.account, config.register.ip, config.register.account]
+            .iter()
+            .map(bucket::refill_duration)
+            .max()
+            .unwrap_or_default();
+
+        let store = match config.backend {
+            RateLimitBackend::Memory => LimiterStore::Memory(MemoryStore::new(bucket_ttl)),
+            RateLimitBackend::Postgres => LimiterStore::Postgres(PostgresStore::new(db)),
+        };
+
+        Self {
+            store: Arc::new(store),
+            config: Arc::new(config),
+            bucket_ttl,
+        }
+    }
+
+    pub fn trust_proxy(&self) -> bool {
+        self.config.trust_proxy
+    }
+
+    /// Address the limits apply to, taken from the proxy headers only when they are trusted.
+    pub fn client_ip(&self, req: &HttpRequest) -> String {
+        if self.trust_proxy() {
+            req.connection_info().realip_remote_addr().map(|ip| ip.to_string())
+        } else {
+            req.peer_addr().map(|addr| addr.ip().to_string())
+        }
+        .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// Periodically forgets the refilled buckets and expired lockouts so the store stays bounded.
+    pub fn spawn_sweep_job(&self) {
+        let limiter = self.clone();
+
+        rt::spawn(async move {
+            let mut interval = rt::time::interval(SWEEP_INTERVAL);
+
+            loop {
+                interval.tick().await;
+
+                match limiter
+                    .store
+                    .sweep(limiter.bucket_ttl, &limiter.config.lockout, Utc::now().timestamp_millis())
+                    .await
+                {
+                    Ok(count) => log::debug!("Rate limiter sweep removed {} entries", count),
+                    Err(e) => log::error!("Rate limiter sweep failed: {}", e),
+                }
+            }
+        });
+    }
+
+    fn limits(&self, scope: RateLimitScope) -> &ScopeLimits {
+        match scope {
+            RateLimitScope::Login => &self.config.login,
+            RateLimitScope::Register => &self.config.register,
+        }
+    }
+
+    async fn check(&self, key: String, limits: &BucketLimits) -> Result<(), CustomError> {
+        let retry_after = self
+            .store
+            .consume(&key, limits, Utc::now().timestamp_millis())
+            .await
+            .map_err(|_e| {log::error!("Rate limiter store error: {}", _e); CustomError::InternalError})?;
+
+        match retry_after {
+            Some(retry_after) => {
+                log::warn!("Rate limit exceeded for {}", key);
+                Err(CustomError::TooManyRequests { retry_after })
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub async fn check_ip(&self, scope: RateLimitScope, ip: &str) -> Result<(), CustomError> {
+        let key = format!("{}:ip:{}", scope.as_str(), ip);
+        self.check(key, &self.limits(scope).ip).await
+    }
+
+    pub async fn check_account(&self, email: &str) -> Result<(), CustomError> {
+        let scope = RateLimitScope::Login;
+        let key = format!("{}:account:{}", scope.as_str(), normalize_email(email));
+        self.check(key, &self.limits(scope).account).await
+    }
+
+    /// Counts one account creation for the client. Keyed on the address rather than the
+    /// submitted email, which would let anyone block the sign-up of someone else's address.
+    pub async fn check_registration(&self, ip: &str) -> Result<(), CustomError> {
+        let scope = RateLimitScope::Register;
+        let key = format!("{}:account:{}", scope.as_str(), ip);
+        self.check(key, &self.limits(scope).account).await
+    }
+
+    pub async fn check_lockout(&self, email: &str) -> Result<(), CustomError> {
+        let key = lockout_key(email);
+        let now = Utc::now().timestamp_millis();
+
+        let remaining = match self.store.as_ref() {
+            LimiterStore::Memory(store) => Ok(store.remaining_lockout(&key, now)),
+            LimiterStore::Postgres(store) => store.remaining_lockout(&key, now).await,
+        }
+        .map_err(|_e| {log::error!("Rate limiter store error: {}", _e); CustomError::InternalError})?;
+
+        match remaining {
+            Some(retry_after) => Err(CustomError::TooManyRequests { retry_after }),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn record_login_failure(&self, email: &str) {
+        let key = lockout_key(email);
+        let now = Utc::now().timestamp_millis();
+
+        let result = match self.store.as_ref() {
+            LimiterStore::Memory(store) => {
+                store.register_failure(&key, &self.config.lockout, now);
+                Ok(())
+            }
+            LimiterStore::Postgres(store) => store.register_failure(&key, &self.config.lockout, now).await,
+        };
+
+        if let Err(e) = result {
+            log::error!("Could not record failed login: {}", e);
+        }
+    }
+
+    pub async fn record_login_success(&self, email: &str) {
+        let key = lockout_key(email);
+
+        let result = match self.store.as_ref() {
+            LimiterStore::Memory(store) => {
+                store.clear_failures(&key);
+                Ok(())
+            }
+            LimiterStore::Postgres(store) => store.clear_failures(&key).await,
+        };
+
+        if let Err(e) = result {
+            log::error!("Could not reset failed logins: {}", e);
+        }
+    }
+}
+
+fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Failures are counted per email whatever the address, so that rotating addresses does not
+/// escape the backoff. The trade-off is that anyone can lock the owner out for up to
+/// `LOGIN_LOCKOUT_MAX_SECONDS` by failing logins on their email; a successful login resets the count.
+fn lockout_key(email: &str) -> String {
+    format!("lockout:{}", normalize_email(email))
+}
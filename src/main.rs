@@ -1,16 +1,27 @@
 pub mod api;
+pub mod audit;
 pub mod auth;
 pub mod config;
 pub mod database;
+pub mod dto;
+pub mod geo;
+pub mod limiter;
 pub mod middleware;
+pub mod registry;
+pub mod services;
 pub mod validators;
 
 use actix_cors::Cors;
-use actix_web::{App, HttpServer, middleware::Logger, web};
+use actix_web::{App, HttpServer, http::header, middleware::Logger, web};
 use dotenv::dotenv;
 use sea_orm::{Database, DbConn};
 
+use crate::auth::JWT_KEYS;
 use crate::config::AppConfig;
+use crate::limiter::RateLimiter;
+use crate::middleware::AuditTrail;
+use crate::registry::company_registry;
+use crate::services::{PublicStatsCache, spawn_alert_digest_job, spawn_geocoding_backfill, spawn_purge_job, spawn_retention_job, spawn_verification_backfill};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -18,7 +29,12 @@ async fn main() -> std::io::Result<()> {
 
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
-    let app_config = AppConfig::from_env();
+    let app_config = AppConfig::from_env().unwrap_or_else(|e| {
+        log::error!("Invalid configuration: {}", e);
+        std::process::exit(1)
+    });
+
+    once_cell::sync::Lazy::force(&JWT_KEYS);
 
     log::info!(
         "Starting server at {}:{}",
@@ -30,6 +46,18 @@ async fn main() -> std::io::Result<()> {
         .await
         .expect("Error connecting to the database");
 
+    let limiter = RateLimiter::new(app_config.rate_limit.clone(), db.clone());
+    limiter.spawn_sweep_job();
+    let registry = company_registry(&app_config.company_registry);
+
+    spawn_retention_job(db.clone(), app_config.retention);
+    spawn_purge_job(db.clone(), app_config.archive);
+    spawn_geocoding_backfill(db.clone());
+    spawn_alert_digest_job(db.clone());
+    spawn_verification_backfill(db.clone(), registry.clone());
+
+    let public_stats = web::Data::new(PublicStatsCache::new(app_config.public_stats));
+
     HttpServer::new(move || {
         let cors = Cors::default()
             .allowed_origin("http://localhost:3000")
@@ -39,12 +67,16 @@ async fn main() -> std::io::Result<()> {
             .allowed_origin("http://localhost:4200")
             .allow_any_method()
             .allow_any_header()
+            .expose_headers([header::ETAG])
             .max_age(3600);
         App::new()
             .wrap(cors)
             .app_data(web::Data::new(db.clone()))
+            .app_data(public_stats.clone())
+            .app_data(web::Data::from(registry.clone()))
             .service(actix_files::Files::new("/images", "./static/images").show_files_listing())
-            .configure(|config| api::configure_routes(config, db.clone()))
+            .configure(|config| api::configure_routes(config, db.clone(), limiter.clone()))
+            .wrap(AuditTrail::new(limiter.trust_proxy()))
             .wrap(Logger::default())
     }).bind(format!(
         "{}:{}",
@@ -0,0 +1,83 @@
+use actix_service::{Service, Transform};
+use actix_web::Error;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::USER_AGENT;
+use futures::future::{Ready, ready};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use crate::audit::{self, RequestMetadata};
+
+/// Exposes the request metadata to the audit entries written while serving it.
+pub struct AuditTrail {
+    pub trust_proxy: bool,
+}
+
+impl AuditTrail {
+    pub fn new(trust_proxy: bool) -> Self {
+        Self { trust_proxy }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AuditTrail
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AuditTrailMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuditTrailMiddleware {
+            service: Arc::new(service),
+            trust_proxy: self.trust_proxy,
+        }))
+    }
+}
+
+pub struct AuditTrailMiddleware<S> {
+    service: Arc<S>,
+    trust_proxy: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditTrailMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        let ip = if self.trust_proxy {
+            req.connection_info().realip_remote_addr().map(|ip| ip.to_string())
+        } else {
+            req.peer_addr().map(|addr| addr.ip().to_string())
+        };
+
+        let metadata = RequestMetadata {
+            ip,
+            user_agent: req
+                .headers()
+                .get(USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string()),
+            methode: req.method().to_string(),
+            chemin: req.path().to_string(),
+        };
+
+        Box::pin(audit::scope(metadata, async move { service.call(req).await }))
+    }
+}
@@ -3,14 +3,17 @@ use actix_web::dev::{ServiceRequest, ServiceResponse};
 use actix_web::error::ErrorUnauthorized;
 use actix_web::{Error, HttpMessage};
 use futures::future::{Ready, ready};
-use jsonwebtoken::{DecodingKey, Validation, decode};
 use sea_orm::DbConn;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use crate::auth::jwt::{Claims, JWT_SECRET, decode_jwt};
+use crate::audit::{self, Actor};
+use crate::auth::{AuthenticatedUser, CustomError, Role};
+use crate::auth::jwt::decode_jwt;
+use crate::database::models::sea_orm_active_enums::StatutInscription;
+use crate::database::repositories::UtilisateurRepository;
 
 pub struct AuthMiddleware {
     pub db: Arc<DbConn>,
@@ -36,12 +39,14 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(AuthMiddlewareService {
             service: Arc::new(service),
+            db: self.db.clone(),
         }))
     }
 }
 
 pub struct AuthMiddlewareService<S> {
     service: Arc<S>,
+    db: Arc<DbConn>,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthMiddlewareService<S>
@@ -84,54 +89,55 @@ where
 
         let token = auth_str.trim_start_matches("Bearer ").trim().to_string();
         let service = self.service.clone();
+        let db = self.db.clone();
 
         Box::pin(async move {
-            match decode_jwt(&token) {
-                Ok(token_data) => {
-                    let user_id = extract_user_id_from_token(&token).unwrap();
-                    let claims = extract_claims_from_token(&token).unwrap();
-                    req.extensions_mut().insert(claims);
-                    req.extensions_mut().insert(user_id);
-                    req.extensions_mut().insert(token_data);
-                    service.call(req).await
-                }
+            let claims = match decode_jwt(&token) {
+                Ok(token_data) => token_data.claims,
                 Err(err) => {
                     log::debug!("Token validation failed: {:?}", err);
-                    Err(ErrorUnauthorized("Invalid token format. Please log in again.",))
+                    return Err(ErrorUnauthorized("Invalid token format. Please log in again."));
+                }
+            };
+
+            let user = UtilisateurRepository::new(db.as_ref())
+                .find_by_id(claims.user_id)
+                .await
+                .map_err(|_e| CustomError::InternalError)?;
+
+            let user = match user {
+                Some(user) if !user.is_archived() => user,
+                _ => {
+                    log::debug!("Token refers to unknown or archived user {}", claims.user_id);
+                    return Err(ErrorUnauthorized("Unknown user. Please log in again."));
                 }
+            };
+
+            if claims.version_jeton != user.version_jeton {
+                log::debug!("Token of user {} was revoked", claims.user_id);
+                return Err(ErrorUnauthorized("Session revoked. Please log in again."));
             }
-        })
-    }
-}
 
-fn extract_user_id_from_token(token: &str) -> Option<i32> {
-    let validation = Validation::default();
-
-    match decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
-        &validation,
-    ) {
-        Ok(token_data) => Some(token_data.claims.user_id),
-        Err(e) => {
-            log::error!("Failed to decode token: {:?}", e);
-            None
-        }
-    }
-}
+            let authenticated_user = AuthenticatedUser {
+                user_id: user.id,
+                email: user.email.clone(),
+                role: Role::from_user(&user),
+                association_id: user.refuge.as_ref().map(|shelter| shelter.id),
+                association_approuvee: user
+                    .refuge
+                    .as_ref()
+                    .is_some_and(|shelter| shelter.inscription == StatutInscription::Approuvée),
+                famille_id: user.accueillant.as_ref().map(|foster| foster.id),
+            };
+
+            audit::set_actor(Actor {
+                user_id: authenticated_user.user_id,
+                role: authenticated_user.role,
+            });
 
-fn extract_claims_from_token(token: &str) -> Option<Claims> {
-    let validation = Validation::default();
-
-    match decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
-        &validation,
-    ) {
-        Ok(token_data) => Some(token_data.claims),
-        Err(e) => {
-            log::error!("Failed to decode token: {:?}", e);
-            None
-        }
+            req.extensions_mut().insert(claims);
+            req.extensions_mut().insert(authenticated_user);
+            service.call(req).await
+        })
     }
-}
\ No newline at end of file
+}
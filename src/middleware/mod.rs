@@ -1,5 +1,9 @@
+mod audit_middleware;
 mod auth_middleware;
+mod rate_limit_middleware;
 mod role_middleware;
 
+pub use audit_middleware::AuditTrail;
 pub use auth_middleware::AuthMiddleware;
-pub use role_middleware::RoleGuard;
\ No newline at end of file
+pub use rate_limit_middleware::RateLimit;
+pub use role_middleware::RoleGuard;
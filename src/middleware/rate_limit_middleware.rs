@@ -0,0 +1,82 @@
+use actix_service::{Service, Transform};
+use actix_web::Error;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use futures::future::{Ready, ready};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use crate::limiter::{RateLimitScope, RateLimiter};
+
+pub struct RateLimit {
+    pub limiter: RateLimiter,
+    pub scope: RateLimitScope,
+}
+
+impl RateLimit {
+    pub fn new(limiter: RateLimiter, scope: RateLimitScope) -> Self {
+        Self { limiter, scope }
+    }
+
+    pub fn login(limiter: RateLimiter) -> Self {
+        Self::new(limiter, RateLimitScope::Login)
+    }
+
+    pub fn register(limiter: RateLimiter) -> Self {
+        Self::new(limiter, RateLimitScope::Register)
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service: Arc::new(service),
+            limiter: self.limiter.clone(),
+            scope: self.scope,
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Arc<S>,
+    limiter: RateLimiter,
+    scope: RateLimitScope,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let limiter = self.limiter.clone();
+        let scope = self.scope;
+
+        let ip = limiter.client_ip(req.request());
+
+        Box::pin(async move {
+            limiter.check_ip(scope, &ip).await?;
+            service.call(req).await
+        })
+    }
+}
@@ -1,6 +1,6 @@
 use actix_service::{Service, Transform};
 use actix_web::dev::{ServiceRequest, ServiceResponse};
-use actix_web::error::ErrorUnauthorized;
+use actix_web::error::{ErrorForbidden, ErrorUnauthorized};
 use actix_web::{Error, HttpMessage};
 use futures::future::{Ready, ready};
 use std::future::Future;
@@ -8,27 +8,48 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use crate::auth::jwt::Claims;
+use crate::auth::{AuthenticatedUser, CustomError, Role};
 
+/// Restricts a scope to the given roles. Shelters whose registration is not
+/// approved yet can only read, unless the scope opts out with `allow_pending`.
 pub struct RoleGuard {
-    pub role: String,
+    pub roles: Vec<Role>,
+    pub allow_pending: bool,
 }
 
 impl RoleGuard {
-    pub fn new(role: String) -> Self {
-        Self { role }
+    pub fn new(roles: Vec<Role>) -> Self {
+        Self { roles, allow_pending: false }
+    }
+
+    /// Lets pending shelters mutate too, for their profile, documents and account.
+    pub fn allow_pending(mut self) -> Self {
+        self.allow_pending = true;
+        self
+    }
+
+    pub fn any_of(roles: &[Role]) -> Self {
+        Self::new(roles.to_vec())
+    }
+
+    pub fn authenticated() -> Self {
+        Self::any_of(&Role::ALL)
     }
 
     pub fn foster() -> Self {
-        Self {
-            role: "foster".to_string(),
-        }
+        Self::any_of(&[Role::Foster])
     }
 
     pub fn shelter() -> Self {
-        Self {
-            role: "shelter".to_string(),
-        }
+        Self::any_of(&[Role::Shelter])
+    }
+
+    pub fn admin() -> Self {
+        Self::any_of(&[Role::Admin])
+    }
+
+    pub fn shelter_or_admin() -> Self {
+        Self::any_of(&[Role::Shelter, Role::Admin])
     }
 }
 
@@ -46,14 +67,16 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(RoleGuardMiddleware {
             service: Arc::new(service),
-            role: self.role.clone(),
+            roles: self.roles.clone(),
+            allow_pending: self.allow_pending,
         }))
     }
 }
 
 pub struct RoleGuardMiddleware<S> {
     service: Arc<S>,
-    role: String,
+    roles: Vec<Role>,
+    allow_pending: bool,
 }
 
 impl<S, B> Service<ServiceRequest> for RoleGuardMiddleware<S>
@@ -71,21 +94,27 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let service = self.service.clone();
-        let required_role = self.role.clone();
+        let allowed_roles = self.roles.clone();
+        let allow_pending = self.allow_pending;
 
         Box::pin(async move {
-            let has_permission = if let Some(claims) = req.extensions_mut().get::<Claims>() {
-                let user_role_str = claims.role.to_lowercase();
-                user_role_str == required_role.as_str()
+            let (has_permission, pending) = if let Some(user) = req.extensions().get::<AuthenticatedUser>() {
+                (
+                    allowed_roles.contains(&user.role),
+                    user.role == Role::Shelter && !user.association_approuvee,
+                )
             } else {
                 return Err(ErrorUnauthorized("User not authenticated"));
             };
 
-            if has_permission {
-                service.call(req).await
-            } else {
-                Err(ErrorUnauthorized(format!("Insufficient permissions.")))
+            if !has_permission {
+                return Err(ErrorForbidden("Insufficient permissions."));
+            }
+            if pending && !allow_pending && !req.method().is_safe() {
+                return Err(CustomError::PendingApproval.into());
             }
+
+            service.call(req).await
         })
     }
 }
\ No newline at end of file
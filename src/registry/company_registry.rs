@@ -0,0 +1,86 @@
+use chrono::Utc;
+use derive_more::{Display, Error};
+use futures::future::BoxFuture;
+use log::{info, warn};
+use sea_orm::prelude::DateTime;
+use std::sync::Arc;
+
+use crate::config::{CompanyRegistryBackend, CompanyRegistryConfig};
+use crate::database::models::sea_orm_active_enums::StatutVerification;
+use crate::registry::SireneRegistry;
+
+/// Establishment listed under a SIRET.
+pub struct Establishment {
+    pub denomination: Option<String>,
+    pub actif: bool,
+}
+
+#[derive(Debug, Display, Error)]
+pub enum RegistryError {
+    #[display("Company registry unreachable: {}", _0)]
+    Request(reqwest::Error),
+    #[display("Company registry answered with status {}", _0)]
+    Status(#[error(not(source))] u16),
+}
+
+/// Looks up establishments by SIRET.
+pub trait CompanyRegistry: Send + Sync {
+    /// `None` when no establishment has this SIRET.
+    fn find_establishment<'a>(&'a self, siret: &'a str) -> BoxFuture<'a, Result<Option<Establishment>, RegistryError>>;
+}
+
+/// Offline registry for development, every SIRET belongs to an active establishment.
+pub struct StubRegistry;
+
+impl CompanyRegistry for StubRegistry {
+    fn find_establishment<'a>(&'a self, _siret: &'a str) -> BoxFuture<'a, Result<Option<Establishment>, RegistryError>> {
+        Box::pin(async {
+            Ok(Some(Establishment {
+                denomination: None,
+                actif: true,
+            }))
+        })
+    }
+}
+
+pub fn company_registry(config: &CompanyRegistryConfig) -> Arc<dyn CompanyRegistry> {
+    match config.backend {
+        CompanyRegistryBackend::Sirene => Arc::new(SireneRegistry::new(config)),
+        CompanyRegistryBackend::Stub => {
+            warn!("Using the stub company registry, every SIRET is considered valid");
+            Arc::new(StubRegistry)
+        }
+    }
+}
+
+/// Status of a shelter registered under `siret`. It stays pending when the registry cannot be reached,
+/// so that the next check or an admin can settle it.
+pub async fn verification_status(registry: &dyn CompanyRegistry, siret: &str) -> StatutVerification {
+    match registry.find_establishment(siret).await {
+        Ok(Some(establishment)) if establishment.actif => {
+            info!(
+                "SIRET {} verified ({})",
+                siret,
+                establishment.denomination.as_deref().unwrap_or("unnamed establishment")
+            );
+            StatutVerification::Vérifiée
+        }
+        Ok(Some(_)) => {
+            info!("SIRET {} belongs to a closed establishment", siret);
+            StatutVerification::Refusée
+        }
+        Ok(None) => {
+            info!("SIRET {} is not listed in the company registry", siret);
+            StatutVerification::Refusée
+        }
+        Err(e) => {
+            warn!("Could not verify SIRET {}: {}", siret, e);
+            StatutVerification::EnAttente
+        }
+    }
+}
+
+/// Date stored along with `status`, only verified shelters have one.
+pub fn verified_at(status: &StatutVerification) -> Option<DateTime> {
+    (*status == StatutVerification::Vérifiée).then(|| Utc::now().naive_utc())
+}
@@ -0,0 +1,5 @@
+pub mod company_registry;
+pub mod sirene;
+
+pub use company_registry::{CompanyRegistry, Establishment, RegistryError, StubRegistry, company_registry, verification_status, verified_at};
+pub use sirene::SireneRegistry;
@@ -0,0 +1,97 @@
+use futures::future::BoxFuture;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::config::CompanyRegistryConfig;
+use crate::registry::{CompanyRegistry, Establishment, RegistryError};
+
+const API_KEY_HEADER: &str = "X-INSEE-Api-Key-Integration";
+const ACTIVE: &str = "A";
+
+/// INSEE Sirene API, see https://portail-api.insee.fr.
+pub struct SireneRegistry {
+    client: Client,
+    url: String,
+    api_key: String,
+}
+
+#[derive(Deserialize)]
+struct SiretResponse {
+    etablissement: Etablissement,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Etablissement {
+    unite_legale: UniteLegale,
+    /// Most recent period first.
+    periodes_etablissement: Vec<PeriodeEtablissement>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UniteLegale {
+    denomination_unite_legale: Option<String>,
+    etat_administratif_unite_legale: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PeriodeEtablissement {
+    etat_administratif_etablissement: Option<String>,
+}
+
+impl SireneRegistry {
+    pub fn new(config: &CompanyRegistryConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .expect("Could not build the Sirene HTTP client");
+
+        SireneRegistry {
+            client,
+            url: config.sirene_url.trim_end_matches('/').to_string(),
+            api_key: config.sirene_api_key.clone(),
+        }
+    }
+
+    async fn fetch(&self, siret: &str) -> Result<Option<Establishment>, RegistryError> {
+        let response = self
+            .client
+            .get(format!("{}/siret/{}", self.url, siret))
+            .header(API_KEY_HEADER, &self.api_key)
+            .send()
+            .await
+            .map_err(RegistryError::Request)?;
+
+        match response.status() {
+            StatusCode::OK => {}
+            StatusCode::NOT_FOUND => return Ok(None),
+            status => return Err(RegistryError::Status(status.as_u16())),
+        }
+
+        let etablissement = response
+            .json::<SiretResponse>()
+            .await
+            .map_err(RegistryError::Request)?
+            .etablissement;
+
+        let actif = etablissement
+            .periodes_etablissement
+            .first()
+            .is_some_and(|periode| periode.etat_administratif_etablissement.as_deref() == Some(ACTIVE))
+            && etablissement.unite_legale.etat_administratif_unite_legale.as_deref() == Some(ACTIVE);
+
+        Ok(Some(Establishment {
+            denomination: etablissement.unite_legale.denomination_unite_legale,
+            actif,
+        }))
+    }
+}
+
+impl CompanyRegistry for SireneRegistry {
+    fn find_establishment<'a>(&'a self, siret: &'a str) -> BoxFuture<'a, Result<Option<Establishment>, RegistryError>> {
+        Box::pin(self.fetch(siret))
+    }
+}
@@ -0,0 +1,247 @@
+use chrono::Utc;
+use log::{info, warn};
+use sea_orm::ActiveValue::Set;
+use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+use std::fs;
+use std::io::ErrorKind;
+use uuid::Uuid;
+
+use crate::auth::{CustomError, hash_password};
+use crate::database::models::{FamilleActiveModelEx, UtilisateurActiveModelEx};
+use crate::database::repositories::{AlerteRepository, AnimalRepository, AssociationRepository, FamilleRepository, MediaRepository, RechercheRepository, UtilisateurRepository};
+
+pub const ANONYMIZED_NAME: &str = "Anonyme";
+
+/// Single entry point for account removal, shared by the shelter, foster and user routes.
+pub struct AccountService<'a> {
+    db: &'a DatabaseConnection,
+}
+
+impl<'a> AccountService<'a> {
+    pub fn new(db: &'a DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Archives the profiles of the account, or deletes it when it has none, in a single transaction.
+    pub async fn delete_account(&self, user_id: i32) -> Result<(), CustomError> {
+        let txn = self.db.begin().await.map_err(|_e| CustomError::InternalError)?;
+        let user_repo = UtilisateurRepository::new(&txn);
+
+        let user = user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .ok_or(CustomError::NotFound)?;
+
+        if user.refuge.is_loaded() {
+            Self::delete_shelter_profile(&txn, user_id).await?;
+        }
+        if user.accueillant.is_loaded() {
+            Self::delete_foster_profile(&txn, user_id).await?;
+        }
+
+        // Archived profiles still reference the account until the purge job removes both.
+        if user.refuge.is_loaded() || user.accueillant.is_loaded() {
+            txn.commit().await.map_err(|_e| CustomError::DeletionError)?;
+            info!("User with ID {} archived", user_id);
+            return Ok(());
+        }
+
+        let delete_user = user_repo
+            .delete(user_id)
+            .await
+            .map_err(|_e| CustomError::DeletionError)?;
+
+        if delete_user.rows_affected == 0 {
+            warn!("User with ID {} was not deleted (0 rows affected)", user_id);
+            return Err(CustomError::DeletionError);
+        }
+
+        txn.commit().await.map_err(|_e| CustomError::DeletionError)?;
+        info!("User with ID {} successfully deleted", user_id);
+        Ok(())
+    }
+
+    /// Scrubs the personal data of a foster while keeping their requests,
+    /// so shelters retain the history of their placements. Everything runs in
+    /// one transaction and revokes the tokens already issued to the account.
+    pub async fn anonymize_foster(&self, user_id: i32) -> Result<(), CustomError> {
+        let txn = self.db.begin().await.map_err(|_e| CustomError::InternalError)?;
+        let user_repo = UtilisateurRepository::new(&txn);
+        let repo = FamilleRepository::new(&txn);
+
+        let user = user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .ok_or(CustomError::NotFound)?;
+
+        let foster = repo
+            .find_by_user_id(user_id)
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .ok_or(CustomError::NotFound)?;
+
+        if foster.anonymise_le.is_some() {
+            return Ok(());
+        }
+
+        let hosted = AnimalRepository::new(&txn)
+            .find_hosted_by(foster.id)
+            .await
+            .map_err(|_e| CustomError::InternalError)?;
+        if !hosted.is_empty() {
+            return Err(CustomError::FosteredError);
+        }
+
+        info!("Attempting to anonymize foster with ID: {}", foster.id);
+
+        let foster_id = foster.id;
+        let mut foster_active_model: FamilleActiveModelEx = foster.into();
+        foster_active_model.prenom = Set(None);
+        foster_active_model.nom = Set(ANONYMIZED_NAME.to_string());
+        foster_active_model.telephone = Set(String::new());
+        foster_active_model.rue = Set(String::new());
+        foster_active_model.commune = Set(String::new());
+        foster_active_model.code_postal = Set(String::new());
+        foster_active_model.pays = Set(String::new());
+        foster_active_model.hebergement = Set(String::new());
+        foster_active_model.terrain = Set(None);
+        foster_active_model.logement = Set(None);
+        foster_active_model.jardin = Set(false);
+        foster_active_model.possede_chats = Set(false);
+        foster_active_model.possede_chiens = Set(false);
+        foster_active_model.enfants = Set(false);
+        foster_active_model.experience = Set(None);
+        foster_active_model.latitude = Set(None);
+        foster_active_model.longitude = Set(None);
+        foster_active_model.anonymise_le = Set(Some(Utc::now().naive_utc()));
+
+        repo.update(foster_active_model)
+            .await
+            .map_err(|_e| CustomError::UpdateError)?;
+
+        repo.set_accepted_species(foster_id, Vec::new())
+            .await
+            .map_err(|_e| CustomError::UpdateError)?;
+
+        let search_repo = RechercheRepository::new(&txn);
+        let searches = search_repo
+            .find_by_foster(foster_id)
+            .await
+            .map_err(|_e| CustomError::InternalError)?;
+        for search in searches {
+            search_repo
+                .delete(search.id)
+                .await
+                .map_err(|_e| CustomError::DeletionError)?;
+        }
+
+        AlerteRepository::new(&txn)
+            .delete_by_user(user_id)
+            .await
+            .map_err(|_e| CustomError::DeletionError)?;
+
+        let token_version = user.version_jeton + 1;
+        let mut user_active_model: UtilisateurActiveModelEx = user.into();
+        user_active_model.email = Set(format!("anonyme-{}@anonyme.invalid", user_id));
+        user_active_model.mot_de_passe = Set(hash_password(&Uuid::new_v4().to_string())?);
+        user_active_model.version_jeton = Set(token_version);
+
+        user_repo
+            .update(user_active_model)
+            .await
+            .map_err(|_e| CustomError::UpdateError)?;
+
+        txn.commit().await.map_err(|_e| CustomError::UpdateError)?;
+        info!("Foster with ID {} successfully anonymized", foster_id);
+        Ok(())
+    }
+
+    async fn delete_shelter_profile(txn: &DatabaseTransaction, user_id: i32) -> Result<(), CustomError> {
+        let repo = AssociationRepository::new(txn);
+
+        let shelter = repo
+            .find_by_user_id(user_id)
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .ok_or(CustomError::NotFound)?;
+        if !shelter.pensionnaires.is_empty() {
+            return Err(CustomError::ShelteredError);
+        }
+
+        info!("Attempting to delete shelter with ID: {}", shelter.id);
+
+        let delete_result = repo
+            .delete(shelter.id)
+            .await
+            .map_err(|_e| CustomError::DeletionError)?;
+
+        if delete_result.rows_affected > 0 {
+            info!("Shelter with ID {} successfully archived", shelter.id);
+            Ok(())
+        } else {
+            warn!("Shelter with ID {} was not archived (0 rows affected)", shelter.id);
+            Err(CustomError::DeletionError)
+        }
+    }
+
+    async fn delete_foster_profile(txn: &DatabaseTransaction, user_id: i32) -> Result<(), CustomError> {
+        let repo = FamilleRepository::new(txn);
+
+        let foster = repo
+            .find_by_user_id(user_id)
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .ok_or(CustomError::NotFound)?;
+        if !foster.animals.is_empty() {
+            return Err(CustomError::FosteredError);
+        }
+
+        info!("Attempting to delete foster with ID: {}", foster.id);
+
+        let delete_result = repo
+            .delete(foster.id)
+            .await
+            .map_err(|_e| CustomError::DeletionError)?;
+
+        if delete_result.rows_affected > 0 {
+            info!("Foster with ID {} successfully archived", foster.id);
+            Ok(())
+        } else {
+            warn!("Foster with ID {} was not archived (0 rows affected)", foster.id);
+            Err(CustomError::DeletionError)
+        }
+    }
+
+    /// Files are only removed once their rows are gone, so a failed delete leaves no dangling media.
+    pub async fn delete_shelter_media(&self, shelter_id: i32) -> Result<(), CustomError> {
+        let repo = MediaRepository::new(self.db);
+
+        let medias = repo
+            .find_by_association(shelter_id)
+            .await
+            .map_err(|_e| CustomError::InternalError)?;
+
+        repo.delete_by_association(shelter_id)
+            .await
+            .map_err(|_e| CustomError::DeletionError)?;
+
+        for media in medias {
+            remove_static_file(&media.url);
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes an uploaded file, `url` being relative to `./static`.
+pub fn remove_static_file(url: &str) {
+    let path = format!("./static{}", url);
+
+    match fs::remove_file(&path) {
+        Ok(()) => info!("Removed {}", path),
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => warn!("Could not remove {}: {}", path, e),
+    }
+}
@@ -0,0 +1,161 @@
+use actix_web::rt;
+use chrono::{Duration, NaiveDateTime, Utc};
+use log::{error, info};
+use sea_orm::ActiveValue::Set;
+use sea_orm::DatabaseConnection;
+use sea_orm::entity::prelude::HasMany;
+use std::collections::HashSet;
+
+use crate::database::models::sea_orm_active_enums::{FrequenceAlerte, Statut};
+use crate::database::models::{AlerteActiveModel, AnimalModelEx, RechercheModelEx};
+use crate::database::repositories::{AlerteRepository, AnimalRepository, RechercheRepository};
+use crate::dto::Age;
+use crate::geo::Coordinates;
+
+const DIGEST_CHECK_INTERVAL_SECONDS: u64 = 3600;
+
+/// Turns animals becoming available into inbox alerts for the matching saved searches.
+pub struct AlertService<'a> {
+    db: &'a DatabaseConnection,
+}
+
+fn matches_search(search: &RechercheModelEx, tag_ids: &HashSet<i32>, shelter: Option<Coordinates>) -> bool {
+    let tags_match = match &search.tags {
+        HasMany::Loaded(tags) => tags.iter().all(|tag| tag_ids.contains(&tag.id)),
+        HasMany::Unloaded => true,
+    };
+
+    let distance_match = match search.rayon_km {
+        None => true,
+        Some(radius_km) => {
+            let foster = search
+                .famille
+                .as_ref()
+                .and_then(|foster| Coordinates::from_columns(foster.latitude, foster.longitude));
+
+            match (foster, shelter) {
+                (Some(foster), Some(shelter)) => foster.distance_km(&shelter) <= radius_km as f64,
+                _ => false,
+            }
+        }
+    };
+
+    tags_match && distance_match
+}
+
+/// Size and compatibilities asked by the search, unknown compatibilities don't match.
+fn matches_profile(search: &RechercheModelEx, animal: &AnimalModelEx) -> bool {
+    let size_match = search.taille.is_none() || search.taille == animal.taille;
+    let compatible = |required: bool, known: Option<bool>| !required || known == Some(true);
+
+    size_match
+        && compatible(search.ok_enfants, animal.ok_enfants)
+        && compatible(search.ok_chiens, animal.ok_chiens)
+        && compatible(search.ok_chats, animal.ok_chats)
+}
+
+impl<'a> AlertService<'a> {
+    pub fn new(db: &'a DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Called when an animal is created or comes back to the shelter.
+    pub async fn animal_available(&self, animal_id: i32) -> Result<usize, sea_orm::DbErr> {
+        let Some(animal) = AnimalRepository::new(self.db).find_by_id(animal_id).await? else {
+            return Ok(0);
+        };
+        if animal.statut != Statut::EnRefuge {
+            return Ok(0);
+        }
+
+        let alerts = self.matching_alerts(&animal).await?;
+        let count = alerts.len();
+
+        AlerteRepository::new(self.db).create_many(alerts).await?;
+
+        info!("Animal with ID {} matched {} saved search(es)", animal_id, count);
+        Ok(count)
+    }
+
+    async fn matching_alerts(&self, animal: &AnimalModelEx) -> Result<Vec<AlerteActiveModel>, sea_orm::DbErr> {
+        let tag_ids: HashSet<i32> = match &animal.tags {
+            HasMany::Loaded(tags) => tags.iter().map(|tag| tag.id).collect(),
+            HasMany::Unloaded => HashSet::new(),
+        };
+        let shelter = animal
+            .refuge
+            .as_ref()
+            .and_then(|shelter| Coordinates::from_columns(shelter.latitude, shelter.longitude));
+
+        let candidates = RechercheRepository::new(self.db)
+            .find_candidates(animal.espece_id, animal.sexe.clone(), Age::today(animal.date_naissance).annees)
+            .await?;
+
+        let now = Utc::now().naive_utc();
+
+        Ok(candidates
+            .into_iter()
+            .filter(|search| matches_search(search, &tag_ids, shelter) && matches_profile(search, animal))
+            .filter_map(|search| Some(AlerteActiveModel {
+                utilisateur_id: Set(search.famille.as_ref()?.utilisateur_id),
+                recherche_id: Set(Some(search.id)),
+                famille_id: Set(Some(search.famille_id)),
+                animal_id: Set(Some(animal.id)),
+                cree_le: Set(now),
+                publiee_le: Set((search.frequence == FrequenceAlerte::Immédiate).then_some(now)),
+                lue: Set(false),
+                ..Default::default()
+            }))
+            .collect())
+    }
+
+    /// Publishes the alerts held back for daily and weekly searches whose digest is due.
+    pub async fn publish_digests(&self) -> Result<u64, sea_orm::DbErr> {
+        let now = Utc::now().naive_utc();
+        let mut published = 0;
+
+        for (frequence, period) in [
+            (FrequenceAlerte::Quotidienne, Duration::days(1)),
+            (FrequenceAlerte::Hebdomadaire, Duration::weeks(1)),
+        ] {
+            published += self.publish_due(frequence, now - period, now).await?;
+        }
+
+        Ok(published)
+    }
+
+    async fn publish_due(&self, frequence: FrequenceAlerte, cutoff: NaiveDateTime, now: NaiveDateTime) -> Result<u64, sea_orm::DbErr> {
+        let search_repo = RechercheRepository::new(self.db);
+
+        let ids: Vec<i32> = search_repo
+            .find_due(frequence, cutoff)
+            .await?
+            .into_iter()
+            .map(|search| search.id)
+            .collect();
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let published = AlerteRepository::new(self.db).publish_pending(ids.clone(), now).await?;
+        search_repo.mark_notified(ids, now).await?;
+
+        Ok(published)
+    }
+}
+
+/// Periodically releases daily and weekly alert digests into the fosters' inboxes.
+pub fn spawn_alert_digest_job(db: DatabaseConnection) {
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(std::time::Duration::from_secs(DIGEST_CHECK_INTERVAL_SECONDS));
+
+        loop {
+            interval.tick().await;
+
+            match AlertService::new(&db).publish_digests().await {
+                Ok(count) => info!("Alert digest job published {} alert(s)", count),
+                Err(e) => error!("Alert digest job failed: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,401 @@
+use chrono::NaiveDate;
+use csv::{ReaderBuilder, Trim, WriterBuilder};
+use sea_orm::ActiveValue::Set;
+use sea_orm::{Condition, DatabaseConnection};
+use sea_orm::entity::prelude::HasMany;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use validator::Validate;
+
+use crate::api::animal::AnimalCreate;
+use crate::auth::CustomError;
+use crate::database::models::sea_orm_active_enums::{NiveauEnergie, PrecisionNaissance, Sexe, Statut, StatutDemande, Taille};
+use crate::database::models::{AnimalActiveModel, AnimalModel};
+use crate::database::repositories::{AnimalRepository, EspeceRepository, RaceRepository, TagRepository};
+use crate::validators::common_validators::format_validation_errors;
+
+const MAX_IMPORT_ROWS: usize = 1000;
+
+/// One spreadsheet line, with columns named after the `AnimalCreate` fields.
+/// Species, breeds and tags are given by name, breeds also by alias and tags separated by `;`, `,` or `|`.
+#[derive(Debug, Deserialize)]
+struct AnimalRow {
+    nom_animal: String,
+    race_animal: Option<String>,
+    croise_animal: Option<bool>,
+    couleur_animal: String,
+    date_naissance_animal: NaiveDate,
+    precision_naissance_animal: Option<PrecisionNaissance>,
+    sexe_animal: Sexe,
+    description_animal: String,
+    taille_animal: Option<Taille>,
+    niveau_energie_animal: Option<NiveauEnergie>,
+    ok_enfants_animal: Option<bool>,
+    ok_chiens_animal: Option<bool>,
+    ok_chats_animal: Option<bool>,
+    propre_animal: Option<bool>,
+    besoins_speciaux_animal: Option<bool>,
+    histoire_animal: Option<String>,
+    espece_animal: String,
+    tags: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RowError {
+    pub ligne: u64,
+    pub erreurs: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub lignes: usize,
+    pub valides: usize,
+    pub erreurs: Vec<RowError>,
+    pub animaux: Vec<AnimalModel>,
+}
+
+#[derive(Debug, Serialize)]
+struct AnimalExportRow {
+    id: i32,
+    nom_animal: String,
+    race_animal: Option<String>,
+    croise_animal: bool,
+    couleur_animal: String,
+    date_naissance_animal: NaiveDate,
+    precision_naissance_animal: PrecisionNaissance,
+    sexe_animal: Sexe,
+    description_animal: String,
+    taille_animal: Option<Taille>,
+    niveau_energie_animal: Option<NiveauEnergie>,
+    ok_enfants_animal: Option<bool>,
+    ok_chiens_animal: Option<bool>,
+    ok_chats_animal: Option<bool>,
+    propre_animal: Option<bool>,
+    besoins_speciaux_animal: bool,
+    puce_animal: bool,
+    histoire_animal: Option<String>,
+    espece_animal: String,
+    tags: String,
+    statut: Statut,
+    famille_id: Option<i32>,
+    cree_le: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RequestExportRow {
+    demande_id: i32,
+    animal_id: i32,
+    nom_animal: String,
+    famille_id: i32,
+    famille_prenom: Option<String>,
+    famille_commune: String,
+    statut_demande: StatutDemande,
+    date_debut: String,
+    date_fin: String,
+}
+
+/// Bulk import and export of a shelter's animals as CSV.
+pub struct AnimalCsvService<'a> {
+    db: &'a DatabaseConnection,
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Spreadsheets run cells starting with these characters as formulas.
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// Quotes text exported from user input so that spreadsheets show it instead of evaluating it.
+fn escape_cell(value: String) -> String {
+    if value.starts_with(FORMULA_PREFIXES) {
+        format!("'{}", value)
+    } else {
+        value
+    }
+}
+
+/// Spreadsheets saved with a French locale use `;` as separator.
+fn detect_delimiter(data: &[u8]) -> u8 {
+    let header = data.split(|byte| *byte == b'\n').next().unwrap_or_default();
+    if header.contains(&b';') && !header.contains(&b',') {
+        b';'
+    } else {
+        b','
+    }
+}
+
+impl<'a> AnimalCsvService<'a> {
+    pub fn new(db: &'a DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Validates every row, then creates all animals in one transaction unless a row failed or `dry_run` is set.
+    pub async fn import(&self, shelter_id: i32, data: &[u8], dry_run: bool) -> Result<ImportReport, CustomError> {
+        let species: HashMap<String, i32> = EspeceRepository::new(self.db)
+            .find_all()
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .into_iter()
+            .map(|espece| (normalize(&espece.nom), espece.id))
+            .collect();
+        let tags: HashMap<String, i32> = TagRepository::new(self.db)
+            .find_all()
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .into_iter()
+            .map(|tag| (normalize(&tag.nom), tag.id))
+            .collect();
+        let breeds: HashMap<(i32, String), (i32, String)> = RaceRepository::new(self.db)
+            .find_all()
+            .await
+            .map_err(|_e| CustomError::InternalError)?
+            .into_iter()
+            .flat_map(|race| {
+                let mut names = vec![normalize(&race.nom)];
+                if let HasMany::Loaded(aliases) = &race.alias {
+                    names.extend(aliases.iter().map(|alias| normalize(&alias.alias)));
+                }
+                names
+                    .into_iter()
+                    .map(move |name| ((race.espece_id, name), (race.id, race.nom.clone())))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut reader = ReaderBuilder::new()
+            .delimiter(detect_delimiter(data))
+            .trim(Trim::All)
+            .from_reader(data);
+
+        let mut lignes = 0;
+        let mut erreurs = vec![];
+        let mut animals = vec![];
+
+        for result in reader.deserialize::<AnimalRow>() {
+            lignes += 1;
+            if lignes > MAX_IMPORT_ROWS {
+                return Err(CustomError::ValidationError {
+                    error_messages: format!("file: At most {} animals can be imported at once", MAX_IMPORT_ROWS),
+                });
+            }
+
+            // Header is line 1.
+            let ligne = lignes as u64 + 1;
+            let row = match result {
+                Ok(row) => row,
+                Err(e) => {
+                    erreurs.push(RowError { ligne, erreurs: e.to_string() });
+                    continue;
+                }
+            };
+
+            match Self::validate_row(row, shelter_id, &species, &breeds, &tags) {
+                Ok(animal) => animals.push(animal),
+                Err(messages) => erreurs.push(RowError { ligne, erreurs: messages }),
+            }
+        }
+
+        let valides = animals.len();
+        if dry_run || !erreurs.is_empty() || animals.is_empty() {
+            return Ok(ImportReport {
+                dry_run,
+                lignes,
+                valides,
+                erreurs,
+                animaux: vec![],
+            });
+        }
+
+        let created = AnimalRepository::new(self.db)
+            .import(animals)
+            .await
+            .map_err(|_e| CustomError::CreationError)?;
+
+        Ok(ImportReport {
+            dry_run,
+            lignes,
+            valides,
+            erreurs,
+            animaux: created,
+        })
+    }
+
+    fn validate_row(
+        row: AnimalRow,
+        shelter_id: i32,
+        species: &HashMap<String, i32>,
+        breeds: &HashMap<(i32, String), (i32, String)>,
+        tags: &HashMap<String, i32>,
+    ) -> Result<(AnimalActiveModel, Vec<i32>), String> {
+        let mut messages = vec![];
+
+        let espece_id = species.get(&normalize(&row.espece_animal)).copied();
+        if espece_id.is_none() {
+            messages.push(format!("espece_animal: Unknown species '{}'", row.espece_animal));
+        }
+
+        let breed = match (espece_id, row.race_animal.as_deref().filter(|name| !name.is_empty())) {
+            (Some(espece_id), Some(name)) => {
+                let breed = breeds.get(&(espece_id, normalize(name))).cloned();
+                if breed.is_none() {
+                    messages.push(format!("race_animal: Unknown breed '{}' for this species", name));
+                }
+                breed
+            }
+            _ => None,
+        };
+
+        let mut tag_ids = vec![];
+        for name in row
+            .tags
+            .as_deref()
+            .unwrap_or_default()
+            .split([';', ',', '|'])
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            match tags.get(&normalize(name)) {
+                Some(id) if !tag_ids.contains(id) => tag_ids.push(*id),
+                Some(_) => {}
+                None => messages.push(format!("tags: Unknown tag '{}'", name)),
+            }
+        }
+
+        let animal = AnimalCreate {
+            nom_animal: row.nom_animal,
+            race_id: breed.as_ref().map(|(race_id, _)| *race_id),
+            croise_animal: row.croise_animal.unwrap_or_default(),
+            couleur_animal: row.couleur_animal,
+            date_naissance_animal: row.date_naissance_animal,
+            precision_naissance_animal: row.precision_naissance_animal,
+            sexe_animal: row.sexe_animal,
+            description_animal: row.description_animal,
+            taille_animal: row.taille_animal,
+            niveau_energie_animal: row.niveau_energie_animal,
+            ok_enfants_animal: row.ok_enfants_animal,
+            ok_chiens_animal: row.ok_chiens_animal,
+            ok_chats_animal: row.ok_chats_animal,
+            propre_animal: row.propre_animal,
+            besoins_speciaux_animal: row.besoins_speciaux_animal.unwrap_or_default(),
+            histoire_animal: row.histoire_animal,
+            poids_animal: None,
+            espece_animal: espece_id.unwrap_or_default().to_string(),
+            association_id: shelter_id,
+            tags: tag_ids,
+        };
+        if let Err(validation_errors) = animal.validate() {
+            messages.push(format_validation_errors(validation_errors));
+        }
+
+        let Some(espece_id) = espece_id.filter(|_| messages.is_empty()) else {
+            return Err(messages.join("; "));
+        };
+
+        let model = AnimalActiveModel {
+            nom: Set(animal.nom_animal),
+            race: Set(breed.map(|(_, nom)| nom)),
+            race_id: Set(animal.race_id),
+            croise: Set(animal.croise_animal),
+            couleur: Set(animal.couleur_animal),
+            date_naissance: Set(animal.date_naissance_animal),
+            precision_naissance: Set(animal.precision_naissance_animal.unwrap_or(PrecisionNaissance::Exacte)),
+            sexe: Set(animal.sexe_animal),
+            description: Set(animal.description_animal),
+            taille: Set(animal.taille_animal),
+            niveau_energie: Set(animal.niveau_energie_animal),
+            ok_enfants: Set(animal.ok_enfants_animal),
+            ok_chiens: Set(animal.ok_chiens_animal),
+            ok_chats: Set(animal.ok_chats_animal),
+            propre: Set(animal.propre_animal),
+            besoins_speciaux: Set(animal.besoins_speciaux_animal),
+            histoire: Set(animal.histoire_animal),
+            statut: Set(Statut::EnRefuge),
+            association_id: Set(shelter_id),
+            espece_id: Set(espece_id),
+            ..Default::default()
+        };
+
+        Ok((model, animal.tags))
+    }
+
+    pub async fn export_animals(&self, shelter_id: i32) -> Result<Vec<u8>, CustomError> {
+        let animals = AnimalRepository::new(self.db)
+            .find_by_shelters(vec![shelter_id], Condition::all())
+            .await
+            .map_err(|_e| CustomError::InternalError)?;
+
+        let mut writer = WriterBuilder::new().from_writer(vec![]);
+        for animal in animals {
+            let tags = match &animal.tags {
+                HasMany::Loaded(tags) => tags.iter().map(|tag| tag.nom.clone()).collect::<Vec<_>>().join(";"),
+                HasMany::Unloaded => String::new(),
+            };
+
+            writer
+                .serialize(AnimalExportRow {
+                    id: animal.id,
+                    espece_animal: escape_cell(animal.espece.as_ref().map(|espece| espece.nom.clone()).unwrap_or_default()),
+                    nom_animal: escape_cell(animal.nom),
+                    race_animal: animal.race.map(escape_cell),
+                    croise_animal: animal.croise,
+                    couleur_animal: escape_cell(animal.couleur),
+                    date_naissance_animal: animal.date_naissance,
+                    precision_naissance_animal: animal.precision_naissance,
+                    sexe_animal: animal.sexe,
+                    description_animal: escape_cell(animal.description),
+                    taille_animal: animal.taille,
+                    niveau_energie_animal: animal.niveau_energie,
+                    ok_enfants_animal: animal.ok_enfants,
+                    ok_chiens_animal: animal.ok_chiens,
+                    ok_chats_animal: animal.ok_chats,
+                    propre_animal: animal.propre,
+                    besoins_speciaux_animal: animal.besoins_speciaux,
+                    puce_animal: animal.puce,
+                    histoire_animal: animal.histoire.map(escape_cell),
+                    tags: escape_cell(tags),
+                    statut: animal.statut,
+                    famille_id: animal.famille_id,
+                    cree_le: animal.cree_le.format("%Y-%m-%d %H:%M:%S").to_string(),
+                })
+                .map_err(|_e| CustomError::InternalError)?;
+        }
+
+        writer.into_inner().map_err(|_e| CustomError::InternalError)
+    }
+
+    pub async fn export_requests(&self, shelter_id: i32) -> Result<Vec<u8>, CustomError> {
+        let animals = AnimalRepository::new(self.db)
+            .find_requested(shelter_id)
+            .await
+            .map_err(|_e| CustomError::InternalError)?;
+
+        let mut writer = WriterBuilder::new().from_writer(vec![]);
+        for animal in animals {
+            let HasMany::Loaded(requests) = &animal.demandes else {
+                continue;
+            };
+
+            for request in requests {
+                let foster = request.famille.as_ref();
+
+                writer
+                    .serialize(RequestExportRow {
+                        demande_id: request.id,
+                        animal_id: animal.id,
+                        nom_animal: escape_cell(animal.nom.clone()),
+                        famille_id: request.famille_id,
+                        famille_prenom: foster.and_then(|foster| foster.prenom.clone()).map(escape_cell),
+                        famille_commune: escape_cell(foster.map(|foster| foster.commune.clone()).unwrap_or_default()),
+                        statut_demande: request.statut_demande.clone(),
+                        date_debut: request.date_debut.to_string(),
+                        date_fin: request.date_fin.to_string(),
+                    })
+                    .map_err(|_e| CustomError::InternalError)?;
+            }
+        }
+
+        writer.into_inner().map_err(|_e| CustomError::InternalError)
+    }
+}
@@ -0,0 +1,104 @@
+use actix_web::rt;
+use chrono::{Duration, Utc};
+use log::{error, info, warn};
+use sea_orm::entity::prelude::HasMany;
+use sea_orm::{DatabaseConnection, DbErr};
+
+use crate::config::ArchiveConfig;
+use crate::database::repositories::{AnimalRepository, AssociationRepository, FamilleRepository, MediaRepository, SoinRepository, UtilisateurRepository};
+use crate::services::AccountService;
+use crate::services::account_service::remove_static_file;
+
+/// Periodically hard-deletes animals, shelters and fosters archived for more than `retention_days`.
+pub fn spawn_purge_job(db: DatabaseConnection, config: ArchiveConfig) {
+    if config.retention_days == 0 {
+        info!("Archive purge job disabled");
+        return;
+    }
+
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(std::time::Duration::from_secs(config.interval_hours.max(1) * 3600));
+
+        loop {
+            interval.tick().await;
+
+            match purge_archived(&db, &config).await {
+                Ok(count) => info!("Archive purge job removed {} archived row(s)", count),
+                Err(e) => error!("Archive purge job failed: {}", e),
+            }
+        }
+    });
+}
+
+pub async fn purge_archived(db: &DatabaseConnection, config: &ArchiveConfig) -> Result<usize, DbErr> {
+    let cutoff = (Utc::now() - Duration::days(config.retention_days as i64)).naive_utc();
+    let mut count = 0;
+
+    for animal in AnimalRepository::new(db).find_archived_before(cutoff).await? {
+        match purge_animal(db, animal.id).await {
+            Ok(()) => count += 1,
+            Err(e) => warn!("Could not purge animal with ID {}: {}", animal.id, e),
+        }
+    }
+
+    for shelter in AssociationRepository::new(db).find_archived_before(cutoff).await? {
+        match purge_shelter(db, shelter.id, shelter.utilisateur_id).await {
+            Ok(()) => count += 1,
+            Err(e) => warn!("Could not purge shelter with ID {}: {}", shelter.id, e),
+        }
+    }
+
+    for foster in FamilleRepository::new(db).find_archived_before(cutoff).await? {
+        match purge_foster(db, foster.id, foster.utilisateur_id).await {
+            Ok(()) => count += 1,
+            Err(e) => warn!("Could not purge foster with ID {}: {}", foster.id, e),
+        }
+    }
+
+    Ok(count)
+}
+
+async fn purge_animal(db: &DatabaseConnection, animal_id: i32) -> Result<(), DbErr> {
+    let media_repo = MediaRepository::new(db);
+    let medias = media_repo.find_by_animal(animal_id).await?;
+    media_repo.delete_by_animal(animal_id).await?;
+    for media in medias {
+        remove_static_file(&media.url);
+    }
+
+    // Vet visits and their documents go with the animal row, only their files are left to remove.
+    for visit in SoinRepository::new(db).find_visits(vec![animal_id]).await? {
+        if let HasMany::Loaded(documents) = visit.documents {
+            for document in documents {
+                remove_static_file(&document.url);
+            }
+        }
+    }
+
+    AnimalRepository::new(db).purge(animal_id).await?;
+
+    Ok(())
+}
+
+async fn purge_shelter(db: &DatabaseConnection, shelter_id: i32, user_id: i32) -> Result<(), DbErr> {
+    for animal in AnimalRepository::new(db).find_all_by_shelter(shelter_id).await? {
+        purge_animal(db, animal.id).await?;
+    }
+
+    AccountService::new(db)
+        .delete_shelter_media(shelter_id)
+        .await
+        .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+    AssociationRepository::new(db).purge(shelter_id).await?;
+    UtilisateurRepository::new(db).delete(user_id).await?;
+
+    Ok(())
+}
+
+async fn purge_foster(db: &DatabaseConnection, foster_id: i32, user_id: i32) -> Result<(), DbErr> {
+    FamilleRepository::new(db).purge(foster_id).await?;
+    UtilisateurRepository::new(db).delete(user_id).await?;
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use actix_web::rt;
+use log::{error, info};
+use sea_orm::ActiveValue::Set;
+use sea_orm::{DatabaseConnection, IntoActiveModel};
+
+use crate::database::repositories::{AssociationRepository, FamilleRepository};
+use crate::geo::{GEOCODER, Geocoder};
+
+/// Geocodes, once at startup, the shelters and fosters saved without coordinates.
+pub fn spawn_geocoding_backfill(db: DatabaseConnection) {
+    rt::spawn(async move {
+        match backfill_coordinates(&db).await {
+            Ok(count) => info!("Geocoded {} address(es) missing coordinates", count),
+            Err(e) => error!("Geocoding backfill failed: {}", e),
+        }
+    });
+}
+
+pub async fn backfill_coordinates(db: &DatabaseConnection) -> Result<usize, sea_orm::DbErr> {
+    let mut count = 0;
+
+    let shelter_repo = AssociationRepository::new(db);
+    for shelter in shelter_repo.find_without_coordinates().await? {
+        let Some(coordinates) = GEOCODER.geocode(&shelter.code_postal, &shelter.commune, &shelter.pays) else {
+            continue;
+        };
+
+        let mut shelter_active_model = shelter.into_active_model();
+        shelter_active_model.latitude = Set(Some(coordinates.latitude));
+        shelter_active_model.longitude = Set(Some(coordinates.longitude));
+        shelter_repo.update_model(shelter_active_model).await?;
+        count += 1;
+    }
+
+    let foster_repo = FamilleRepository::new(db);
+    for foster in foster_repo.find_without_coordinates().await? {
+        let Some(coordinates) = GEOCODER.geocode(&foster.code_postal, &foster.commune, &foster.pays) else {
+            continue;
+        };
+
+        let mut foster_active_model = foster.into_active_model();
+        foster_active_model.latitude = Set(Some(coordinates.latitude));
+        foster_active_model.longitude = Set(Some(coordinates.longitude));
+        foster_repo.update_model(foster_active_model).await?;
+        count += 1;
+    }
+
+    Ok(count)
+}
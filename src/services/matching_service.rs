@@ -0,0 +1,185 @@
+use sea_orm::entity::prelude::HasMany;
+use serde::Serialize;
+
+use crate::database::models::sea_orm_active_enums::{ContrainteTag, Logement, NiveauExperience};
+use crate::database::models::{AnimalModelEx, FamilleModelEx};
+use crate::geo::{Coordinates, MIN_RADIUS_KM};
+
+const BASE_SCORE: i32 = 50;
+
+/// One line of the score explanation.
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchReason {
+    pub critere: &'static str,
+    pub points: i32,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchScore {
+    pub score: i32,
+    pub compatible: bool,
+    pub raisons: Vec<MatchReason>,
+}
+
+impl MatchScore {
+    fn add(&mut self, critere: &'static str, points: i32, detail: impl Into<String>) {
+        self.score += points;
+        self.raisons.push(MatchReason {
+            critere,
+            points,
+            detail: detail.into(),
+        });
+    }
+
+    fn exclude(&mut self, critere: &'static str, detail: impl Into<String>) {
+        self.compatible = false;
+        self.raisons.push(MatchReason {
+            critere,
+            points: 0,
+            detail: detail.into(),
+        });
+    }
+}
+
+/// Scores how well a foster suits an animal.
+/// Violated tag constraints and unaccepted species make the pair incompatible.
+pub fn score(foster: &FamilleModelEx, animal: &AnimalModelEx) -> MatchScore {
+    let mut result = MatchScore {
+        score: BASE_SCORE,
+        compatible: true,
+        raisons: vec![],
+    };
+
+    score_species(&mut result, foster, animal);
+
+    if let HasMany::Loaded(tags) = &animal.tags {
+        for constraint in tags.iter().filter_map(|tag| tag.contrainte.as_ref()) {
+            score_constraint(&mut result, foster, constraint);
+        }
+    }
+
+    score_distance(&mut result, foster, animal);
+
+    result.score = result.score.clamp(0, 100);
+    result
+}
+
+/// Tag constraints carried by the animal, which candidate fosters must all meet.
+pub fn constraints(animal: &AnimalModelEx) -> Vec<ContrainteTag> {
+    match &animal.tags {
+        HasMany::Loaded(tags) => tags.iter().filter_map(|tag| tag.contrainte.clone()).collect(),
+        HasMany::Unloaded => vec![],
+    }
+}
+
+/// Tag constraints the foster cannot meet, so that animals carrying them are left out before scoring.
+pub fn unmet_constraints(foster: &FamilleModelEx) -> Vec<ContrainteTag> {
+    let mut unmet = vec![];
+    if !foster.jardin {
+        unmet.push(ContrainteTag::BesoinDeJardin);
+    }
+    if foster.logement == Some(Logement::Appartement) {
+        unmet.push(ContrainteTag::PasEnAppartement);
+    }
+    if foster.possede_chats {
+        unmet.push(ContrainteTag::PasAvecDesChats);
+    }
+    if foster.possede_chiens {
+        unmet.push(ContrainteTag::PasAvecDesChiens);
+    }
+    if foster.enfants {
+        unmet.push(ContrainteTag::PasAvecDesEnfants);
+    }
+    if foster.experience == Some(NiveauExperience::Débutant) {
+        unmet.push(ContrainteTag::ExpérienceRequise);
+    }
+    unmet
+}
+
+/// Species accepted by the foster, empty when they have no preference.
+pub fn accepted_species(foster: &FamilleModelEx) -> Vec<i32> {
+    match &foster.especes_acceptees {
+        HasMany::Loaded(species) => species.iter().map(|espece| espece.id).collect(),
+        HasMany::Unloaded => vec![],
+    }
+}
+
+fn score_species(result: &mut MatchScore, foster: &FamilleModelEx, animal: &AnimalModelEx) {
+    match &foster.especes_acceptees {
+        HasMany::Loaded(species) if !species.is_empty() => {
+            if species.iter().any(|espece| espece.id == animal.espece_id) {
+                result.add("espece", 20, "Espèce acceptée par la famille");
+            } else {
+                result.exclude("espece", "Espèce non acceptée par la famille");
+            }
+        }
+        _ => result.add("espece", 5, "Aucune préférence d'espèce"),
+    }
+}
+
+fn score_constraint(result: &mut MatchScore, foster: &FamilleModelEx, constraint: &ContrainteTag) {
+    match constraint {
+        ContrainteTag::BesoinDeJardin => {
+            if foster.jardin {
+                result.add("jardin", 10, "Jardin disponible");
+            } else {
+                result.exclude("jardin", "L'animal a besoin d'un jardin");
+            }
+        }
+        ContrainteTag::PasEnAppartement => match foster.logement {
+            Some(Logement::Maison) => result.add("logement", 10, "Logement en maison"),
+            Some(Logement::Appartement) => result.exclude("logement", "L'animal ne peut pas vivre en appartement"),
+            None => result.add("logement", -5, "Type de logement non renseigné"),
+        },
+        ContrainteTag::PasAvecDesChats => {
+            if foster.possede_chats {
+                result.exclude("animaux", "L'animal ne s'entend pas avec les chats");
+            } else {
+                result.add("animaux", 5, "Pas de chat au foyer");
+            }
+        }
+        ContrainteTag::PasAvecDesChiens => {
+            if foster.possede_chiens {
+                result.exclude("animaux", "L'animal ne s'entend pas avec les chiens");
+            } else {
+                result.add("animaux", 5, "Pas de chien au foyer");
+            }
+        }
+        ContrainteTag::PasAvecDesEnfants => {
+            if foster.enfants {
+                result.exclude("enfants", "L'animal ne peut pas vivre avec des enfants");
+            } else {
+                result.add("enfants", 5, "Pas d'enfant au foyer");
+            }
+        }
+        ContrainteTag::ExpérienceRequise => match foster.experience {
+            Some(NiveauExperience::Expérimenté) => result.add("experience", 15, "Famille expérimentée"),
+            Some(NiveauExperience::Intermédiaire) => result.add("experience", 0, "Expérience intermédiaire"),
+            Some(NiveauExperience::Débutant) => result.exclude("experience", "L'animal demande une famille expérimentée"),
+            None => result.add("experience", -10, "Expérience non renseignée"),
+        },
+    }
+}
+
+fn score_distance(result: &mut MatchScore, foster: &FamilleModelEx, animal: &AnimalModelEx) {
+    let foster_coordinates = Coordinates::from_columns(foster.latitude, foster.longitude);
+    let shelter_coordinates = animal
+        .refuge
+        .as_ref()
+        .and_then(|shelter| Coordinates::from_columns(shelter.latitude, shelter.longitude));
+
+    let (Some(foster_coordinates), Some(shelter_coordinates)) = (foster_coordinates, shelter_coordinates) else {
+        return;
+    };
+
+    // Below `MIN_RADIUS_KM` the department centroids cannot tell distances apart.
+    let distance = foster_coordinates.distance_km(&shelter_coordinates);
+    if distance <= MIN_RADIUS_KM {
+        result.add("distance", 15, "Dans le même secteur que le refuge");
+        return;
+    }
+
+    let points = if distance <= 2.0 * MIN_RADIUS_KM { 5 } else { -5 };
+    result.add("distance", points, format!("À environ {:.0} km du refuge", distance));
+}
@@ -0,0 +1,21 @@
+pub mod account_service;
+pub mod alert_service;
+pub mod animal_csv_service;
+pub mod archive_service;
+pub mod geocoding_service;
+pub mod matching_service;
+pub mod public_stats_service;
+pub mod retention_service;
+pub mod soin_service;
+pub mod verification_service;
+
+pub use account_service::AccountService;
+pub use alert_service::{AlertService, spawn_alert_digest_job};
+pub use animal_csv_service::AnimalCsvService;
+pub use archive_service::spawn_purge_job;
+pub use geocoding_service::spawn_geocoding_backfill;
+pub use matching_service::MatchScore;
+pub use public_stats_service::PublicStatsCache;
+pub use retention_service::spawn_retention_job;
+pub use soin_service::SoinService;
+pub use verification_service::spawn_verification_backfill;
@@ -0,0 +1,133 @@
+use chrono::Utc;
+use sea_orm::{DatabaseConnection, DbErr};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::config::PublicStatsConfig;
+use crate::database::repositories::StatsRepository;
+use crate::database::repositories::stats_repository::{AnimalImpactCount, PostalCodeCount};
+use crate::dto::stats::{PublicDepartmentStats, PublicSpeciesStats, PublicTotals};
+use crate::dto::PublicStats;
+use crate::geo::department_code;
+
+const UNKNOWN_DEPARTMENT: &str = "inconnu";
+
+/// Keeps the last computed public statistics for `cache_seconds`.
+pub struct PublicStatsCache {
+    ttl: Duration,
+    entry: RwLock<Option<(Instant, Arc<PublicStats>)>>,
+}
+
+impl PublicStatsCache {
+    pub fn new(config: PublicStatsConfig) -> Self {
+        Self {
+            ttl: Duration::from_secs(config.cache_seconds),
+            entry: RwLock::new(None),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn cached(&self) -> Option<Arc<PublicStats>> {
+        let entry = self.entry.read().unwrap_or_else(|e| e.into_inner());
+        entry
+            .as_ref()
+            .filter(|(computed_at, _)| computed_at.elapsed() < self.ttl)
+            .map(|(_, stats)| stats.clone())
+    }
+
+    pub async fn get_or_refresh(&self, db: &DatabaseConnection) -> Result<Arc<PublicStats>, DbErr> {
+        if let Some(stats) = self.cached() {
+            return Ok(stats);
+        }
+
+        let stats = Arc::new(compute(db).await?);
+        *self.entry.write().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), stats.clone()));
+
+        Ok(stats)
+    }
+}
+
+/// French department, or the country code outside of France.
+fn department_of(pays: &str, code_postal: &str) -> String {
+    if pays != "FR" {
+        return pays.to_string();
+    }
+
+    department_code(code_postal).unwrap_or_else(|| UNKNOWN_DEPARTMENT.to_string())
+}
+
+fn department<'a>(
+    departments: &'a mut BTreeMap<String, PublicDepartmentStats>,
+    pays: &str,
+    code_postal: &str,
+) -> &'a mut PublicDepartmentStats {
+    let code = department_of(pays, code_postal);
+    departments.entry(code.clone()).or_insert_with(|| PublicDepartmentStats {
+        departement: code,
+        ..Default::default()
+    })
+}
+
+async fn compute(db: &DatabaseConnection) -> Result<PublicStats, DbErr> {
+    let repo = StatsRepository::new(db);
+    let shelters = repo.count_shelters_by_postal_code().await?;
+    let fosters = repo.count_active_fosters_by_postal_code().await?;
+    let fostered = repo.count_fostered_animals().await?;
+    let adoptions = repo.count_adoptions_this_year().await?;
+
+    Ok(aggregate(shelters, fosters, fostered, adoptions))
+}
+
+fn aggregate(
+    shelters: Vec<PostalCodeCount>,
+    fosters: Vec<PostalCodeCount>,
+    fostered: Vec<AnimalImpactCount>,
+    adoptions: Vec<AnimalImpactCount>,
+) -> PublicStats {
+    let mut totals = PublicTotals::default();
+    let mut species: BTreeMap<i32, PublicSpeciesStats> = BTreeMap::new();
+    let mut departments: BTreeMap<String, PublicDepartmentStats> = BTreeMap::new();
+
+    for count in shelters {
+        totals.refuges += count.total;
+        department(&mut departments, &count.pays, &count.code_postal).refuges += count.total;
+    }
+
+    for count in fosters {
+        totals.familles_actives += count.total;
+        department(&mut departments, &count.pays, &count.code_postal).familles_actives += count.total;
+    }
+
+    for (counts, is_adoption) in [(fostered, false), (adoptions, true)] {
+        for count in counts {
+            let species_entry = species.entry(count.espece_id).or_insert_with(|| PublicSpeciesStats {
+                espece_id: count.espece_id,
+                espece: count.espece.clone(),
+                animaux_en_accueil: 0,
+                adoptions_annee: 0,
+            });
+            let department_entry = department(&mut departments, &count.pays, &count.code_postal);
+
+            if is_adoption {
+                totals.adoptions_annee += count.total;
+                species_entry.adoptions_annee += count.total;
+                department_entry.adoptions_annee += count.total;
+            } else {
+                totals.animaux_en_accueil += count.total;
+                species_entry.animaux_en_accueil += count.total;
+                department_entry.animaux_en_accueil += count.total;
+            }
+        }
+    }
+
+    PublicStats {
+        genere_le: Utc::now().naive_utc(),
+        totaux: totals,
+        par_espece: species.into_values().collect(),
+        par_departement: departments.into_values().collect(),
+    }
+}
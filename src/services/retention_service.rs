@@ -0,0 +1,54 @@
+use actix_web::rt;
+use chrono::{Duration, Utc};
+use log::{error, info, warn};
+use sea_orm::DatabaseConnection;
+
+use crate::config::RetentionConfig;
+use crate::database::repositories::{FamilleRepository, UtilisateurRepository};
+use crate::services::AccountService;
+
+/// Periodically anonymizes fosters who have not logged in for `inactive_days`.
+pub fn spawn_retention_job(db: DatabaseConnection, config: RetentionConfig) {
+    if !config.is_enabled() {
+        info!("Retention job disabled");
+        return;
+    }
+
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(std::time::Duration::from_secs(config.interval_hours.max(1) * 3600));
+
+        loop {
+            interval.tick().await;
+
+            match run_retention(&db, &config).await {
+                Ok(count) => info!("Retention job anonymized {} inactive foster(s)", count),
+                Err(e) => error!("Retention job failed: {}", e),
+            }
+        }
+    });
+}
+
+pub async fn run_retention(db: &DatabaseConnection, config: &RetentionConfig) -> Result<usize, sea_orm::DbErr> {
+    let cutoff = (Utc::now() - Duration::days(config.inactive_days as i64)).naive_utc();
+
+    let inactive_ids = UtilisateurRepository::new(db).find_inactive_ids(cutoff).await?;
+    if inactive_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let fosters = FamilleRepository::new(db)
+        .find_not_anonymized_by_user_ids(inactive_ids)
+        .await?;
+
+    let service = AccountService::new(db);
+    let mut count = 0;
+
+    for foster in fosters {
+        match service.anonymize_foster(foster.utilisateur_id).await {
+            Ok(()) => count += 1,
+            Err(e) => warn!("Could not anonymize foster with ID {}: {}", foster.id, e),
+        }
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,164 @@
+use chrono::{Duration, NaiveDate, Utc};
+use sea_orm::{DatabaseConnection, DbErr};
+use std::collections::{HashMap, HashSet};
+
+use crate::database::models::TraitementModel;
+use crate::database::repositories::SoinRepository;
+use crate::dto::{DossierSoins, Rappel, TypeSoin};
+
+/// Builds care records and the reminders of upcoming care.
+pub struct SoinService<'a> {
+    db: &'a DatabaseConnection,
+}
+
+/// Next intake of an ongoing treatment, from today on.
+fn next_intake(treatment: &TraitementModel, today: NaiveDate) -> Option<NaiveDate> {
+    let frequency = i64::from(treatment.frequence_jours.max(1));
+    let next = if today <= treatment.debut {
+        treatment.debut
+    } else {
+        let elapsed = (today - treatment.debut).num_days();
+        treatment.debut + Duration::days((elapsed + frequency - 1) / frequency * frequency)
+    };
+
+    match treatment.fin {
+        Some(fin) if next > fin => None,
+        _ => Some(next),
+    }
+}
+
+impl<'a> SoinService<'a> {
+    pub fn new(db: &'a DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn dossier(&self, animal_id: i32) -> Result<DossierSoins, DbErr> {
+        let repo = SoinRepository::new(self.db);
+
+        Ok(DossierSoins {
+            animal_id,
+            fiche: repo.find_fiche(animal_id).await?,
+            vaccinations: repo.find_vaccinations(vec![animal_id]).await?,
+            traitements: repo.find_treatments(vec![animal_id]).await?,
+            visites: repo.find_visits(vec![animal_id]).await?,
+        })
+    }
+
+    /// Care due within `days` for the given animals, keyed by id with their name.
+    /// Vaccine boosters stay listed once overdue until a new shot is recorded.
+    pub async fn reminders(&self, animals: HashMap<i32, String>, days: u32) -> Result<Vec<Rappel>, DbErr> {
+        let repo = SoinRepository::new(self.db);
+        let animal_ids: Vec<i32> = animals.keys().copied().collect();
+        let today = Utc::now().date_naive();
+        let horizon = today + Duration::days(i64::from(days));
+        let name_of = |animal_id: i32| animals.get(&animal_id).cloned().unwrap_or_default();
+
+        let mut reminders = Vec::new();
+
+        // Vaccinations come newest first, only the latest shot of each vaccine counts.
+        let mut seen = HashSet::new();
+        for vaccination in repo.find_vaccinations(animal_ids.clone()).await? {
+            if !seen.insert((vaccination.animal_id, vaccination.vaccin.to_lowercase())) {
+                continue;
+            }
+            if let Some(rappel_le) = vaccination.rappel_le.filter(|rappel_le| *rappel_le <= horizon) {
+                reminders.push(Rappel {
+                    animal_id: vaccination.animal_id,
+                    animal_nom: name_of(vaccination.animal_id),
+                    type_soin: TypeSoin::Vaccination,
+                    soin_id: vaccination.id,
+                    libelle: vaccination.vaccin,
+                    echeance: rappel_le,
+                    en_retard: rappel_le < today,
+                });
+            }
+        }
+
+        for treatment in repo.find_treatments(animal_ids.clone()).await? {
+            if let Some(next) = next_intake(&treatment, today).filter(|next| *next <= horizon) {
+                reminders.push(Rappel {
+                    animal_id: treatment.animal_id,
+                    animal_nom: name_of(treatment.animal_id),
+                    type_soin: TypeSoin::Traitement,
+                    soin_id: treatment.id,
+                    libelle: format!("{} ({})", treatment.medicament, treatment.posologie),
+                    echeance: next,
+                    en_retard: false,
+                });
+            }
+        }
+
+        for visit in repo.find_visits(animal_ids).await? {
+            let prevue_le = visit.prevue_le.date();
+            if prevue_le >= today && prevue_le <= horizon {
+                reminders.push(Rappel {
+                    animal_id: visit.animal_id,
+                    animal_nom: name_of(visit.animal_id),
+                    type_soin: TypeSoin::Visite,
+                    soin_id: visit.id,
+                    libelle: visit.motif,
+                    echeance: prevue_le,
+                    en_retard: false,
+                });
+            }
+        }
+
+        reminders.sort_by_key(|reminder| reminder.echeance);
+        Ok(reminders)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn treatment(debut: NaiveDate, fin: Option<NaiveDate>, frequence_jours: i32) -> TraitementModel {
+        TraitementModel {
+            id: 1,
+            animal_id: 1,
+            medicament: "Vermifuge".to_string(),
+            posologie: "1 comprimé".to_string(),
+            debut,
+            fin,
+            frequence_jours,
+            ajoute_par: None,
+            cree_le: debut.and_hms_opt(0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn starts_on_the_first_day() {
+        let treatment = treatment(date(2025, 1, 10), None, 3);
+
+        assert_eq!(next_intake(&treatment, date(2025, 1, 1)), Some(date(2025, 1, 10)));
+        assert_eq!(next_intake(&treatment, date(2025, 1, 10)), Some(date(2025, 1, 10)));
+    }
+
+    #[test]
+    fn rounds_up_to_the_next_intake() {
+        let treatment = treatment(date(2025, 1, 1), None, 3);
+
+        assert_eq!(next_intake(&treatment, date(2025, 1, 2)), Some(date(2025, 1, 4)));
+        assert_eq!(next_intake(&treatment, date(2025, 1, 4)), Some(date(2025, 1, 4)));
+        assert_eq!(next_intake(&treatment, date(2025, 1, 5)), Some(date(2025, 1, 7)));
+    }
+
+    #[test]
+    fn treats_a_missing_frequency_as_daily() {
+        let treatment = treatment(date(2025, 1, 1), None, 0);
+
+        assert_eq!(next_intake(&treatment, date(2025, 1, 5)), Some(date(2025, 1, 5)));
+    }
+
+    #[test]
+    fn stops_after_the_last_day() {
+        let treatment = treatment(date(2025, 1, 1), Some(date(2025, 1, 6)), 3);
+
+        assert_eq!(next_intake(&treatment, date(2025, 1, 5)), None);
+        assert_eq!(next_intake(&treatment, date(2025, 1, 3)), Some(date(2025, 1, 4)));
+    }
+}
@@ -0,0 +1,39 @@
+use actix_web::rt;
+use log::{error, info};
+use sea_orm::ActiveValue::Set;
+use sea_orm::{DatabaseConnection, IntoActiveModel};
+use std::sync::Arc;
+
+use crate::database::models::sea_orm_active_enums::StatutVerification;
+use crate::database::repositories::AssociationRepository;
+use crate::registry::{CompanyRegistry, verification_status, verified_at};
+
+/// Checks again, once at startup, the shelters whose SIRET could not be verified when they registered.
+pub fn spawn_verification_backfill(db: DatabaseConnection, registry: Arc<dyn CompanyRegistry>) {
+    rt::spawn(async move {
+        match backfill_verifications(&db, registry.as_ref()).await {
+            Ok(count) => info!("Settled the SIRET verification of {} shelter(s)", count),
+            Err(e) => error!("SIRET verification backfill failed: {}", e),
+        }
+    });
+}
+
+pub async fn backfill_verifications(db: &DatabaseConnection, registry: &dyn CompanyRegistry) -> Result<usize, sea_orm::DbErr> {
+    let mut count = 0;
+
+    let repo = AssociationRepository::new(db);
+    for shelter in repo.find_by_verification(vec![StatutVerification::EnAttente]).await? {
+        let status = verification_status(registry, &shelter.siret).await;
+        if status == StatutVerification::EnAttente {
+            continue;
+        }
+
+        let mut shelter_active_model = shelter.into_active_model();
+        shelter_active_model.verifiee_le = Set(verified_at(&status));
+        shelter_active_model.verification = Set(status);
+        repo.update_model(shelter_active_model).await?;
+        count += 1;
+    }
+
+    Ok(count)
+}
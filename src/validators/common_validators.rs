@@ -1,40 +1,92 @@
 use actix_web::web;
-use once_cell::sync::Lazy;
-use regex::Regex;
+use chrono::{Months, NaiveDate, Utc};
 use validator::{Validate, ValidationError, ValidationErrors};
 
 use crate::auth::CustomError;
+use crate::validators::country::{find_country, is_valid_postal_code, normalize_phone, normalize_postal_code};
+use crate::validators::siret::{is_valid_siret, normalize_siret};
 
-pub static PHONE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(0|\+33 )[1-9]([\-. ]?[0-9]{2} ){3}([\-. ]?[0-9]{2})|([0-9]{8})$").unwrap());
-pub static ZIPCODE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:0[1-9]|[1-8]\d|9[0-8])\d{3}$").unwrap());
-pub static SIRET_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d{14}|((\d{3}[ ]\d{3}[ ]\d{3})|\d{9})[ ]\d{5})$").unwrap());
+pub fn validate_country(pays: &str) -> Result<(), ValidationError> {
+    if find_country(pays).is_some() {
+        Ok(())
+    } else {
+        let mut error = ValidationError::new("invalid_country");
+        error.message = Some("Country must be a supported ISO 3166 code such as FR, BE or CH".into());
+        Err(error)
+    }
+}
+
+pub struct Contact {
+    /// ISO code as stored, e.g. `FR` for `fr`.
+    pub pays: String,
+    pub code_postal: String,
+    pub telephone: String,
+}
+
+/// Country-dependent checks of the postal code and phone number, returned normalized along with the country.
+pub fn validate_contact(pays: &str, code_postal: &str, telephone: &str) -> Result<Contact, CustomError> {
+    let Some(country) = find_country(pays) else {
+        return Err(CustomError::ValidationError {
+            error_messages: "pays: Country must be a supported ISO 3166 code such as FR, BE or CH".to_string(),
+        });
+    };
+
+    let mut error_messages = vec![];
+
+    let code_postal = normalize_postal_code(code_postal);
+    if !is_valid_postal_code(country, &code_postal) {
+        error_messages.push(format!("code_postal: Zip Code is not valid for {}", country.code));
+    }
 
-pub fn validate_phone(phone: &str) -> Result<(), ValidationError> {
-    if PHONE_REGEX.is_match(phone) {
+    let normalized_phone = normalize_phone(country, telephone);
+    if normalized_phone.is_none() {
+        error_messages.push("telephone: Phone number must be a valid national or international number".to_string());
+    }
+
+    match normalized_phone {
+        Some(telephone) if error_messages.is_empty() => Ok(Contact {
+            pays: country.code.to_string(),
+            code_postal,
+            telephone,
+        }),
+        _ => Err(CustomError::ValidationError {
+            error_messages: error_messages.join("; "),
+        }),
+    }
+}
+
+pub fn validate_siret(siret: &str) -> Result<(), ValidationError> {
+    if is_valid_siret(&normalize_siret(siret)) {
         Ok(())
     } else {
-        let mut error = ValidationError::new("invalid_phone");
-        error.message = Some("Phone number must be in the french format".into());
+        let mut error = ValidationError::new("invalid_siret");
+        error.message = Some("Your company SIRET number must have 14 digits and a valid checksum".into());
         Err(error)
     }
 }
 
-pub fn validate_zipcode(zip: &str) -> Result<(), ValidationError> {
-    if ZIPCODE_REGEX.is_match(zip) {
+const MAX_ANIMAL_AGE_YEARS: u32 = 40;
+
+pub fn validate_birth_date(date_naissance: &NaiveDate) -> Result<(), ValidationError> {
+    let today = Utc::now().date_naive();
+    let oldest = today.checked_sub_months(Months::new(MAX_ANIMAL_AGE_YEARS * 12));
+
+    if *date_naissance <= today && oldest.is_none_or(|oldest| *date_naissance >= oldest) {
         Ok(())
     } else {
-        let mut error = ValidationError::new("invalid_zipcode");
-        error.message = Some("Zip Code must be a valid french department".into());
+        let mut error = ValidationError::new("invalid_birth_date");
+        error.message = Some("Birth date must be realistic".into());
         Err(error)
     }
 }
 
-pub fn validate_siret(siret: &str) -> Result<(), ValidationError> {
-    if SIRET_REGEX.is_match(siret) {
+/// ISO 11784 microchips carry 15 digits.
+pub fn validate_microchip(numero_puce: &str) -> Result<(), ValidationError> {
+    if numero_puce.len() == 15 && numero_puce.chars().all(|c| c.is_ascii_digit()) {
         Ok(())
     } else {
-        let mut error = ValidationError::new("invalid_siret");
-        error.message = Some("Your company SIRET number must be in the french format".into());
+        let mut error = ValidationError::new("invalid_microchip");
+        error.message = Some("The microchip number must have 15 digits".into());
         Err(error)
     }
 }
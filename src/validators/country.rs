@@ -0,0 +1,81 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::ops::RangeInclusive;
+
+/// Country accepted for addresses and phone numbers, identified by its ISO 3166-1 alpha-2 code.
+pub struct Country {
+    pub code: &'static str,
+    pub dial_code: &'static str,
+    /// National numbers start with a `0` that is dropped in international form.
+    pub trunk_prefix: bool,
+    /// Digits of the national significant number.
+    pub number_length: RangeInclusive<usize>,
+    pub postal_code: Regex,
+}
+
+fn country(code: &'static str, dial_code: &'static str, trunk_prefix: bool, number_length: RangeInclusive<usize>, postal_code: &str) -> Country {
+    Country {
+        code,
+        dial_code,
+        trunk_prefix,
+        number_length,
+        postal_code: Regex::new(postal_code).unwrap(),
+    }
+}
+
+pub static COUNTRIES: Lazy<Vec<Country>> = Lazy::new(|| {
+    vec![
+        country("FR", "33", true, 9..=9, r"^(?:0[1-9]|[1-8]\d|9[0-8])\d{3}$"),
+        country("BE", "32", true, 8..=9, r"^[1-9]\d{3}$"),
+        country("CH", "41", true, 9..=9, r"^[1-9]\d{3}$"),
+        country("LU", "352", false, 4..=11, r"^\d{4}$"),
+        country("MC", "377", false, 8..=9, r"^980\d{2}$"),
+        country("DE", "49", true, 5..=13, r"^\d{5}$"),
+        country("AT", "43", true, 4..=13, r"^[1-9]\d{3}$"),
+        country("ES", "34", false, 9..=9, r"^(?:0[1-9]|[1-4]\d|5[0-2])\d{3}$"),
+        country("IT", "39", false, 6..=11, r"^\d{5}$"),
+        country("PT", "351", false, 9..=9, r"^\d{4}-\d{3}$"),
+        country("NL", "31", true, 9..=9, r"^[1-9]\d{3} ?[A-Z]{2}$"),
+        country("GB", "44", true, 9..=10, r"^[A-Z]{1,2}\d[A-Z\d]? ?\d[A-Z]{2}$"),
+    ]
+});
+
+/// Codes are matched regardless of case and surrounding spaces, e.g. ` fr`.
+pub fn find_country(code: &str) -> Option<&'static Country> {
+    let code = code.trim();
+    COUNTRIES.iter().find(|country| country.code.eq_ignore_ascii_case(code))
+}
+
+/// Upper-cased and trimmed, e.g. `1234 ab` becomes `1234 AB`.
+pub fn normalize_postal_code(code_postal: &str) -> String {
+    code_postal.trim().to_uppercase()
+}
+
+pub fn is_valid_postal_code(country: &Country, code_postal: &str) -> bool {
+    country.postal_code.is_match(code_postal)
+}
+
+/// E.164 form of `phone`. National numbers are read as numbers of `country`,
+/// international ones (`+` or `00`) may belong to any supported country.
+pub fn normalize_phone(country: &Country, phone: &str) -> Option<String> {
+    let compact: String = phone
+        .replace("(0)", "")
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '.' | '-' | '/' | '(' | ')'))
+        .collect();
+
+    let (country, national) = match compact.strip_prefix('+').or_else(|| compact.strip_prefix("00")) {
+        Some(digits) => {
+            let country = COUNTRIES.iter().find(|country| digits.starts_with(country.dial_code))?;
+            (country, &digits[country.dial_code.len()..])
+        }
+        None if country.trunk_prefix => (country, compact.strip_prefix('0')?),
+        None => (country, compact.as_str()),
+    };
+
+    let valid = national.chars().all(|c| c.is_ascii_digit())
+        && (!country.trunk_prefix || !national.starts_with('0'))
+        && country.number_length.contains(&national.len());
+
+    valid.then(|| format!("+{}{}", country.dial_code, national))
+}
@@ -1 +1,3 @@
-pub mod common_validators;
\ No newline at end of file
+pub mod common_validators;
+pub mod country;
+pub mod siret;
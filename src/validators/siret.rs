@@ -0,0 +1,63 @@
+/// La Poste establishments all share this SIREN and have too many of them for the Luhn key,
+/// their SIRET digits add up to a multiple of 5 instead.
+const LA_POSTE_SIREN: &str = "356000000";
+
+/// Strips the spaces used to group digits, e.g. `732 829 320 00074` becomes `73282932000074`.
+pub fn normalize_siret(siret: &str) -> String {
+    siret.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// 14 digits with a valid Luhn key, expects a normalized SIRET.
+pub fn is_valid_siret(siret: &str) -> bool {
+    if siret.len() != 14 || !siret.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let digits = siret.bytes().map(|digit| u32::from(digit - b'0'));
+    if siret.starts_with(LA_POSTE_SIREN) && digits.clone().sum::<u32>().is_multiple_of(5) {
+        return true;
+    }
+
+    let checksum: u32 = digits
+        .rev()
+        .enumerate()
+        .map(|(position, digit)| match position % 2 {
+            0 => digit,
+            _ if digit > 4 => digit * 2 - 9,
+            _ => digit * 2,
+        })
+        .sum();
+
+    checksum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_a_valid_luhn_key() {
+        assert!(is_valid_siret("73282932000074"));
+        assert!(is_valid_siret(&normalize_siret("732 829 320 00074")));
+    }
+
+    #[test]
+    fn rejects_a_wrong_key_or_format() {
+        assert!(!is_valid_siret("73282932000075"));
+        assert!(!is_valid_siret("7328293200007"));
+        assert!(!is_valid_siret("7328293200007A"));
+        assert!(!is_valid_siret("732 829 320 00074"));
+    }
+
+    #[test]
+    fn la_poste_establishments_sum_to_a_multiple_of_five() {
+        assert!(is_valid_siret("35600000049837"));
+        assert!(is_valid_siret("35600000000048"));
+        assert!(!is_valid_siret("35600000000000"));
+    }
+
+    #[test]
+    fn only_la_poste_gets_the_exception() {
+        assert!(!is_valid_siret("35700000049840"));
+    }
+}